@@ -1,50 +1,343 @@
-use crate::{MainWindow, StockData};
+use crate::{MainWindow, SimulationParams, StockData};
 use data_io::*;
 use core_sim::*;
 use slint::{SharedString, ModelRc, VecModel, Image, SharedPixelBuffer};
+use std::sync::Mutex;
 
+// Dữ liệu thị trường đã load (parse file CSV đúng 1 lần, dùng chung cho mọi callback)
+static MARKET_DATA: Mutex<Option<MarketDataStore>> = Mutex::new(None);
+
+// Sự kiện doanh nghiệp (chia tách, cổ tức) dùng để điều chỉnh giá lịch sử
+static CORPORATE_ACTIONS: Mutex<Vec<CorporateAction>> = Mutex::new(Vec::new());
+
+// Database SQLite đang mở (khi user load file .db), thay cho MARKET_DATA
+static PRICE_DB: Mutex<Option<PriceDatabase>> = Mutex::new(None);
+
+// File database tạo ra khi import CSV vào SQLite
+const PRICE_DATABASE: &str = "data/prices.db";
+
+/// Chạy `f` trên nguồn dữ liệu đang dùng: database SQLite nếu đã mở, không thì store trong bộ nhớ
+fn with_price_source<R>(
+    ticker: &str,
+    f: impl FnOnce(&dyn PriceSource) -> DataIoResult<R>,
+) -> DataIoResult<R> {
+    if let Some(db) = PRICE_DB.lock().unwrap().as_ref() {
+        return f(db);
+    }
+    match MARKET_DATA.lock().unwrap().as_ref() {
+        Some(store) => f(store),
+        None => Err(DataIoError::UnknownTicker {
+            ticker: ticker.to_string(),
+        }),
+    }
+}
+
+/// Lấy series của ticker từ nguồn dữ liệu đã load
+fn series_for_ticker(ticker: &str) -> DataIoResult<PriceSeries> {
+    with_price_source(ticker, |source| source.price_series(ticker))
+}
+
+/// Series dùng cho ước lượng / mô phỏng: đã điều chỉnh theo sự kiện doanh nghiệp nếu user bật
+fn analysis_series(ui: &MainWindow, ticker: &str) -> DataIoResult<PriceSeries> {
+    let series = series_for_ticker(ticker)?;
+    if !ui.get_adjust_prices() {
+        return Ok(series);
+    }
+    let actions = CORPORATE_ACTIONS.lock().unwrap();
+    adjust_for_corporate_actions(&series, &actions)
+}
+
+pub fn load_corporate_actions_file(ui: &MainWindow) {
+    let actions_path = ui.get_actions_path().to_string();
+
+    match load_corporate_actions(&actions_path) {
+        Ok(actions) => {
+            let count = actions.len();
+            *CORPORATE_ACTIONS.lock().unwrap() = actions;
+            ui.set_adjust_prices(true);
+            set_status(ui, &format!("Loaded {} corporate actions from {}", count, actions_path), false);
+        }
+        Err(e) => {
+            report_error(ui, &e);
+        }
+    }
+}
+
+/// Thông báo lỗi dễ hiểu cho người dùng, kèm gợi ý theo từng loại lỗi
+fn user_message(err: &DataIoError) -> String {
+    match err {
+        DataIoError::FileNotFound { path } => {
+            format!("File not found: {}. Check that the data folder is next to the app.", path)
+        }
+        DataIoError::BadHeader { .. } => {
+            format!("{}. Supported layouts: CafeF, SSI, Vietstock, Yahoo.", err)
+        }
+        DataIoError::MalformedRow { .. } | DataIoError::NonPositivePrice { .. } | DataIoError::NegativeVolume { .. } => {
+            format!("Data error: {}. Fix or remove this row and reload.", err)
+        }
+        DataIoError::InvalidCorporateAction { .. } => {
+            format!("{}. Fix the corporate actions file or untick price adjustment.", err)
+        }
+        DataIoError::InsufficientHistory { .. } => {
+            format!("{}. Choose another ticker.", err)
+        }
+        DataIoError::OutOfOrderBar { .. } | DataIoError::ConflictingBar { .. } => {
+            format!("{}. The daily file was not applied; reload the full export instead.", err)
+        }
+        DataIoError::InvalidSource { .. } => {
+            format!("{}. Enter a data file, a folder or a pattern such as data/CafeF.*.csv.", err)
+        }
+        _ => err.to_string(),
+    }
+}
+
+fn set_status(ui: &MainWindow, message: &str, is_error: bool) {
+    ui.set_status_message(message.into());
+    ui.set_status_is_error(is_error);
+}
+
+fn report_error(ui: &MainWindow, err: &DataIoError) {
+    let message = user_message(err);
+    eprintln!("{}", message);
+    set_status(ui, &message, true);
+}
+
+// Số xung đột liệt kê trên dòng trạng thái sau khi gộp nhiều file
+const MAX_CONFLICTS_SHOWN: usize = 3;
+
+// Cache nhị phân của dữ liệu đã parse (kèm các phiên đã append), tránh đọc lại CSV mỗi lần mở app
+const MARKET_DATA_CACHE: &str = "data/market_data.cache";
+
+/// Load dữ liệu từ đường dẫn trong ô nhập: 1 file, 1 thư mục hoặc glob pattern
+///
+/// Nhiều file (HSX, HNX, UPCOM, file cập nhật hằng ngày) được gộp theo ngày,
+/// phiên khác giá giữa các file lấy theo file mới hơn (xem `dir_files`).
+/// Nếu các file nguồn chưa đổi từ lần trước thì đọc thẳng từ cache.
 pub fn load_csv_file(ui: &MainWindow) {
-    
-    let csv_path = "data/CafeF.HSX.Upto10.11.2025.csv";
+    let csv_path = ui.get_csv_path().trim().to_string();
+    if is_database_path(&csv_path) {
+        open_database(ui, &csv_path);
+        return;
+    }
+
+    match MarketDataStore::load_cached(&csv_path, MARKET_DATA_CACHE, MergePolicy::PreferIncoming) {
+        Ok((store, report)) => {
+            let tickers = store.tickers();
+            let exchanges: Vec<String> = store
+                .exchange_counts()
+                .iter()
+                .map(|(exchange, count)| format!("{} {}", exchange, count))
+                .collect();
+
+            let mut message = format!("Loaded {} tickers from {}", tickers.len(), csv_path);
+            if !exchanges.is_empty() {
+                message.push_str(&format!(" ({})", exchanges.join(", ")));
+            }
+            match &report {
+                None => message.push_str(" (cached)"),
+                Some(report) => {
+                    if report.files.len() > 1 {
+                        message.push_str(&format!(". Merged {}", report.summary()));
+                    }
+                    if !report.conflicts.is_empty() {
+                        let examples: Vec<String> = report
+                            .conflicts
+                            .iter()
+                            .take(MAX_CONFLICTS_SHOWN)
+                            .map(|c| format!("{} {} close {} → {}", c.ticker, c.date, c.existing.close, c.incoming.close))
+                            .collect();
+                        let more = report.conflicts.len().saturating_sub(MAX_CONFLICTS_SHOWN);
+                        message.push_str(&format!(
+                            "; newer files were kept for {} conflicting days: {}{}",
+                            report.conflicts.len(),
+                            examples.join(", "),
+                            if more > 0 { format!(" and {} more", more) } else { String::new() }
+                        ));
+                    }
+                }
+            }
 
-    // Load danh sách ticker từ CSV
-    let tickers_result = load_available_tickers(csv_path);
+            *MARKET_DATA.lock().unwrap() = Some(store);
+            *PRICE_DB.lock().unwrap() = None;
+            show_tickers(ui, &tickers);
+            set_status(ui, &message, false);
 
-    if let Ok(tickers) = tickers_result {
-        //  Chuyển dữ liệu cho UI
-        let mut shared_tickers = Vec::new();
-        for t in &tickers {
-            shared_tickers.push(SharedString::from(t.as_str()));
+            // chọn ticker đầu tiên và load thông tin
+            if !tickers.is_empty() {
+                let default_ticker = &tickers[0];
+                ui.set_selected_ticker(default_ticker.clone().into());
+                update_stock_info(ui, default_ticker);  
+            }
+        }
+        Err(e) => {
+            report_error(ui, &e);
         }
-        let ticker_model = ModelRc::new(VecModel::from(shared_tickers));
+    }
+}
 
-        //  Cập nhật UI
-        ui.set_available_tickers(ticker_model);
-        ui.set_csv_path(csv_path.into());
-        ui.set_csv_loaded(true);
+fn is_database_path(path: &str) -> bool {
+    let path = path.to_lowercase();
+    [".db", ".sqlite", ".sqlite3"].iter().any(|ext| path.ends_with(ext))
+}
+
+/// Mở database SQLite làm nguồn dữ liệu: danh sách ticker và thông tin mã lấy bằng truy vấn có index
+fn open_database(ui: &MainWindow, db_path: &str) {
+    if !std::path::Path::new(db_path).exists() {
+        report_error(ui, &DataIoError::FileNotFound { path: db_path.to_string() });
+        return;
+    }
+
+    let opened = PriceDatabase::open(db_path).and_then(|db| {
+        let tickers = db.ticker_list()?;
+        Ok((db, tickers))
+    });
+
+    match opened {
+        Ok((db, tickers)) => {
+            *PRICE_DB.lock().unwrap() = Some(db);
+            *MARKET_DATA.lock().unwrap() = None;
+            show_tickers(ui, &tickers);
+            set_status(ui, &format!("Opened database {} with {} tickers", db_path, tickers.len()), false);
+
+            if let Some(default_ticker) = tickers.first() {
+                ui.set_selected_ticker(default_ticker.clone().into());
+                update_stock_info(ui, default_ticker);
+            }
+        }
+        Err(e) => {
+            report_error(ui, &e);
+        }
+    }
+}
+
+/// Import các file CSV trong ô đường dẫn (file, thư mục hoặc glob) vào `data/prices.db` rồi mở database
+pub fn import_to_database(ui: &MainWindow) {
+    let csv_path = ui.get_csv_path().trim().to_string();
+    if is_database_path(&csv_path) {
+        open_database(ui, &csv_path);
+        return;
+    }
+
+    // Đóng kết nối cũ trước khi ghi vào cùng file
+    *PRICE_DB.lock().unwrap() = None;
+
+    let imported = source_files(&csv_path).and_then(|files| {
+        let mut db = PriceDatabase::open(PRICE_DATABASE)?;
+        let mut rows = 0;
+        for file in &files {
+            rows += db.import_csv(&file.to_string_lossy())?;
+        }
+        Ok((files.len(), rows))
+    });
 
-        // chọn ticker đầu tiên và load thông tin
-        if !tickers.is_empty() {
-            let default_ticker = &tickers[0];
-            ui.set_selected_ticker(default_ticker.clone().into());
-            update_stock_info(ui, default_ticker);  
+    match imported {
+        Ok((files, rows)) => {
+            ui.set_csv_path(PRICE_DATABASE.into());
+            open_database(ui, PRICE_DATABASE);
+            if !ui.get_status_is_error() {
+                set_status(ui, &format!("Imported {} rows from {} files into {}", rows, files, PRICE_DATABASE), false);
+            }
+        }
+        Err(e) => {
+            report_error(ui, &e);
         }
+    }
+}
 
+/// Đưa danh sách ticker lên dropdown
+fn show_tickers(ui: &MainWindow, tickers: &[String]) {
+    //  Chuyển dữ liệu cho UI
+    let mut shared_tickers = Vec::new();
+    for t in tickers {
+        shared_tickers.push(SharedString::from(t.as_str()));
+    }
+    let ticker_model = ModelRc::new(VecModel::from(shared_tickers));
+
+    //  Cập nhật UI
+    ui.set_available_tickers(ticker_model);
+    ui.set_csv_loaded(true);
+}
+
+/// Nối file delta hằng ngày vào dữ liệu đang mở rồi cập nhật cache
+///
+/// Delta có phiên cũ hơn dữ liệu hiện có hoặc khác giá với phiên đã có sẽ bị từ chối toàn bộ
+pub fn append_delta_file(ui: &MainWindow) {
+    let delta_path = ui.get_delta_path().trim().to_string();
+
+    let result = {
+        let mut guard = MARKET_DATA.lock().unwrap();
+        match guard.as_mut() {
+            Some(store) => store.append_file(&delta_path).and_then(|report| {
+                store.save_cache(MARKET_DATA_CACHE)?;
+                Ok((report, store.tickers()))
+            }),
+            None => {
+                set_status(ui, "Load CSV or Parquet data before appending a daily file.", true);
+                return;
+            }
+        }
+    };
+
+    match result {
+        Ok((report, tickers)) => {
+            show_tickers(ui, &tickers);
+            set_status(
+                ui,
+                &format!(
+                    "Appended {} new bars from {} ({} already loaded)",
+                    report.new_bars, delta_path, report.identical_bars
+                ),
+                false,
+            );
+
+            let ticker = ui.get_selected_ticker().to_string();
+            if !ticker.is_empty() {
+                update_stock_info(ui, &ticker);
+            }
+        }
+        Err(e) => {
+            report_error(ui, &e);
+        }
+    }
+}
+
+/// Mở hộp thoại chọn file (hoặc thư mục) dữ liệu rồi load luôn
+pub fn browse_data_source(ui: &MainWindow, pick_folder: bool) {
+    let dialog = rfd::FileDialog::new().set_directory("data");
+    let picked = if pick_folder {
+        dialog.pick_folder()
     } else {
-        eprintln!("Error loading CSV: {:?} - app_logic.rs:34", tickers_result.err().unwrap());
+        dialog
+            .add_filter("Price data", &["csv", "parquet", "arrow", "ipc", "feather", "db", "sqlite"])
+            .pick_file()
+    };
+
+    // User bấm Cancel → giữ nguyên đường dẫn cũ
+    if let Some(path) = picked {
+        ui.set_csv_path(path.to_string_lossy().to_string().into());
+        load_csv_file(ui);
     }
 }
 
 /// Cập nhật thông tin ticker và hiển thị trên UI
 pub fn update_stock_info(ui: &MainWindow, ticker: &str) {
-    // Lấy csv_path từ UI (đã được set khi load CSV)
-    let csv_path = ui.get_csv_path().to_string();
+    let info = with_price_source(ticker, |source| {
+        let info = source.ticker_info(ticker)?;
+        Ok((info, source.ticker_exchange(ticker)?))
+    });
+
+    // Ước lượng cũ thuộc ticker trước, không dùng cho sai số tham số của ticker mới
+    clear_parameter_inference(ui);
+    clear_garch_fit(ui);
+
+    match info {
+        Ok(((ticker_name, date_range, record_count, last_price), exchange)) => {
+            let exchange = exchange.map(|exchange| exchange.to_string()).unwrap_or_default();
 
-    match get_stock_info(&csv_path, ticker) {
-        Ok((ticker_name, date_range, record_count, last_price)) => {
             // Cập nhật thông tin ticker trên UI
             let stock_data = StockData {
                 ticker: ticker_name.clone().into(),
+                exchange: exchange.into(),
                 date_range: date_range.into(),
                 record_count: record_count as i32,
                 last_price: last_price as f32,
@@ -57,49 +350,727 @@ pub fn update_stock_info(ui: &MainWindow, ticker: &str) {
             ui.set_sim_params(params);
 
             println!("✓ Loaded ticker: {} (Last Price: {:.2}) - app_logic.rs:59", ticker_name, last_price);
+
+            update_quality_report(ui, ticker);
         }
         Err(e) => {
-            eprintln!("Error getting stock info: {} - app_logic.rs:62", e);
+            report_error(ui, &e);
         }
     }
 }
 
+// Số dòng tối đa hiển thị trong danh sách vấn đề
+const MAX_QUALITY_ISSUES_SHOWN: usize = 200;
+
+/// Kiểm tra chất lượng dữ liệu của ticker và hiển thị danh sách vấn đề
+pub fn update_quality_report(ui: &MainWindow, ticker: &str) {
+    match analysis_series(ui, ticker) {
+        Ok(series) => {
+            let report = check_data_quality(&series, &QualityConfig::default());
+
+            let summary = if report.is_clean() {
+                format!("{}: no issues found in {} records", report.ticker, report.records)
+            } else {
+                report.summary()
+            };
+
+            let mut lines: Vec<SharedString> = report
+                .issues
+                .iter()
+                .take(MAX_QUALITY_ISSUES_SHOWN)
+                .map(|issue| SharedString::from(issue.to_string()))
+                .collect();
+            if report.issues.len() > MAX_QUALITY_ISSUES_SHOWN {
+                lines.push(format!("... and {} more", report.issues.len() - MAX_QUALITY_ISSUES_SHOWN).into());
+            }
+
+            ui.set_quality_summary(summary.into());
+            ui.set_quality_issues(ModelRc::new(VecModel::from(lines)));
+        }
+        Err(e) => {
+            report_error(ui, &e);
+        }
+    }
+}
+
+/// Cửa sổ ước lượng theo lựa chọn trên UI
+fn estimation_window(ui: &MainWindow) -> Result<EstimationWindow, String> {
+    match ui.get_window_mode().as_str() {
+        "Last N days" => {
+            let days = ui.get_lookback_days();
+            if days < 2 {
+                return Err(format!("Lookback must be at least 2 days, got {}", days));
+            }
+            Ok(EstimationWindow::LastDays(days as usize))
+        }
+        "Date range" => {
+            let parse = |text: &str| {
+                chrono::NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+                    .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", text))
+            };
+            let from = parse(&ui.get_window_from())?;
+            let to = parse(&ui.get_window_to())?;
+            if from > to {
+                return Err(format!("Start date {} is after end date {}", from, to));
+            }
+            Ok(EstimationWindow::DateRange { from, to })
+        }
+        _ => Ok(EstimationWindow::Full),
+    }
+}
+
+/// Đơn vị μ/σ đang chọn trên UI
+fn param_unit(ui: &MainWindow) -> TimeUnit {
+    TimeUnit::from_name(&ui.get_param_unit()).unwrap_or_default()
+}
+
+/// μ/σ đang hiển thị kèm đơn vị
+fn displayed_params(ui: &MainWindow) -> GbmParams {
+    let params = ui.get_sim_params();
+    GbmParams::new(params.mu as f64, params.sigma as f64, param_unit(ui))
+}
+
+fn show_params(ui: &MainWindow, gbm: GbmParams) {
+    let mut params = ui.get_sim_params();
+    params.mu = gbm.mu as f32;
+    params.sigma = gbm.sigma as f32;
+    ui.set_sim_params(params);
+    ui.set_param_unit(gbm.unit.name().into());
+    refresh_param_equivalent(ui);
+}
+
+/// Tham số Heston đang nhập, cùng đơn vị với μ/σ
+fn heston_params(params: &SimulationParams) -> HestonParams {
+    HestonParams {
+        mu: params.mu as f64,
+        v0: params.heston_v0 as f64,
+        kappa: params.heston_kappa as f64,
+        theta: params.heston_theta as f64,
+        xi: params.heston_xi as f64,
+        rho: params.heston_rho as f64,
+    }
+}
+
+/// Phân phối cú sốc đang chọn cho GBM đuôi dày (không phụ thuộc đơn vị μ/σ)
+fn innovation(params: &SimulationParams) -> Innovation {
+    match params.innovation.as_str() {
+        "Skewed-t" => Innovation::SkewedT {
+            nu: params.innovation_nu as f64,
+            lambda: params.innovation_lambda as f64,
+        },
+        "NIG" => Innovation::NormalInverseGaussian {
+            alpha: params.nig_alpha as f64,
+            beta: params.nig_beta as f64,
+        },
+        _ => Innovation::StudentT { nu: params.innovation_nu as f64 },
+    }
+}
+
+/// Đổi đơn vị μ/σ, giữ nguyên model (μ theo phiên nhân N, σ nhân √N)
+pub fn change_param_unit(ui: &MainWindow, unit_name: &str) {
+    let Some(unit) = TimeUnit::from_name(unit_name) else {
+        set_status(ui, &format!("Unknown parameter unit '{}'", unit_name), true);
+        return;
+    };
+    // λ (số jump mỗi đơn vị thời gian) và v0, κ, θ, ξ của Heston đổi tỷ lệ thuận như μ
+    let factor = unit.trading_days() / param_unit(ui).trading_days();
+    let mut params = ui.get_sim_params();
+    params.jump_lambda = (params.jump_lambda as f64 * factor) as f32;
+    let heston = heston_params(&params).rescale(factor);
+    params.heston_v0 = heston.v0 as f32;
+    params.heston_kappa = heston.kappa as f32;
+    params.heston_theta = heston.theta as f32;
+    params.heston_xi = heston.xi as f32;
+    ui.set_sim_params(params);
+    show_params(ui, displayed_params(ui).to_unit(unit));
+    // Tham số GARCH luôn theo phiên, chỉ σ hiển thị đổi theo đơn vị
+    if let Some(fit) = LAST_GARCH_FIT.lock().unwrap().as_ref() {
+        ui.set_garch_fit_summary(format_garch_fit(fit, unit).into());
+    }
+    let estimates = LAST_ROLLING_ESTIMATES.lock().unwrap();
+    if !estimates.is_empty() {
+        draw_rolling_chart(ui, &estimates, unit);
+    }
+}
+
+/// Hiện μ/σ ở dạng còn lại (theo năm nếu đang nhập theo phiên và ngược lại)
+pub fn refresh_param_equivalent(ui: &MainWindow) {
+    let params = displayed_params(ui);
+    let other = match params.unit {
+        TimeUnit::TradingDay => params.to_annual(TRADING_DAYS_252),
+        TimeUnit::Year { .. } => params.to_daily(),
+    };
+    ui.set_param_equivalent(
+        format!("= μ {:.6}, σ {:.6} ({})", other.mu, other.sigma, other.unit).into(),
+    );
+}
+
+// Mức tin cậy và số lần lấy mẫu lại cho khoảng tin cậy bootstrap của μ/σ
+const PARAM_CONFIDENCE: f64 = 0.95;
+const PARAM_BOOTSTRAP_RESAMPLES: usize = 1000;
+
+fn clear_parameter_inference(ui: &MainWindow) {
+    ui.set_param_inference("".into());
+    ui.set_estimation_sample_size(0);
+    ui.set_use_param_uncertainty(false);
+}
+
+/// Dòng SE / khoảng tin cậy / t-stat hiển thị dưới nút Estimate, theo đơn vị μ/σ đang chọn
+fn format_inference(inference: &DriftVolatilityInference, unit: TimeUnit, sigma_label: &str) -> String {
+    // μ và SE/CI của μ nhân N, σ và SE/CI của σ nhân √N
+    let n = unit.trading_days();
+    let line = |name: &str, estimate: &ParameterEstimate, scale: f64| {
+        format!(
+            "{} = {:.6} ± {:.6} (SE), {:.0}% CI [{:.6}, {:.6}]",
+            name,
+            estimate.value * scale,
+            estimate.std_error * scale,
+            estimate.interval.confidence * 100.0,
+            estimate.interval.low * scale,
+            estimate.interval.high * scale
+        )
+    };
+    format!(
+        "{}, t = {:.2}\n{}\nn = {} returns, {}",
+        line("μ", &inference.mu, n),
+        inference.t_stat,
+        line(sigma_label, &inference.sigma, n.sqrt()),
+        inference.sample_size,
+        unit
+    )
+}
+
 pub fn estimate_parameters(ui: &MainWindow) {
-    let csv_path = ui.get_csv_path();
     let ticker = ui.get_selected_ticker();
+    let estimator = VolatilityEstimator::from_name(&ui.get_vol_estimator()).unwrap_or_default();
+    let window = match estimation_window(ui) {
+        Ok(window) => window,
+        Err(message) => {
+            set_status(ui, &message, true);
+            return;
+        }
+    };
+    let lambda = ui.get_ewma_lambda() as f64;
+    if ui.get_use_ewma() && !(lambda > 0.0 && lambda < 1.0) {
+        set_status(ui, &format!("EWMA λ must be between 0 and 1, got {}", lambda), true);
+        return;
+    }
     
-    match load_closes_for_ticker(&csv_path.to_string(), &ticker.to_string()) {
-        Ok(closes) => {
-            let returns = calculate_log_returns(&closes);
-            let mu = calculate_mean(&returns);
-            let sigma = calculate_stdev(&returns, mu);
+    // Cần ít nhất 3 giá đóng cửa → 2 log-returns để tính σ (Yang-Zhang cũng cần 3 phiên)
+    let series = analysis_series(ui, &ticker)
+        .map(|series| window.apply(&series))
+        .and_then(|series| series.ensure_history(3).map(|_| series));
+
+    match series {
+        Ok(series) => {
+            // Log-returns theo thứ tự thời gian (series đã sort ngày tăng dần)
+            let returns = series.log_returns();
+            let (mu, sigma, method) = if ui.get_use_ewma() {
+                (
+                    ewma_mean(&returns, lambda),
+                    ewma_volatility(&returns, lambda),
+                    format!("EWMA λ={}", lambda),
+                )
+            } else {
+                (calculate_mean(&returns), series.volatility(estimator), estimator.name().to_string())
+            };
+            
+            // Ước lượng từ giá ngày → μ/σ theo phiên, hiển thị theo đơn vị đang chọn
+            let estimated = GbmParams::daily(mu, sigma).to_unit(param_unit(ui));
+            show_params(ui, estimated);
+
+            // SE và khoảng tin cậy tính trên log-returns (σ theo close-to-close)
+            let seed = ui.get_sim_params().seed as u64;
+            match infer_drift_volatility(&returns, PARAM_CONFIDENCE, PARAM_BOOTSTRAP_RESAMPLES, seed) {
+                Some(inference) => {
+                    let sigma_label = if ui.get_use_ewma() || estimator != VolatilityEstimator::CloseToClose {
+                        "σ (close-to-close)"
+                    } else {
+                        "σ"
+                    };
+                    ui.set_param_inference(format_inference(&inference, estimated.unit, sigma_label).into());
+                    ui.set_estimation_sample_size(inference.sample_size as i32);
+                }
+                None => clear_parameter_inference(ui),
+            }
             
+            println!("Estimated parameters: μ = {:.6}, σ = {:.6} ({}) - app_logic.rs:82", mu, sigma, method);
+            set_status(
+                ui,
+                &format!(
+                    "Estimated μ = {:.6}, σ = {:.6} ({}, {}) for {} from {} returns",
+                    estimated.mu,
+                    estimated.sigma,
+                    estimated.unit,
+                    method,
+                    ticker,
+                    returns.len()
+                ),
+                false,
+            );
+        }
+        Err(e) => {
+            report_error(ui, &e);
+        }
+    }
+}
+
+/// Ước lượng tham số Merton jump-diffusion trên cửa sổ ước lượng đang chọn và chuyển sang model này
+pub fn calibrate_jumps(ui: &MainWindow) {
+    let ticker = ui.get_selected_ticker();
+    let window = match estimation_window(ui) {
+        Ok(window) => window,
+        Err(message) => {
+            set_status(ui, &message, true);
+            return;
+        }
+    };
+
+    let series = analysis_series(ui, &ticker)
+        .map(|series| window.apply(&series))
+        .and_then(|series| series.ensure_history(4).map(|_| series));
+    let returns = match series {
+        Ok(series) => series.log_returns(),
+        Err(e) => {
+            report_error(ui, &e);
+            return;
+        }
+    };
+
+    match calibrate_jump_diffusion(&returns, DEFAULT_JUMP_THRESHOLD) {
+        Ok(estimate) => {
+            // Kết quả theo phiên, hiển thị theo đơn vị μ/σ đang chọn
+            let unit = param_unit(ui);
+            show_params(ui, GbmParams::daily(estimate.mu, estimate.sigma).to_unit(unit));
             let mut params = ui.get_sim_params();
-            params.mu = mu as f32;
-            params.sigma = sigma as f32;
+            params.jump_lambda = (estimate.lambda * unit.trading_days()) as f32;
+            params.jump_mean = estimate.jump_mean as f32;
+            params.jump_std = estimate.jump_std as f32;
+            params.model_type = "Jump Diffusion".into();
             ui.set_sim_params(params);
-            
-            println!("Estimated parameters: μ = {:.6}, σ = {:.6} - app_logic.rs:82", mu, sigma);
+            // SE / khoảng tin cậy của μ/σ theo GBM không còn khớp với tham số mới
+            clear_parameter_inference(ui);
+
+            ui.set_jump_calibration(
+                format!(
+                    "{} jumps in {} returns (λ = {:.4} per {})\nmean jump {:+.2}%, std {:.2}% (log)",
+                    estimate.jump_count,
+                    estimate.sample_size,
+                    estimate.lambda * unit.trading_days(),
+                    unit,
+                    estimate.jump_mean * 100.0,
+                    estimate.jump_std * 100.0
+                )
+                .into(),
+            );
+            set_status(ui, &format!("Calibrated jump-diffusion for {} from {} returns", ticker, estimate.sample_size), false);
         }
         Err(e) => {
-            eprintln!("Error estimating parameters: {} - app_logic.rs:85", e);
+            set_status(ui, &format!("Cannot calibrate jumps: {}", e), true);
         }
     }
 }
 
+/// Khớp hình dạng cú sốc (ν, λ, α, β) theo skewness / kurtosis của cửa sổ ước lượng và chuyển sang GBM đuôi dày
+pub fn fit_innovation_shape(ui: &MainWindow) {
+    let ticker = ui.get_selected_ticker();
+    let window = match estimation_window(ui) {
+        Ok(window) => window,
+        Err(message) => {
+            set_status(ui, &message, true);
+            return;
+        }
+    };
+
+    let series = analysis_series(ui, &ticker)
+        .map(|series| window.apply(&series))
+        .and_then(|series| series.ensure_history(MIN_FAT_TAIL_OBSERVATIONS + 1).map(|_| series));
+    let returns = match series {
+        Ok(series) => series.log_returns(),
+        Err(e) => {
+            report_error(ui, &e);
+            return;
+        }
+    };
+
+    match fit_fat_tails(&returns) {
+        Ok(fit) => {
+            let mut params = ui.get_sim_params();
+            // Student-t chỉ khớp kurtosis; skewed-t khớp cả skewness nên dùng ν riêng
+            params.innovation_nu = if params.innovation == "Skewed-t" { fit.skewed_t_nu } else { fit.student_t_nu } as f32;
+            params.innovation_lambda = fit.skewed_t_lambda as f32;
+            params.nig_alpha = fit.nig_alpha as f32;
+            params.nig_beta = fit.nig_beta as f32;
+            params.model_type = "Fat-tailed GBM".into();
+            ui.set_sim_params(params);
+
+            ui.set_innovation_fit(
+                format!(
+                    "skewness {:.3}, excess kurtosis {:.3} ({} returns)\nStudent-t ν = {:.2} | skewed-t ν = {:.2}, λ = {:.3}\nNIG α = {:.3}, β = {:.3}",
+                    fit.skewness,
+                    fit.excess_kurtosis,
+                    fit.sample_size,
+                    fit.student_t_nu,
+                    fit.skewed_t_nu,
+                    fit.skewed_t_lambda,
+                    fit.nig_alpha,
+                    fit.nig_beta
+                )
+                .into(),
+            );
+            set_status(ui, &format!("Fitted innovation shape for {} from {} returns", ticker, fit.sample_size), false);
+        }
+        Err(e) => {
+            set_status(ui, &format!("Cannot fit innovation shape: {}", e), true);
+        }
+    }
+}
+
+// Kết quả fit GARCH gần nhất của ticker đang chọn, dùng khi chạy model "GARCH"
+static LAST_GARCH_FIT: Mutex<Option<GarchFit>> = Mutex::new(None);
+const GARCH_INTEGRATED_PERSISTENCE: f64 = 0.999;
+
+fn clear_garch_fit(ui: &MainWindow) {
+    *LAST_GARCH_FIT.lock().unwrap() = None;
+    ui.set_garch_fit_summary("".into());
+}
+
+/// Tóm tắt tham số GARCH, σ hiện tại / dài hạn theo đơn vị μ/σ đang chọn
+fn format_garch_fit(fit: &GarchFit, unit: TimeUnit) -> String {
+    let sigma = |variance: f64| (variance * unit.trading_days()).sqrt() * 100.0;
+    let mut lines = vec![
+        format!("{} on {} returns", fit, fit.sample_size),
+        format!("ω = {:.3e}, α = {:.4}, β = {:.4}", fit.omega, fit.alpha, fit.beta),
+    ];
+    let mut shape = Vec::new();
+    if fit.kind == GarchKind::GjrGarch {
+        shape.push(format!("γ = {:.4}", fit.gamma));
+    }
+    if let Some(nu) = fit.nu {
+        shape.push(format!("ν = {:.2}", nu));
+    }
+    if !shape.is_empty() {
+        lines.push(shape.join(", "));
+    }
+    lines.push(match fit.half_life() {
+        Some(half_life) => format!("persistence {:.4} (half-life {:.1} days)", fit.persistence(), half_life),
+        None => format!("persistence {:.4}", fit.persistence()),
+    });
+    // Persistence ≈ 1 (gần IGARCH): phương sai gần như không hồi quy, mức dài hạn không có ý nghĩa
+    let long_run = fit
+        .long_run_variance()
+        .filter(|_| fit.persistence() < GARCH_INTEGRATED_PERSISTENCE)
+        .map_or("n/a".to_string(), |variance| format!("{:.2}%", sigma(variance)));
+    lines.push(format!("σ next day {:.2}%, long-run {} ({})", sigma(fit.next_variance), long_run, unit));
+    lines.push(format!("log L = {:.1}, AIC = {:.1}, BIC = {:.1}", fit.log_likelihood, fit.aic(), fit.bic()));
+    lines.join("\n")
+}
+
+/// Fit GARCH / GJR-GARCH bằng maximum likelihood trên cửa sổ ước lượng đang chọn và chuyển sang model này
+pub fn fit_garch_model(ui: &MainWindow) {
+    let ticker = ui.get_selected_ticker();
+    let kind = GarchKind::from_name(&ui.get_garch_kind()).unwrap_or_default();
+    let innovation = GarchInnovation::from_name(&ui.get_garch_innovation()).unwrap_or_default();
+    let window = match estimation_window(ui) {
+        Ok(window) => window,
+        Err(message) => {
+            set_status(ui, &message, true);
+            return;
+        }
+    };
+
+    let series = analysis_series(ui, &ticker)
+        .map(|series| window.apply(&series))
+        .and_then(|series| series.ensure_history(MIN_GARCH_OBSERVATIONS + 1).map(|_| series));
+    let returns = match series {
+        Ok(series) => series.log_returns(),
+        Err(e) => {
+            report_error(ui, &e);
+            return;
+        }
+    };
+
+    match fit_garch(&returns, kind, innovation) {
+        Ok(fit) => {
+            ui.set_garch_fit_summary(format_garch_fit(&fit, param_unit(ui)).into());
+            *LAST_GARCH_FIT.lock().unwrap() = Some(fit);
+            let mut params = ui.get_sim_params();
+            params.model_type = "GARCH".into();
+            ui.set_sim_params(params);
+            set_status(ui, &format!("Fitted {} for {} from {} returns", fit, ticker, fit.sample_size), false);
+        }
+        Err(e) => {
+            set_status(ui, &format!("Cannot fit GARCH: {}", e), true);
+        }
+    }
+}
+
+/// Vẽ μ/σ trên cửa sổ trượt trong khoảng dữ liệu đang chọn
+// Rolling estimates vừa vẽ, giữ lại để vẽ lại khi đổi đơn vị μ/σ
+static LAST_ROLLING_ESTIMATES: Mutex<Vec<RollingEstimate>> = Mutex::new(Vec::new());
+
+fn draw_rolling_chart(ui: &MainWindow, estimates: &[RollingEstimate], unit: TimeUnit) {
+    match crate::charts::create_rolling_params_chart(estimates, unit, 800, 360) {
+        Ok(chart_data) => {
+            let buffer = SharedPixelBuffer::clone_from_slice(&chart_data, 800, 360);
+            ui.set_rolling_image(Image::from_rgba8(buffer));
+        }
+        Err(e) => {
+            set_status(ui, &format!("Cannot draw rolling chart: {}", e), true);
+        }
+    }
+}
+
+pub fn plot_rolling_parameters(ui: &MainWindow) {
+    let ticker = ui.get_selected_ticker();
+    let estimator = VolatilityEstimator::from_name(&ui.get_vol_estimator()).unwrap_or_default();
+    let rolling_window = ui.get_rolling_window().max(0) as usize;
+    let window = match estimation_window(ui) {
+        Ok(window) => window,
+        Err(message) => {
+            set_status(ui, &message, true);
+            return;
+        }
+    };
+
+    let series = analysis_series(ui, &ticker)
+        .map(|series| window.apply(&series))
+        .and_then(|series| series.ensure_history(rolling_window.max(2) + 1).map(|_| series));
+
+    match series {
+        Ok(series) => {
+            let estimates = rolling_estimates(&series, rolling_window, estimator);
+            if estimates.is_empty() {
+                set_status(ui, "Rolling window must be at least 2 days", true);
+                return;
+            }
+
+            draw_rolling_chart(ui, &estimates, param_unit(ui));
+            *LAST_ROLLING_ESTIMATES.lock().unwrap() = estimates;
+        }
+        Err(e) => {
+            report_error(ui, &e);
+        }
+    }
+}
+
+/// 1 dòng kết luận cho kiểm định ở mức 5%
+fn verdict(test: &TestResult, rejected: &str, accepted: &str) -> String {
+    format!(
+        "{:>10.2}  p = {:.2e}  → {}",
+        test.statistic,
+        test.p_value,
+        if test.rejects(0.05) { rejected } else { accepted }
+    )
+}
+
+fn adf_verdict(result: &Option<AdfResult>) -> String {
+    match result {
+        Some(adf) => format!(
+            "{:>10.2}  5% crit {:.2}, {} lags  → {}",
+            adf.statistic,
+            adf.critical_values[1].1,
+            adf.lags,
+            if adf.rejects_unit_root(0.05) { "stationary" } else { "unit root" }
+        ),
+        None => "not enough data".to_string(),
+    }
+}
+
+/// Tóm tắt các kiểm định và gợi ý chọn model
+fn format_diagnostics(d: &ReturnDiagnostics) -> String {
+    let mut lines = vec![
+        format!(
+            "n = {} log-returns, mean {:.6}, std {:.6}, skewness {:.3}, excess kurtosis {:.3}",
+            d.n, d.mean, d.std_dev, d.skewness, d.excess_kurtosis
+        ),
+        format!("Jarque-Bera          {}", verdict(&d.jarque_bera, "not normal", "normal not rejected")),
+        format!(
+            "Ljung-Box({}) r       {}",
+            d.ljung_box_lags,
+            verdict(&d.ljung_box, "autocorrelated", "no autocorrelation")
+        ),
+        format!(
+            "Ljung-Box({}) r²      {}",
+            d.ljung_box_lags,
+            verdict(&d.ljung_box_squared, "volatility clustering", "no volatility clustering")
+        ),
+        format!("ADF log price        {}", adf_verdict(&d.adf_log_prices)),
+        format!("ADF log-returns      {}", adf_verdict(&d.adf_returns)),
+        String::new(),
+    ];
+
+    if d.jarque_bera.rejects(0.05) {
+        lines.push("• Returns are not normal: Bootstrap keeps the empirical skew and fat tails, GBM does not.".to_string());
+    } else {
+        lines.push("• Normality is not rejected: GBM is a reasonable fit.".to_string());
+    }
+    if d.ljung_box.rejects(0.05) {
+        lines.push("• Returns are autocorrelated: GBM and i.i.d. Bootstrap ignore this dependence, a block Bootstrap keeps it.".to_string());
+    }
+    if d.ljung_box_squared.rejects(0.05) {
+        lines.push("• Volatility clusters: constant-σ models understate risk after turbulent periods (try GARCH or a block Bootstrap).".to_string());
+    }
+    lines.join("\n")
+}
+
+/// Chẩn đoán phân phối log-returns trong cửa sổ ước lượng đang chọn
+pub fn show_return_diagnostics(ui: &MainWindow) {
+    let ticker = ui.get_selected_ticker();
+    let lags = ui.get_ljung_box_lags();
+    if lags < 1 {
+        set_status(ui, &format!("Ljung-Box lags must be at least 1, got {}", lags), true);
+        return;
+    }
+    let window = match estimation_window(ui) {
+        Ok(window) => window,
+        Err(message) => {
+            set_status(ui, &message, true);
+            return;
+        }
+    };
+
+    let series = analysis_series(ui, &ticker)
+        .map(|series| window.apply(&series))
+        .and_then(|series| series.ensure_history(lags as usize + 3).map(|_| series));
+
+    match series {
+        Ok(series) => {
+            let Some(diagnostics) = data_io::diagnose_returns(series.closes(), lags as usize) else {
+                set_status(ui, "Not enough returns for the diagnostics", true);
+                return;
+            };
+            ui.set_diagnostics_summary(format_diagnostics(&diagnostics).into());
+
+            let returns = series.log_returns();
+            let histogram = crate::charts::create_returns_histogram(&returns, 480, 300).map(|chart_data| {
+                let buffer = SharedPixelBuffer::clone_from_slice(&chart_data, 480, 300);
+                ui.set_returns_hist_image(Image::from_rgba8(buffer));
+            });
+            let qq_plot = crate::charts::create_qq_plot(&normal_qq_points(&returns), 480, 300).map(|chart_data| {
+                let buffer = SharedPixelBuffer::clone_from_slice(&chart_data, 480, 300);
+                ui.set_qq_image(Image::from_rgba8(buffer));
+            });
+
+            match (histogram, qq_plot) {
+                (Err(e), _) => set_status(ui, &format!("Cannot draw returns histogram: {}", e), true),
+                (_, Err(e)) => set_status(ui, &format!("Cannot draw QQ plot: {}", e), true),
+                _ => set_status(ui, &format!("Diagnosed {} returns of {}", diagnostics.n, ticker), false),
+            }
+        }
+        Err(e) => {
+            report_error(ui, &e);
+        }
+    }
+}
+
+/// Kết quả VaR / ES của lần chạy gần nhất theo từng phương pháp, dùng khi export
+static LAST_RISK_MEASURES: Mutex<Vec<(&'static str, Vec<RiskMeasure>)>> = Mutex::new(Vec::new());
+
+/// Các mức tin cậy được tick trên UI (mặc định 95% nếu không tick mức nào)
+fn risk_levels(ui: &MainWindow) -> Vec<f64> {
+    let selected = [
+        (ui.get_risk_level_90(), DEFAULT_RISK_LEVELS[0]),
+        (ui.get_risk_level_95(), DEFAULT_RISK_LEVELS[1]),
+        (ui.get_risk_level_99(), DEFAULT_RISK_LEVELS[2]),
+        (ui.get_risk_level_995(), DEFAULT_RISK_LEVELS[3]),
+    ];
+    let levels: Vec<f64> = selected
+        .iter()
+        .filter(|(checked, _)| *checked)
+        .map(|&(_, level)| level)
+        .collect();
+    if levels.is_empty() { vec![0.95] } else { levels }
+}
+
+/// Bảng VaR / ES theo giá, % và giá trị vị thế, nhóm theo mức tin cậy
+fn format_risk_table(rows: &[(&str, Vec<RiskMeasure>)], initial_price: f64, shares: f64) -> String {
+    let mut lines = vec![format!(
+        "{:<7} {:<15} {:>10} {:>8} {:>10} {:>8} {:>16} {:>16}",
+        "Level", "Method", "VaR", "VaR %", "ES", "ES %", "VaR position", "ES position"
+    )];
+    let level_count = rows.first().map_or(0, |(_, measures)| measures.len());
+    for i in 0..level_count {
+        for (j, (method, measures)) in rows.iter().enumerate() {
+            let m = &measures[i];
+            let level = if j == 0 { format!("{}%", m.confidence * 100.0) } else { String::new() };
+            lines.push(format!(
+                "{:<7} {:<15} {:>10.2} {:>7.2}% {:>10.2} {:>7.2}% {:>16.0} {:>16.0}",
+                level,
+                method,
+                m.var,
+                m.var_percent(initial_price),
+                m.es,
+                m.es_percent(initial_price),
+                m.var_position(shares),
+                m.es_position(shares)
+            ));
+        }
+    }
+    lines.push(format!("Position: {} shares × {:.2} = {:.0}", shares, initial_price, shares * initial_price));
+    lines.join("\n")
+}
+
+/// Chỉ số path của lần chạy gần nhất, dùng khi export
+static LAST_PATH_METRICS: Mutex<Option<PathMetricsSummary>> = Mutex::new(None);
+
+/// Bảng phân phối drawdown / run-up / thời gian dưới đỉnh và xác suất chạm ngưỡng
+fn format_path_metrics(metrics: &PathMetricsSummary, dt: f64) -> String {
+    let mut lines = vec![
+        format!("Based on {} paths, times in trading days", metrics.path_count),
+        format!(
+            "{:<22} {:>9} {:>9} {:>9} {:>9} {:>9}",
+            "Metric", "Mean", "P5", "Median", "P95", "Max"
+        ),
+    ];
+    let mut row = |name: &str, stats: &SummaryStats, scale: f64, suffix: &str| {
+        lines.push(format!(
+            "{:<22} {:>8.1}{} {:>8.1}{} {:>8.1}{} {:>8.1}{} {:>8.1}{}",
+            name,
+            stats.mean * scale, suffix,
+            stats.p5 * scale, suffix,
+            stats.median * scale, suffix,
+            stats.p95 * scale, suffix,
+            stats.max * scale, suffix
+        ));
+    };
+    row("Max drawdown", &metrics.max_drawdown, 100.0, "%");
+    row("Max run-up", &metrics.max_run_up, 100.0, "%");
+    row("Time under water", &metrics.time_under_water, dt, " ");
+    if let Some(recovery) = &metrics.recovery_steps {
+        row("Recovery (recovered)", recovery, dt, " ");
+    }
+    lines.push(format!("Recovered within horizon: {:.1}%", metrics.recovered_fraction * 100.0));
+
+    if let (Some(upper), Some(p)) = (metrics.barriers.upper, metrics.upper_hit_probability) {
+        lines.push(format!("P(touch upper {:.2}): {:.1}%", upper, p * 100.0));
+    }
+    if let (Some(lower), Some(p)) = (metrics.barriers.lower, metrics.lower_hit_probability) {
+        lines.push(format!("P(touch lower {:.2}): {:.1}%", lower, p * 100.0));
+    }
+    if let Some(p) = metrics.any_hit_probability {
+        lines.push(format!("P(touch either): {:.1}%  (checked at each simulated step)", p * 100.0));
+    }
+    lines.join("\n")
+}
+
+/// Từ số path này trở lên, thống kê được tính 1 lượt (Welford + t-digest) thay vì giữ mọi path
+const STREAMING_PATH_THRESHOLD: usize = 200_000;
+/// Số path giữ lại để vẽ chart và export khi chạy streaming
+const CHART_SAMPLE_PATHS: usize = 10_000;
+
 pub fn run_simulation(ui: &MainWindow) {
     ui.set_simulation_running(true);
     
     let params = ui.get_sim_params();
-    let csv_path = ui.get_csv_path();
     let ticker = ui.get_selected_ticker();
     
     // Load historical data 
     let historical_returns = if params.model_type.as_str() == "Bootstrap" {
-        match load_closes_for_ticker(&csv_path.to_string(), &ticker.to_string()) {
-            Ok(closes) => Some(calculate_log_returns(&closes)),
+        match analysis_series(ui, &ticker).and_then(|series| series.ensure_history(2).map(|_| series)) {
+            Ok(series) => Some(series.log_returns()),
             Err(e) => {
-                eprintln!("Error loading historical data: {} - app_logic.rs:102", e);
+                report_error(ui, &e);
                 ui.set_simulation_running(false);
                 return;
             }
@@ -109,16 +1080,121 @@ pub fn run_simulation(ui: &MainWindow) {
     };
     
     let model = if params.model_type.as_str() == "Bootstrap" {
+        // Tắt tự chọn thì dùng độ dài khối user nhập (mặc định i.i.d. không cần)
+        let method = BootstrapMethod::from_name(&params.bootstrap_method).unwrap_or_default();
+        let block_length = (!params.auto_block_length).then_some(params.block_length as f64);
+        if method != BootstrapMethod::Iid && block_length.is_some_and(|b| !(b.is_finite() && b >= 1.0)) {
+            set_status(ui, "Block length must be at least 1 day", true);
+            ui.set_simulation_running(false);
+            return;
+        }
         SimulationModel::Bootstrap { 
-            historical_returns: historical_returns.unwrap() 
+            historical_returns: historical_returns.unwrap(),
+            method,
+            block_length,
+        }
+    } else if params.model_type.as_str() == "Heston" {
+        let daily = heston_params(&params).rescale(1.0 / param_unit(ui).trading_days());
+        SimulationModel::Heston {
+            mu: daily.mu,
+            v0: daily.v0,
+            kappa: daily.kappa,
+            theta: daily.theta,
+            xi: daily.xi,
+            rho: daily.rho,
+            scheme: HestonScheme::from_name(&params.heston_scheme).unwrap_or_default(),
+        }
+    } else if params.model_type.as_str() == "Fat-tailed GBM" {
+        let daily = displayed_params(ui).to_daily();
+        SimulationModel::GBMWithInnovation {
+            mu: daily.mu,
+            sigma: daily.sigma,
+            innovation: innovation(&params),
+        }
+    } else if params.model_type.as_str() == "GARCH" {
+        // Tham số fit theo phiên, bắt đầu từ phương sai dự báo cho phiên kế tiếp
+        let Some(fit) = *LAST_GARCH_FIT.lock().unwrap() else {
+            set_status(ui, "Fit GARCH to the selected ticker before running the GARCH model", true);
+            ui.set_simulation_running(false);
+            return;
+        };
+        SimulationModel::Garch {
+            mu: fit.mu,
+            omega: fit.omega,
+            alpha: fit.alpha,
+            gamma: fit.gamma,
+            beta: fit.beta,
+            nu: fit.nu,
+            initial_variance: fit.next_variance,
+        }
+    } else if params.model_type.as_str() == "Jump Diffusion" {
+        // μ/σ/λ đổi về theo phiên như GBM
+        let daily = displayed_params(ui).to_daily();
+        SimulationModel::JumpDiffusion {
+            mu: daily.mu,
+            sigma: daily.sigma,
+            lambda: params.jump_lambda as f64 / param_unit(ui).trading_days(),
+            jump_mean: params.jump_mean as f64,
+            jump_std: params.jump_std as f64,
         }
     } else {
-        SimulationModel::GBM { 
-            mu: params.mu as f64, 
-            sigma: params.sigma as f64 
+        // dt tính bằng phiên nên μ/σ theo năm được đổi về theo phiên trước khi mô phỏng
+        let sample_size = ui.get_estimation_sample_size();
+        if ui.get_use_param_uncertainty() && sample_size > 1 {
+            let daily = displayed_params(ui).to_daily();
+            SimulationModel::GBMWithUncertainty {
+                mu: daily.mu,
+                sigma: daily.sigma,
+                sample_size: sample_size as usize,
+            }
+        } else {
+            displayed_params(ui).to_model()
         }
     };
     
+    let model_name = match &model {
+        SimulationModel::GBM { .. } => "GBM".to_string(),
+        SimulationModel::GBMWithUncertainty { sample_size, .. } => {
+            format!("GBM with parameter uncertainty (n = {})", sample_size)
+        }
+        SimulationModel::GBMWithInnovation { innovation, .. } => match innovation {
+            Innovation::StudentT { nu } => format!("GBM with Student-t shocks (ν = {:.2})", nu),
+            Innovation::SkewedT { nu, lambda } => {
+                format!("GBM with skewed-t shocks (ν = {:.2}, λ = {:.3})", nu, lambda)
+            }
+            Innovation::NormalInverseGaussian { alpha, beta } => {
+                format!("GBM with NIG shocks (α = {:.3}, β = {:.3})", alpha, beta)
+            }
+            Innovation::Normal => "GBM".to_string(),
+        },
+        SimulationModel::JumpDiffusion { lambda, jump_mean, jump_std, .. } => format!(
+            "Merton jump-diffusion (λ = {:.4}/day, jump {:+.2}% ± {:.2}%)",
+            lambda,
+            jump_mean * 100.0,
+            jump_std * 100.0
+        ),
+        SimulationModel::Heston { theta, xi, rho, scheme, .. } => format!(
+            "Heston {} (long-run σ = {:.2}%/day, ξ = {:.4}, ρ = {:.2})",
+            scheme.name(),
+            theta.sqrt() * 100.0,
+            xi,
+            rho
+        ),
+        SimulationModel::Garch { gamma, nu, initial_variance, .. } => format!(
+            "{} (σ₀ = {:.2}%/day{})",
+            if *gamma != 0.0 { "GJR-GARCH" } else { "GARCH" },
+            initial_variance.sqrt() * 100.0,
+            nu.map_or(String::new(), |nu| format!(", Student-t ν = {:.1}", nu))
+        ),
+        SimulationModel::Bootstrap { method: BootstrapMethod::Iid, .. } => "Bootstrap".to_string(),
+        SimulationModel::Bootstrap { historical_returns, method, block_length } => format!(
+            "{} bootstrap (block {:.1} days{})",
+            method.name(),
+            block_length.unwrap_or_else(|| automatic_block_length(historical_returns, *method)),
+            if block_length.is_none() { ", automatic" } else { "" }
+        ),
+    };
+
     let config = SimulationConfig {
         initial_price: params.initial_price as f64,
         horizon_days: params.horizon_days as usize,
@@ -129,18 +1205,93 @@ pub fn run_simulation(ui: &MainWindow) {
         seed: Some(params.seed as u64),
     };
     
-    let result = core_sim::run_simulation(config);
-    
-    // Calculate final prices
+    let initial_price = config.initial_price;
+    let streaming = config.num_paths > STREAMING_PATH_THRESHOLD;
+    let levels = risk_levels(ui);
+    let (result, stats, var95, risk, path_count) = if streaming {
+        // Quá nhiều path để giữ trong bộ nhớ: thống kê 1 lượt, chỉ giữ vài nghìn path đầu để vẽ / export
+        let start = std::time::Instant::now();
+        let summary = fold_terminal_prices(
+            &config,
+            StreamingSummary::new,
+            StreamingSummary::push,
+            StreamingSummary::merge,
+        );
+        let stats = if summary.nan_count() > 0 {
+            Err(StatsError::NanValue { index: 0 })
+        } else {
+            summary.summary()
+        };
+        let p5 = summary.quantile(0.05);
+        let risk = streaming_risk(&summary, initial_price, &levels)
+            .map(|simulated| (simulated, DistributionMoments::from_running(summary.moments())));
+        let mut result = core_sim::run_simulation(SimulationConfig {
+            num_paths: CHART_SAMPLE_PATHS,
+            ..config
+        });
+        result.execution_time_ms = start.elapsed().as_millis();
+        let path_count = summary.count() as usize;
+        (result, stats, p5.map(|p5| initial_price - p5), risk, path_count)
+    } else {
+        let result = core_sim::run_simulation(config);
+        let final_prices: Vec<f64> = result.paths.iter().map(|path| path[path.len() - 1]).collect();
+        let stats = summary_stats(&final_prices, QuantileMethod::Type7, NanPolicy::Reject);
+        let var95 = calculate_var(&final_prices, initial_price, 0.95);
+        let risk = simulated_risk(&final_prices, initial_price, &levels)
+            .map(|simulated| (simulated, DistributionMoments::from_values(&final_prices).ok()));
+        let path_count = result.paths.len();
+        (result, stats, Ok(var95), risk, path_count)
+    };
+
+    // Calculate final prices (chỉ các path đã giữ lại khi chạy streaming)
     let final_prices: Vec<f64> = result.paths.iter()
         .map(|path| path[path.len() - 1])
         .collect();
     
-    let stats = calculate_summary_stats(&final_prices);
-    let var95 = calculate_var(&final_prices, params.initial_price as f64, 0.95);
+    // Path có NaN (NanPolicy::Reject) hoặc quá ít path: báo lỗi thay vì hiện NaN
+    let summarized = stats.and_then(|stats| Ok((stats, var95?, risk?)));
+    let (stats, var95, (simulated_risk, moments)) = match summarized {
+        Ok(values) => values,
+        Err(e) => {
+            set_status(ui, &format!("Cannot summarize simulated prices: {}", e), true);
+            ui.set_simulation_running(false);
+            return;
+        }
+    };
+    // Path phân kỳ overflow → +inf không phải NaN nên kiểm tra riêng, tránh mean/σ/VaR vô hạn
+    if !(stats.mean.is_finite() && stats.std_dev.is_finite() && var95.is_finite()) {
+        set_status(ui, "Simulated prices overflowed to infinity: lower μ, σ or the horizon", true);
+        ui.set_simulation_running(false);
+        return;
+    }
+
+    // Drawdown / run-up / ngưỡng dọc theo path (khi streaming chỉ tính trên các path đã giữ lại)
+    let barriers = Barriers::from_percent(
+        initial_price,
+        ui.get_barrier_up_percent() as f64,
+        ui.get_barrier_down_percent() as f64,
+    );
+    let path_metrics = summarize_path_metrics(&result.paths, &barriers);
+    match &path_metrics {
+        Ok(metrics) => ui.set_path_metrics_summary(format_path_metrics(metrics, params.dt as f64).into()),
+        Err(e) => ui.set_path_metrics_summary(format!("Cannot compute path metrics: {}", e).into()),
+    }
+
+    if streaming {
+        set_status(
+            ui,
+            &format!(
+                "Statistics streamed over {} paths; charts and export use the first {}",
+                path_count,
+                result.paths.len()
+            ),
+            false,
+        );
+    }
     
     let summary = format!(
         "Simulation Results:\n\
+        Model: {}\n\
         Execution Time: {} ms\n\
         Number of Paths: {}\n\
         Horizon: {} days\n\n\
@@ -156,9 +1307,10 @@ pub fn run_simulation(ui: &MainWindow) {
         P75: {:.2}\n\
         P95: {:.2}\n\n\
         Risk Metrics:\n\
-        VaR95: {:.2} ({:.1}%)",
+        VaR95: {:.2} ({:.1}%){}",
+        model_name,
         result.execution_time_ms,
-        result.paths.len(),
+        path_count,
         params.horizon_days,
         stats.mean,
         stats.std_dev,
@@ -170,7 +1322,12 @@ pub fn run_simulation(ui: &MainWindow) {
         stats.p75,
         stats.p95,
         var95,
-        (var95 / params.initial_price as f64) * 100.0
+        (var95 / params.initial_price as f64) * 100.0,
+        path_metrics.as_ref().map_or(String::new(), |m| format!(
+            "\nMedian Max Drawdown: {:.1}%\nRecovered Within Horizon: {:.1}%",
+            m.max_drawdown.median * 100.0,
+            m.recovered_fraction * 100.0
+        ))
     );
     
     ui.set_summary_stats(summary.into());
@@ -187,12 +1344,27 @@ pub fn run_simulation(ui: &MainWindow) {
     ui.set_stat_p95(format!("{:.2}", stats.p95).into());
     ui.set_stat_var95(format!("{:.2} ({:.1}%)", var95, (var95 / params.initial_price as f64) * 100.0).into());
     ui.set_execution_time(format!("{} ms", result.execution_time_ms).into());
+
+    // Bảng VaR / ES: mô phỏng và 2 công thức tham số trên cùng moment của giá cuối
+    let mut risk_rows = vec![("Simulated", simulated_risk)];
+    if let Some(moments) = moments {
+        for method in ParametricMethod::ALL {
+            if let Ok(measures) = parametric_risk(&moments, initial_price, &levels, method) {
+                risk_rows.push((method.name(), measures));
+            }
+        }
+    }
+    let shares = ui.get_position_shares() as f64;
+    ui.set_risk_table(format_risk_table(&risk_rows, initial_price, shares).into());
+    *LAST_RISK_MEASURES.lock().unwrap() = risk_rows;
+    *LAST_PATH_METRICS.lock().unwrap() = path_metrics.ok();
     
     // Store data for export
     store_simulation_data(result.paths.clone(), final_prices.clone());
     
     // Generate charts
     generate_charts(ui, &result.paths, &final_prices);
+    draw_volatility_chart(ui, result.variance_paths.as_deref());
     
     ui.set_simulation_running(false);
 }
@@ -208,7 +1380,7 @@ pub fn export_csv(ui: &MainWindow) {
     
     // Export summary statistics
     let summary_filename = format!("simulation_summary_{}.csv", timestamp);
-    let summary_content = format!(
+    let mut summary_content = format!(
         "Metric,Value\n\
         Ticker,{}\n\
         Model Type,{}\n\
@@ -243,6 +1415,27 @@ pub fn export_csv(ui: &MainWindow) {
         ui.get_stat_var95(),
         ui.get_execution_time()
     );
+
+    // VaR / ES theo từng mức và phương pháp (đơn vị giá, trên 1 cổ phiếu)
+    for (method, measures) in LAST_RISK_MEASURES.lock().unwrap().iter() {
+        for m in measures {
+            let level = m.confidence * 100.0;
+            summary_content.push_str(&format!("VaR{} ({}),{:.4}\n", level, method, m.var));
+            summary_content.push_str(&format!("ES{} ({}),{:.4}\n", level, method, m.es));
+        }
+    }
+    if let Some(metrics) = LAST_PATH_METRICS.lock().unwrap().as_ref() {
+        summary_content.push_str(&format!("Median Max Drawdown,{:.4}\n", metrics.max_drawdown.median));
+        summary_content.push_str(&format!("P95 Max Drawdown,{:.4}\n", metrics.max_drawdown.p95));
+        summary_content.push_str(&format!("Median Max Run-up,{:.4}\n", metrics.max_run_up.median));
+        summary_content.push_str(&format!("Recovered Fraction,{:.4}\n", metrics.recovered_fraction));
+        if let Some(p) = metrics.upper_hit_probability {
+            summary_content.push_str(&format!("P(Touch Upper),{:.4}\n", p));
+        }
+        if let Some(p) = metrics.lower_hit_probability {
+            summary_content.push_str(&format!("P(Touch Lower),{:.4}\n", p));
+        }
+    }
     
     match std::fs::write(&summary_filename, summary_content) {
         Ok(_) => {
@@ -255,11 +1448,13 @@ pub fn export_csv(ui: &MainWindow) {
     }
     
     // Export detailed simulation paths if available
-    unsafe {
-        if let Some((paths, final_prices)) = &LAST_SIMULATION_DATA {
-            let paths_filename = format!("simulation_paths_{}.csv", timestamp);
-            if let Err(e) = export_simulation_paths(paths, &paths_filename) {
-                println!("❌ Error exporting paths CSV: {} - app_logic.rs:262", e);
+    {
+        let last_simulation = LAST_SIMULATION_DATA.lock().unwrap();
+        if let Some((paths, final_prices)) = last_simulation.as_ref() {
+            // Ghi toàn bộ paths (không giới hạn 100 như CSV) ra Parquet dạng (path, step, price)
+            let paths_filename = format!("simulation_paths_{}.parquet", timestamp);
+            if let Err(e) = write_paths_parquet(paths, &paths_filename) {
+                println!("❌ Error exporting paths Parquet: {} - app_logic.rs:262", e);
             } else {
                 println!("✅ Simulation paths exported to: {} - app_logic.rs:264", paths_filename);
             }
@@ -274,33 +1469,6 @@ pub fn export_csv(ui: &MainWindow) {
     }
 }
 
-fn export_simulation_paths(paths: &[Vec<f64>], filename: &str) -> Result<(), Box<dyn std::error::Error>> {
-    use std::io::Write;
-    
-    let mut file = std::fs::File::create(filename)?;
-    
-    // Write header
-    write!(file, "Path")?;
-    if !paths.is_empty() {
-        for day in 0..paths[0].len() {
-            write!(file, ",Day_{}", day)?;
-        }
-    }
-    writeln!(file)?;
-    
-    // Write data
-    let max_paths = paths.len().min(100);
-    for (i, path) in paths.iter().take(max_paths).enumerate() {
-        write!(file, "Path_{}", i + 1)?;
-        for &price in path {
-            write!(file, ",{:.4}", price)?;
-        }
-        writeln!(file)?;
-    }
-    
-    Ok(())
-}
-
 fn export_final_prices(final_prices: &[f64], filename: &str) -> Result<(), Box<dyn std::error::Error>> {
     use std::io::Write;
     
@@ -315,8 +1483,9 @@ fn export_final_prices(final_prices: &[f64], filename: &str) -> Result<(), Box<d
 }
 
 pub fn export_chart(_ui: &MainWindow) {
-    unsafe {
-        if let Some((paths, final_prices)) = &LAST_SIMULATION_DATA {
+    {
+        let last_simulation = LAST_SIMULATION_DATA.lock().unwrap();
+        if let Some((paths, final_prices)) = last_simulation.as_ref() {
             let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
             let mut exported_files = Vec::new();
             
@@ -452,12 +1621,7 @@ fn export_histogram_chart(final_prices: &[f64], filename: &str) -> Result<(), Bo
 
 fn generate_charts(ui: &MainWindow, paths: &[Vec<f64>], final_prices: &[f64]) {
     // Generate price paths chart
-    if let Ok(chart_data) = crate::charts::create_price_paths_chart(paths, 800, 400) {
-        // Convert Vec<u8> RGBA to SharedPixelBuffer
-        let buffer = SharedPixelBuffer::clone_from_slice(&chart_data, 800, 400);
-        let image = Image::from_rgba8(buffer);
-        ui.set_chart_image(image);
-    }
+    draw_price_chart(ui, paths);
     
     // Generate histogram
     if let Ok(histogram_data) = crate::charts::create_histogram(final_prices, 800, 400) {
@@ -468,11 +1632,95 @@ fn generate_charts(ui: &MainWindow, paths: &[Vec<f64>], final_prices: &[f64]) {
     }
 }
 
+/// Số phiên lịch sử tối thiểu / tối đa vẽ trước vùng dự báo trên fan chart
+const FAN_HISTORY_MIN_DAYS: usize = 60;
+const FAN_HISTORY_MAX_DAYS: usize = 250;
+
+/// Vẽ fan chart (dải quantile + giá lịch sử) hoặc vài path mẫu, tùy lựa chọn trên UI
+fn draw_price_chart(ui: &MainWindow, paths: &[Vec<f64>]) {
+    let chart = if ui.get_show_fan_chart() {
+        match path_quantile_bands(paths, &DEFAULT_FAN_QUANTILES) {
+            Ok(bands) => {
+                // Lịch sử dài cỡ horizon để cone dự báo nối liền với giá thật
+                let days = (ui.get_sim_params().horizon_days as usize)
+                    .clamp(FAN_HISTORY_MIN_DAYS, FAN_HISTORY_MAX_DAYS);
+                let history = analysis_series(ui, &ui.get_selected_ticker())
+                    .map(|series| series.closes()[series.len().saturating_sub(days)..].to_vec())
+                    .unwrap_or_default();
+                crate::charts::create_fan_chart(
+                    &history,
+                    &bands,
+                    ui.get_sim_params().dt as f64,
+                    "Price Fan Chart",
+                    "Price",
+                    800,
+                    400,
+                )
+            }
+            Err(e) => {
+                set_status(ui, &format!("Cannot compute quantile bands: {}", e), true);
+                return;
+            }
+        }
+    } else {
+        crate::charts::create_price_paths_chart(paths, 800, 400)
+    };
+
+    if let Ok(chart_data) = chart {
+        // Convert Vec<u8> RGBA to SharedPixelBuffer
+        let buffer = SharedPixelBuffer::clone_from_slice(&chart_data, 800, 400);
+        let image = Image::from_rgba8(buffer);
+        ui.set_chart_image(image);
+    }
+}
+
+/// Dải quantile của volatility mô phỏng theo đơn vị μ/σ đang chọn; xóa chart nếu model không có
+fn draw_volatility_chart(ui: &MainWindow, variance_paths: Option<&[Vec<f64>]>) {
+    let Some(variance_paths) = variance_paths else {
+        ui.set_volatility_image(Image::default());
+        return;
+    };
+    let unit = param_unit(ui);
+    let volatility_paths: Vec<Vec<f64>> = variance_paths
+        .iter()
+        .map(|path| path.iter().map(|v| (v * unit.trading_days()).sqrt() * 100.0).collect())
+        .collect();
+
+    let chart = path_quantile_bands(&volatility_paths, &DEFAULT_FAN_QUANTILES).map(|bands| {
+        crate::charts::create_fan_chart(
+            &[],
+            &bands,
+            ui.get_sim_params().dt as f64,
+            "Simulated Volatility",
+            &format!("σ (%, {})", unit),
+            800,
+            320,
+        )
+    });
+    match chart {
+        Ok(Ok(chart_data)) => {
+            let buffer = SharedPixelBuffer::clone_from_slice(&chart_data, 800, 320);
+            ui.set_volatility_image(Image::from_rgba8(buffer));
+        }
+        Ok(Err(e)) => set_status(ui, &format!("Cannot draw volatility chart: {}", e), true),
+        Err(e) => set_status(ui, &format!("Cannot compute volatility bands: {}", e), true),
+    }
+}
+
+/// Đổi giữa fan chart và path mẫu, vẽ lại từ kết quả mô phỏng gần nhất
+pub fn redraw_price_chart(ui: &MainWindow) {
+    let last_simulation = LAST_SIMULATION_DATA.lock().unwrap();
+    if let Some((paths, _)) = last_simulation.as_ref() {
+        draw_price_chart(ui, paths);
+    }
+}
+
 // Store simulation data for export
-static mut LAST_SIMULATION_DATA: Option<(Vec<Vec<f64>>, Vec<f64>)> = None;
+// (paths, final_prices)
+type SimulationData = (Vec<Vec<f64>>, Vec<f64>);
+
+static LAST_SIMULATION_DATA: Mutex<Option<SimulationData>> = Mutex::new(None);
 
 pub fn store_simulation_data(paths: Vec<Vec<f64>>, final_prices: Vec<f64>) {
-    unsafe {
-        LAST_SIMULATION_DATA = Some((paths, final_prices));
-    }
+    *LAST_SIMULATION_DATA.lock().unwrap() = Some((paths, final_prices));
 }
\ No newline at end of file
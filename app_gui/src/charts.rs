@@ -79,8 +79,7 @@ pub fn create_price_paths_chart(
 
             // Chuyển path thành danh sách điểm (day, price)
             let mut points = Vec::new();
-            for day in 0..path.len() {
-                let price = path[day];
+            for (day, &price) in path.iter().enumerate() {
                 points.push((day as f64, price));
             }
 
@@ -106,6 +105,113 @@ pub fn create_price_paths_chart(
 }
 
 
+/// Fan chart: các dải quantile theo ngày (giá, volatility...), nối tiếp giá trị lịch sử ở bên trái
+///
+/// Trục X tính bằng phiên với 0 là ngày bắt đầu mô phỏng; bước t của band nằm ở t · dt.
+/// Các cặp quantile đối xứng (P5–P95, P25–P75) được tô đậm dần vào trong, median vẽ nét liền.
+pub fn create_fan_chart(
+    history: &[f64],
+    bands: &data_io::QuantileBands,
+    dt: f64,
+    caption: &str,
+    y_desc: &str,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let steps = bands.steps();
+    if steps < 2 {
+        return Ok(vec![255u8; (width * height * 4) as usize]);
+    }
+
+    let temp_path = "temp_fan_chart.png";
+
+    {
+        let root = BitMapBackend::new(temp_path, (width, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let mut min_price = f64::INFINITY;
+        let mut max_price = f64::NEG_INFINITY;
+        for &price in history.iter().chain(bands.bands.iter().flatten()) {
+            if price.is_finite() {
+                min_price = min_price.min(price);
+                max_price = max_price.max(price);
+            }
+        }
+        let padding = ((max_price - min_price) * 0.05).max(1e-6);
+
+        let x_min = -(history.len().saturating_sub(1) as f64);
+        let x_max = (steps - 1) as f64 * dt;
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(caption, ("Arial", 20))
+            .margin(15)
+            .x_label_area_size(35)
+            .y_label_area_size(45)
+            .build_cartesian_2d(x_min..x_max, (min_price - padding)..(max_price + padding))?;
+
+        chart.configure_mesh()
+            .x_desc("Days (0 = start of simulation)")
+            .y_desc(y_desc)
+            .draw()?;
+
+        let day = |t: usize| t as f64 * dt;
+
+        // Tô từ cặp ngoài cùng vào trong
+        let count = bands.probabilities.len();
+        for k in 0..count / 2 {
+            let lower = &bands.bands[k];
+            let upper = &bands.bands[count - 1 - k];
+            let opacity = 0.15 + 0.2 * k as f64;
+            let polygon: Vec<(f64, f64)> = upper.iter().enumerate()
+                .map(|(t, &price)| (day(t), price))
+                .chain(lower.iter().enumerate().rev().map(|(t, &price)| (day(t), price)))
+                .collect();
+            let label = format!(
+                "P{}–P{}",
+                bands.probabilities[k] * 100.0,
+                bands.probabilities[count - 1 - k] * 100.0
+            );
+            chart.draw_series(std::iter::once(Polygon::new(polygon, BLUE.mix(opacity).filled())))?
+                .label(label)
+                .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 15, y + 5)], BLUE.mix(opacity).filled()));
+        }
+
+        if let Some(median) = bands.band(0.5) {
+            chart.draw_series(LineSeries::new(
+                median.iter().enumerate().map(|(t, &price)| (day(t), price)),
+                BLUE.stroke_width(2),
+            ))?
+            .label("Median")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 15, y)], BLUE.stroke_width(2)));
+        }
+
+        if history.len() > 1 {
+            chart.draw_series(LineSeries::new(
+                history.iter().enumerate().map(|(i, &price)| (x_min + i as f64, price)),
+                BLACK.stroke_width(2),
+            ))?
+            .label("History")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 15, y)], BLACK.stroke_width(2)));
+        }
+
+        chart.configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        root.present()?;
+    }
+
+    let img = image::open(temp_path)?;
+    let rgba_img = img.to_rgba8();
+    let buffer = rgba_img.into_raw();
+
+    let _ = std::fs::remove_file(temp_path);
+
+    Ok(buffer)
+}
+
 pub fn create_histogram(
     final_prices: &[f64],
     width: u32,
@@ -181,8 +287,7 @@ pub fn create_histogram(
             .y_desc("Frequency")
             .draw()?;
 
-        for i in 0..bin_count {
-            let count = bins[i];
+        for (i, &count) in bins.iter().enumerate() {
 
             // Tính tọa độ của cột
             let x0 = min_price + (i as f64) * bin_width;  // Bên trái
@@ -209,3 +314,223 @@ pub fn create_histogram(
 
     Ok(buffer)
 }
+
+/// Rolling μ/σ (ước lượng theo phiên) đổi sang `unit`: μ×N, σ×√N với N = số phiên của đơn vị
+pub fn create_rolling_params_chart(
+    estimates: &[data_io::RollingEstimate],
+    unit: core_sim::TimeUnit,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if estimates.len() < 2 {
+        return Ok(vec![255u8; (width * height * 4) as usize]);
+    }
+
+    let temp_path = "temp_rolling.png";
+
+    {
+        let root = BitMapBackend::new(temp_path, (width, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        // 2 chart chồng nhau: μ ở trên, σ ở dưới, cùng trục thời gian
+        let (upper, lower) = root.split_vertically(height / 2);
+
+        let n = unit.trading_days();
+        let mu_label = format!("μ ({})", unit);
+        let sigma_label = format!("σ ({})", unit);
+        draw_rolling_panel(&upper, estimates, &mu_label, |e| e.mu * n, BLUE)?;
+        draw_rolling_panel(&lower, estimates, &sigma_label, |e| e.sigma * n.sqrt(), RED)?;
+
+        root.present()?;
+    }
+
+    let img = image::open(temp_path)?;
+    let rgba_img = img.to_rgba8();
+    let buffer = rgba_img.into_raw();
+
+    let _ = std::fs::remove_file(temp_path);
+
+    Ok(buffer)
+}
+
+/// Vẽ 1 đại lượng (μ hoặc σ) của rolling estimates theo ngày
+fn draw_rolling_panel(
+    area: &DrawingArea<BitMapBackend, plotters::coord::Shift>,
+    estimates: &[data_io::RollingEstimate],
+    label: &str,
+    value: impl Fn(&data_io::RollingEstimate) -> f64,
+    color: RGBColor,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_date = estimates[0].date;
+    let last_date = estimates[estimates.len() - 1].date;
+
+    let mut min_value = f64::INFINITY;
+    let mut max_value = f64::NEG_INFINITY;
+    for estimate in estimates {
+        min_value = min_value.min(value(estimate));
+        max_value = max_value.max(value(estimate));
+    }
+    // Tránh trục Y có độ dài 0 khi giá trị không đổi
+    let padding = ((max_value - min_value) * 0.05).max(1e-6);
+
+    let mut chart = ChartBuilder::on(area)
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(60)
+        .build_cartesian_2d(first_date..last_date, (min_value - padding)..(max_value + padding))?;
+
+    chart.configure_mesh()
+        .y_desc(label)
+        .x_labels(6)
+        .y_label_formatter(&|v| format!("{:.4}", v))
+        .draw()?;
+
+    chart.draw_series(LineSeries::new(
+        estimates.iter().map(|e| (e.date, value(e))),
+        &color,
+    ))?;
+
+    Ok(())
+}
+
+/// Histogram log-returns (theo mật độ) chồng với phân phối chuẩn cùng mean/std
+pub fn create_returns_histogram(
+    returns: &[f64],
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if returns.len() < 2 {
+        return Ok(vec![255u8; (width * height * 4) as usize]);
+    }
+
+    let temp_path = "temp_returns_hist.png";
+
+    {
+        let root = BitMapBackend::new(temp_path, (width, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let mean = data_io::calculate_mean(returns);
+        let std_dev = data_io::calculate_stdev(returns, mean);
+        let min_return = data_io::find_min(returns);
+        let max_return = data_io::find_max(returns);
+
+        let bin_count = 50;
+        let bin_width = (max_return - min_return) / bin_count as f64;
+        if bin_width <= 0.0 || std_dev <= 0.0 {
+            return Ok(vec![255u8; (width * height * 4) as usize]);
+        }
+
+        let mut bins = vec![0usize; bin_count];
+        for &r in returns {
+            let bin_index = (((r - min_return) / bin_width).floor() as usize).min(bin_count - 1);
+            bins[bin_index] += 1;
+        }
+
+        // Đổi số đếm sang mật độ để so sánh trực tiếp với hàm mật độ chuẩn
+        let scale = 1.0 / (returns.len() as f64 * bin_width);
+        let density = |x: f64| {
+            (-0.5 * ((x - mean) / std_dev).powi(2)).exp()
+                / (std_dev * (2.0 * std::f64::consts::PI).sqrt())
+        };
+        let max_density = bins
+            .iter()
+            .map(|&count| count as f64 * scale)
+            .fold(density(mean), f64::max);
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Log-returns vs Normal", ("Arial", 18))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(45)
+            .build_cartesian_2d(min_return..max_return, 0.0..max_density * 1.05)?;
+
+        chart.configure_mesh()
+            .x_desc("Log-return")
+            .y_desc("Density")
+            .x_labels(6)
+            .x_label_formatter(&|v| format!("{:.3}", v))
+            .draw()?;
+
+        chart.draw_series(bins.iter().enumerate().map(|(i, &count)| {
+            let x0 = min_return + i as f64 * bin_width;
+            Rectangle::new(
+                [(x0, 0.0), (x0 + bin_width, count as f64 * scale)],
+                BLUE.mix(0.5).filled(),
+            )
+        }))?;
+
+        let points = 200;
+        chart.draw_series(LineSeries::new(
+            (0..=points).map(|i| {
+                let x = min_return + (max_return - min_return) * i as f64 / points as f64;
+                (x, density(x))
+            }),
+            RED.stroke_width(2),
+        ))?;
+
+        root.present()?;
+    }
+
+    let img = image::open(temp_path)?;
+    let rgba_img = img.to_rgba8();
+    let buffer = rgba_img.into_raw();
+
+    let _ = std::fs::remove_file(temp_path);
+
+    Ok(buffer)
+}
+
+/// QQ-plot returns đã chuẩn hóa so với N(0,1); điểm lệch khỏi đường y = x ở 2 đầu là đuôi dày
+pub fn create_qq_plot(
+    points: &[(f64, f64)],
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if points.len() < 2 {
+        return Ok(vec![255u8; (width * height * 4) as usize]);
+    }
+
+    let temp_path = "temp_qq.png";
+
+    {
+        let root = BitMapBackend::new(temp_path, (width, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        // Cùng khoảng cho 2 trục để đường y = x nằm chéo
+        let limit = points
+            .iter()
+            .flat_map(|&(x, y)| [x.abs(), y.abs()])
+            .filter(|v| v.is_finite())
+            .fold(1.0, f64::max)
+            * 1.05;
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Normal QQ-plot", ("Arial", 18))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(45)
+            .build_cartesian_2d(-limit..limit, -limit..limit)?;
+
+        chart.configure_mesh()
+            .x_desc("Normal quantile")
+            .y_desc("Standardized return")
+            .draw()?;
+
+        chart.draw_series(LineSeries::new([(-limit, -limit), (limit, limit)], RED.stroke_width(2)))?;
+        chart.draw_series(
+            points
+                .iter()
+                .map(|&(x, y)| Circle::new((x, y), 2, BLUE.mix(0.6).filled())),
+        )?;
+
+        root.present()?;
+    }
+
+    let img = image::open(temp_path)?;
+    let rgba_img = img.to_rgba8();
+    let buffer = rgba_img.into_raw();
+
+    let _ = std::fs::remove_file(temp_path);
+
+    Ok(buffer)
+}
@@ -20,8 +20,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         seed: 42,
         use_antithetic: false,
         model_type: "GBM".into(),
+        jump_lambda: 0.02,
+        jump_mean: -0.03,
+        jump_std: 0.05,
+        heston_v0: 0.0004,
+        heston_kappa: 0.008,
+        heston_theta: 0.0004,
+        heston_xi: 0.002,
+        heston_rho: -0.5,
+        heston_scheme: "QE".into(),
+        innovation: "Student-t".into(),
+        innovation_nu: 5.0,
+        innovation_lambda: -0.1,
+        nig_alpha: 1.5,
+        nig_beta: -0.2,
+        bootstrap_method: "i.i.d.".into(),
+        block_length: 10.0,
+        auto_block_length: true,
     };
     ui.set_sim_params(default_params);
+    app_logic::refresh_param_equivalent(&ui);
     
     // Setup callbacks
     let ui_handle = ui.as_weak();
@@ -30,6 +48,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         app_logic::load_csv_file(&ui);
     });
 
+    let ui_handle = ui.as_weak();
+    ui.on_browse_csv_clicked(move |pick_folder| {
+        let ui = ui_handle.unwrap();
+        app_logic::browse_data_source(&ui, pick_folder);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_import_db_clicked(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::import_to_database(&ui);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_append_delta_clicked(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::append_delta_file(&ui);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_load_actions_clicked(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::load_corporate_actions_file(&ui);
+    });
+
     // Callback khi user chọn ticker từ dropdown
     let ui_handle = ui.as_weak();
     ui.on_ticker_selected(move |ticker_name| {
@@ -40,12 +82,69 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         app_logic::update_stock_info(&ui, &ticker_name);
     });
 
+    let ui_handle = ui.as_weak();
+    ui.on_check_quality_clicked(move || {
+        let ui = ui_handle.unwrap();
+        let ticker = ui.get_selected_ticker();
+        app_logic::update_quality_report(&ui, &ticker);
+    });
+
     let ui_handle = ui.as_weak();
     ui.on_estimate_params_clicked(move || {
         let ui = ui_handle.unwrap();
         app_logic::estimate_parameters(&ui);
     });
+
+    let ui_handle = ui.as_weak();
+    ui.on_calibrate_jumps_clicked(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::calibrate_jumps(&ui);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_fit_garch_clicked(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::fit_garch_model(&ui);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_fit_innovation_clicked(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::fit_innovation_shape(&ui);
+    });
     
+    let ui_handle = ui.as_weak();
+    ui.on_plot_rolling_clicked(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::plot_rolling_parameters(&ui);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_diagnose_returns_clicked(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::show_return_diagnostics(&ui);
+    });
+
+    // Fan chart ↔ path mẫu
+    let ui_handle = ui.as_weak();
+    ui.on_price_chart_mode_changed(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::redraw_price_chart(&ui);
+    });
+
+    // Đổi đơn vị μ/σ (theo phiên / theo năm) → quy đổi giá trị đang hiển thị
+    let ui_handle = ui.as_weak();
+    ui.on_param_unit_selected(move |unit| {
+        let ui = ui_handle.unwrap();
+        app_logic::change_param_unit(&ui, &unit);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.on_params_edited(move || {
+        let ui = ui_handle.unwrap();
+        app_logic::refresh_param_equivalent(&ui);
+    });
+
     let ui_handle = ui.as_weak();
     ui.on_run_simulation_clicked(move || {
         let ui = ui_handle.unwrap();
@@ -60,4 +60,198 @@ pub fn simulate_multiple_paths_bootstrap(
             simulate_one_path_bootstrap(initial_price, historical_returns, days, seed)
         })
         .collect()
-}
\ No newline at end of file
+}
+
+/// Cách lấy mẫu lại returns lịch sử
+///
+/// Các biến thể theo khối chép nguyên đoạn returns liên tiếp nên giữ được tự tương quan
+/// và volatility clustering trong phạm vi 1 khối, điều mà lấy mẫu từng phiên làm mất.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BootstrapMethod {
+    /// Từng phiên độc lập (i.i.d.)
+    #[default]
+    Iid,
+    /// Künsch (1989): khối b phiên liên tiếp, bắt đầu ngẫu nhiên trong [0, n - b]
+    MovingBlock,
+    /// Như moving block nhưng nối vòng cuối mẫu về đầu, mọi phiên có cùng xác suất được chọn
+    CircularBlock,
+    /// Politis & Romano (1994): độ dài khối ngẫu nhiên ~ Geometric(1/b), nối vòng;
+    /// path mô phỏng là chuỗi dừng
+    Stationary,
+}
+
+impl BootstrapMethod {
+    pub const ALL: [BootstrapMethod; 4] = [
+        BootstrapMethod::Iid,
+        BootstrapMethod::MovingBlock,
+        BootstrapMethod::CircularBlock,
+        BootstrapMethod::Stationary,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BootstrapMethod::Iid => "i.i.d.",
+            BootstrapMethod::MovingBlock => "Moving block",
+            BootstrapMethod::CircularBlock => "Circular block",
+            BootstrapMethod::Stationary => "Stationary",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        BootstrapMethod::ALL
+            .into_iter()
+            .find(|method| method.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Simulate 1 path bootstrap theo `method` với độ dài khối (trung bình với Stationary)
+/// `block_length`, được kẹp trong [1, n]
+///
+/// `BootstrapMethod::Iid` cho đúng path của `simulate_one_path_bootstrap` cùng seed.
+/// Không có returns lịch sử, hoặc độ dài khối không hữu hạn / nhỏ hơn 1 với các biến thể
+/// theo khối, thì path chỉ gồm giá ban đầu.
+pub fn simulate_one_path_block_bootstrap(
+    initial_price: f64,
+    historical_returns: &[f64],
+    method: BootstrapMethod,
+    block_length: f64,
+    days: usize,
+    seed: u64,
+) -> Vec<f64> {
+    let n = historical_returns.len();
+    let valid_block = block_length.is_finite() && block_length >= 1.0;
+    if n == 0 || (method != BootstrapMethod::Iid && !valid_block) {
+        return vec![initial_price];
+    }
+    if method == BootstrapMethod::Iid {
+        return simulate_one_path_bootstrap(initial_price, historical_returns, days, seed);
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let block_length = block_length.min(n as f64);
+    let fixed_length = (block_length.round() as usize).clamp(1, n);
+    // Xác suất bắt đầu khối mới ở mỗi phiên của stationary bootstrap
+    let restart_probability = 1.0 / block_length;
+
+    let mut prices = Vec::with_capacity(days + 1);
+    prices.push(initial_price);
+    let mut index = 0;
+    for step in 0..days {
+        let new_block = match method {
+            BootstrapMethod::Stationary => step == 0 || rng.gen::<f64>() < restart_probability,
+            _ => step % fixed_length == 0,
+        };
+        index = if new_block {
+            match method {
+                BootstrapMethod::MovingBlock => rng.gen_range(0..=n - fixed_length),
+                _ => rng.gen_range(0..n),
+            }
+        } else {
+            // Moving block không bao giờ vượt quá cuối mẫu vì khối bắt đầu tối đa ở n - b
+            (index + 1) % n
+        };
+
+        let next_price = prices[prices.len() - 1] * historical_returns[index].exp();
+        prices.push(next_price);
+    }
+
+    prices
+}
+
+/// Simulate nhiều paths bootstrap theo khối song song, seed = base_seed + index
+pub fn simulate_multiple_paths_block_bootstrap(
+    initial_price: f64,
+    historical_returns: &[f64],
+    method: BootstrapMethod,
+    block_length: f64,
+    days: usize,
+    num_paths: usize,
+    base_seed: u64,
+) -> Vec<Vec<f64>> {
+    (0..num_paths)
+        .into_par_iter()
+        .map(|i| {
+            simulate_one_path_block_bootstrap(
+                initial_price,
+                historical_returns,
+                method,
+                block_length,
+                days,
+                base_seed + i as u64,
+            )
+        })
+        .collect()
+}
+
+/// Độ dài khối tối ưu của Politis & White (2004), đã sửa theo Patton, Politis & White (2009)
+///
+/// Áp dụng cho cả returns và bình phương returns rồi lấy giá trị lớn hơn: tự tương quan
+/// của returns thường rất yếu, còn volatility clustering nằm ở tự tương quan của r².
+/// Kết quả nằm trong [1, min(3√n, n/3)]; với `BootstrapMethod::Iid` luôn là 1.
+pub fn automatic_block_length(historical_returns: &[f64], method: BootstrapMethod) -> f64 {
+    if method == BootstrapMethod::Iid || historical_returns.len() < 8 {
+        return 1.0;
+    }
+    let squared: Vec<f64> = historical_returns.iter().map(|r| r * r).collect();
+    politis_white_block_length(historical_returns, method)
+        .max(politis_white_block_length(&squared, method))
+}
+
+fn politis_white_block_length(values: &[f64], method: BootstrapMethod) -> f64 {
+    let n = values.len();
+    let nf = n as f64;
+    let mean = values.iter().sum::<f64>() / nf;
+    let centered: Vec<f64> = values.iter().map(|x| x - mean).collect();
+    let autocovariance = |lag: usize| {
+        centered[..n - lag]
+            .iter()
+            .zip(&centered[lag..])
+            .map(|(a, b)| a * b)
+            .sum::<f64>()
+            / nf
+    };
+    let variance = autocovariance(0);
+    let max_block = (3.0 * nf.sqrt()).min(nf / 3.0).max(1.0);
+    if variance <= 0.0 {
+        return 1.0;
+    }
+
+    // Lag nhỏ nhất m sau đó K_N tự tương quan liên tiếp đều không có ý nghĩa
+    let consecutive = 5usize.max(nf.log10().ceil() as usize);
+    let max_lag = ((nf.sqrt().ceil() as usize) + consecutive).min(n - 1);
+    let threshold = 2.0 * (nf.log10() / nf).sqrt();
+    let correlations: Vec<f64> = (0..=max_lag)
+        .map(|lag| autocovariance(lag) / variance)
+        .collect();
+    let insignificant = |lag: usize| correlations[lag].abs() < threshold;
+    let cutoff = (0..max_lag)
+        .find(|&m| (m + 1..=(m + consecutive).min(max_lag)).all(insignificant))
+        .unwrap_or(max_lag);
+    let bandwidth = (2 * cutoff).clamp(1, max_lag);
+
+    // Lag window flat-top: 1 tới M/2, giảm tuyến tính về 0 tại M
+    let window = |lag: usize| {
+        let t = lag as f64 / bandwidth as f64;
+        if t <= 0.5 {
+            1.0
+        } else {
+            2.0 * (1.0 - t)
+        }
+    };
+    let (mut g, mut spectrum) = (0.0, variance);
+    for (lag, correlation) in correlations.iter().enumerate().take(bandwidth + 1).skip(1) {
+        let weighted = window(lag) * correlation * variance;
+        g += 2.0 * lag as f64 * weighted;
+        spectrum += 2.0 * weighted;
+    }
+    if spectrum <= 0.0 {
+        return 1.0;
+    }
+
+    // b = (2G² / D)^(1/3) n^(1/3) với D = 2ĝ² (stationary) hoặc 4ĝ²/3 (circular / moving block)
+    let d = match method {
+        BootstrapMethod::Stationary => 2.0 * spectrum * spectrum,
+        _ => 4.0 / 3.0 * spectrum * spectrum,
+    };
+    ((2.0 * g * g / d).cbrt() * nf.cbrt()).clamp(1.0, max_block)
+}
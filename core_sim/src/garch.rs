@@ -0,0 +1,101 @@
+use rand::distributions::Distribution;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+
+use crate::innovations::Innovation;
+
+/// Tham số GJR-GARCH(1,1) theo phiên (γ = 0 là GARCH(1,1) thường)
+///
+/// rₜ = μ + εₜ, εₜ = σₜ zₜ, σ²ₜ₊₁ = ω + (α + γ·1[εₜ < 0]) ε²ₜ + β σ²ₜ,
+/// với zₜ chuẩn hoặc Student-t đã chuẩn hóa về phương sai 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GarchParams {
+    /// Trung bình log-return mỗi phiên
+    pub mu: f64,
+    pub omega: f64,
+    pub alpha: f64,
+    /// Phần cộng thêm vào α khi cú sốc âm (leverage effect)
+    pub gamma: f64,
+    pub beta: f64,
+    /// Bậc tự do của innovation Student-t, None là phân phối chuẩn
+    pub nu: Option<f64>,
+}
+
+impl GarchParams {
+    /// α + γ/2 + β, < 1 thì phương sai hồi quy về mức dài hạn
+    pub fn persistence(&self) -> f64 {
+        self.alpha + 0.5 * self.gamma + self.beta
+    }
+
+    /// Phương sai dài hạn ω / (1 - persistence), None nếu không dừng
+    pub fn long_run_variance(&self) -> Option<f64> {
+        let persistence = self.persistence();
+        (persistence < 1.0).then(|| self.omega / (1.0 - persistence))
+    }
+
+    /// Phân phối của zₜ tương ứng với `nu`
+    pub fn innovation(&self) -> Innovation {
+        self.nu
+            .map_or(Innovation::Normal, |nu| Innovation::StudentT { nu })
+    }
+}
+
+/// Simulate 1 path GARCH với seed cố định, bắt đầu từ phương sai `initial_variance`
+/// của phiên đầu tiên (thường là dự báo 1 bước từ mô hình đã fit)
+///
+/// Mỗi bước là 1 phiên: GARCH định nghĩa trên returns ngày nên không dùng `dt`.
+/// Trả về (giá, phương sai) cùng độ dài `days + 1`; phương sai ở vị trí t là σ² của
+/// return từ t đến t + 1, phần tử cuối là dự báo cho phiên sau kỳ mô phỏng.
+pub fn simulate_one_path_garch(
+    initial_price: f64,
+    params: &GarchParams,
+    initial_variance: f64,
+    days: usize,
+    seed: u64,
+) -> (Vec<f64>, Vec<f64>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let sampler = params.innovation().sampler();
+
+    let mut prices = Vec::with_capacity(days + 1);
+    let mut variances = Vec::with_capacity(days + 1);
+    prices.push(initial_price);
+    let mut variance = initial_variance.max(0.0);
+    variances.push(variance);
+
+    for _ in 0..days {
+        let z = sampler.sample(&mut rng);
+        let shock = variance.sqrt() * z;
+        let leverage = if shock < 0.0 { params.gamma } else { 0.0 };
+
+        prices.push(prices[prices.len() - 1] * (params.mu + shock).exp());
+        variance =
+            params.omega + (params.alpha + leverage) * shock * shock + params.beta * variance;
+        variances.push(variance);
+    }
+
+    (prices, variances)
+}
+
+/// Simulate nhiều paths GARCH song song, seed = base_seed + index
+pub fn simulate_multiple_paths_garch(
+    initial_price: f64,
+    params: &GarchParams,
+    initial_variance: f64,
+    days: usize,
+    num_paths: usize,
+    base_seed: u64,
+) -> Vec<(Vec<f64>, Vec<f64>)> {
+    (0..num_paths)
+        .into_par_iter()
+        .map(|i| {
+            simulate_one_path_garch(
+                initial_price,
+                params,
+                initial_variance,
+                days,
+                base_seed + i as u64,
+            )
+        })
+        .collect()
+}
@@ -1,9 +1,11 @@
 use rand::distributions::Distribution;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
-use rand_distr::StandardNormal;
+use rand_distr::{ChiSquared, StandardNormal};
 use rayon::prelude::*;
 
+use crate::innovations::Innovation;
+
 
 /// Simulate một path GBM với seed cố định
 ///
@@ -79,6 +81,7 @@ pub fn simulate_multiple_paths(
 /// Kỹ thuật: Với mỗi số random Z, tạo 2 paths:
 /// - Path 1 dùng +Z
 /// - Path 2 dùng -Z (antithetic = đối nghịch)
+///
 /// → Giảm variance, kết quả chính xác hơn với cùng số paths
 pub fn simulate_with_antithetic(
     initial_price: f64,
@@ -96,36 +99,174 @@ pub fn simulate_with_antithetic(
     for pair_index in 0..half_paths {
         // Mỗi cặp paths có seed riêng
         let seed = base_seed + pair_index as u64;
-        let mut rng = StdRng::seed_from_u64(seed);
+        let (prices1, prices2) = simulate_antithetic_pair(initial_price, mu, sigma, days, dt, seed);
+
+        // Thêm cả 2 paths vào kết quả
+        all_paths.push(prices1);
+        all_paths.push(prices2);
+    }
+
+    all_paths
+}
 
-        // 2 paths bắt đầu từ cùng giá
-        let mut prices1 = vec![initial_price];
-        let mut prices2 = vec![initial_price];
+/// 1 cặp paths antithetic (+Z và -Z) từ cùng 1 seed
+pub fn simulate_antithetic_pair(
+    initial_price: f64,
+    mu: f64,
+    sigma: f64,
+    days: usize,
+    dt: f64,
+    seed: u64,
+) -> (Vec<f64>, Vec<f64>) {
+    let mut rng = StdRng::seed_from_u64(seed);
 
-        // Mô phỏng từng ngày
-        for _ in 0..days {
-            // Lấy 1 số random Z
-            let z: f64 = StandardNormal.sample(&mut rng);
+    // 2 paths bắt đầu từ cùng giá
+    let mut prices1 = vec![initial_price];
+    let mut prices2 = vec![initial_price];
 
-            // Tính các thành phần chung
-            let drift = (mu - 0.5 * sigma.powi(2)) * dt;
-            let random_term = sigma * dt.sqrt();
+    // Mô phỏng từng ngày
+    for _ in 0..days {
+        // Lấy 1 số random Z
+        let z: f64 = StandardNormal.sample(&mut rng);
 
-            // Path 1: dùng +Z
-            let current1 = prices1[prices1.len() - 1];
-            let next1 = current1 * (drift + random_term * z).exp();
-            prices1.push(next1);
+        // Tính các thành phần chung
+        let drift = (mu - 0.5 * sigma.powi(2)) * dt;
+        let random_term = sigma * dt.sqrt();
 
-            // Path 2: dùng -Z (antithetic)
-            let current2 = prices2[prices2.len() - 1];
-            let next2 = current2 * (drift + random_term * (-z)).exp();
-            prices2.push(next2);
-        }
+        // Path 1: dùng +Z
+        let current1 = prices1[prices1.len() - 1];
+        let next1 = current1 * (drift + random_term * z).exp();
+        prices1.push(next1);
 
-        // Thêm cả 2 paths vào kết quả
-        all_paths.push(prices1);
-        all_paths.push(prices2);
+        // Path 2: dùng -Z (antithetic)
+        let current2 = prices2[prices2.len() - 1];
+        let next2 = current2 * (drift + random_term * (-z)).exp();
+        prices2.push(next2);
     }
 
-    all_paths
-}
\ No newline at end of file
+    (prices1, prices2)
+}
+/// μ/σ theo phiên ước lượng từ `sample_size` log-returns
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EstimatedParams {
+    pub mu: f64,
+    pub sigma: f64,
+    pub sample_size: usize,
+}
+
+impl EstimatedParams {
+    /// Rút μ/σ cho 1 path từ phân phối lấy mẫu của ước lượng
+    ///
+    /// Theo posterior chuẩn với prior không thông tin:
+    /// σ² = σ̂² · (n-1) / χ²(n-1), rồi μ ~ N(μ̂, σ / √n).
+    pub fn draw<R: rand::Rng>(&self, rng: &mut R) -> (f64, f64) {
+        let dof = (self.sample_size.max(2) - 1) as f64;
+        let chi_squared: f64 = ChiSquared::new(dof).unwrap().sample(rng);
+        let sigma = self.sigma * (dof / chi_squared).sqrt();
+
+        let z: f64 = StandardNormal.sample(rng);
+        let mu = self.mu + sigma / (self.sample_size.max(1) as f64).sqrt() * z;
+
+        (mu, sigma)
+    }
+}
+
+/// Simulate 1 path GBM có tính sai số ước lượng: μ/σ được rút riêng cho path trước khi mô phỏng
+pub fn simulate_one_path_with_uncertainty(
+    initial_price: f64,
+    params: &EstimatedParams,
+    days: usize,
+    dt: f64,
+    seed: u64,
+) -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mu, sigma) = params.draw(&mut rng);
+
+    let drift = (mu - 0.5 * sigma.powi(2)) * dt;
+    let random_term = sigma * dt.sqrt();
+
+    let mut prices = Vec::with_capacity(days + 1);
+    prices.push(initial_price);
+    for _ in 0..days {
+        let z: f64 = StandardNormal.sample(&mut rng);
+        let next_price = prices[prices.len() - 1] * (drift + random_term * z).exp();
+        prices.push(next_price);
+    }
+
+    prices
+}
+
+/// Simulate nhiều paths GBM có sai số tham số song song, seed = base_seed + index
+///
+/// Phân phối giá cuối rộng hơn GBM thường, nhất là khi ít dữ liệu và horizon dài
+/// (sai số của μ cộng dồn theo horizon).
+pub fn simulate_multiple_paths_with_uncertainty(
+    initial_price: f64,
+    params: &EstimatedParams,
+    days: usize,
+    dt: f64,
+    num_paths: usize,
+    base_seed: u64,
+) -> Vec<Vec<f64>> {
+    (0..num_paths)
+        .into_par_iter()
+        .map(|i| {
+            let seed = base_seed + i as u64;
+            simulate_one_path_with_uncertainty(initial_price, params, days, dt, seed)
+        })
+        .collect()
+}
+
+/// μ/σ theo phiên và phân phối cú sốc của GBM đuôi dày
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FatTailParams {
+    pub mu: f64,
+    pub sigma: f64,
+    pub innovation: Innovation,
+}
+
+/// Simulate 1 path GBM với cú sốc theo `params.innovation` thay cho N(0,1)
+///
+/// z đã chuẩn hóa trung bình 0, phương sai 1 nên log-return mỗi bước vẫn có trung bình
+/// (μ - σ²/2)Δt và độ lệch chuẩn σ√Δt; với `Innovation::Normal` path trùng `simulate_one_path`.
+pub fn simulate_one_path_with_innovation(
+    initial_price: f64,
+    params: &FatTailParams,
+    days: usize,
+    dt: f64,
+    seed: u64,
+) -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let sampler = params.innovation.sampler();
+
+    let drift = (params.mu - 0.5 * params.sigma.powi(2)) * dt;
+    let random_term = params.sigma * dt.sqrt();
+
+    let mut prices = Vec::with_capacity(days + 1);
+    prices.push(initial_price);
+    for _ in 0..days {
+        let z = sampler.sample(&mut rng);
+        let next_price = prices[prices.len() - 1] * (drift + random_term * z).exp();
+        prices.push(next_price);
+    }
+
+    prices
+}
+
+/// Simulate nhiều paths GBM đuôi dày song song, seed = base_seed + index
+pub fn simulate_multiple_paths_with_innovation(
+    initial_price: f64,
+    params: &FatTailParams,
+    days: usize,
+    dt: f64,
+    num_paths: usize,
+    base_seed: u64,
+) -> Vec<Vec<f64>> {
+    (0..num_paths)
+        .into_par_iter()
+        .map(|i| {
+            let seed = base_seed + i as u64;
+            simulate_one_path_with_innovation(initial_price, params, days, dt, seed)
+        })
+        .collect()
+}
@@ -0,0 +1,221 @@
+use rand::distributions::Distribution;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use rayon::prelude::*;
+
+/// Ngưỡng ψ = s²/m² chuyển giữa 2 nhánh của QE (Andersen 2008 đề xuất 1.5)
+const QE_PSI_CRITICAL: f64 = 1.5;
+
+/// Tham số Heston theo phiên
+///
+/// dS/S = μ dt + √v dW₁, dv = κ(θ - v) dt + ξ√v dW₂, corr(dW₁, dW₂) = ρ.
+/// v là phương sai theo phiên (σ theo phiên = √v).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HestonParams {
+    pub mu: f64,
+    /// Phương sai ban đầu
+    pub v0: f64,
+    /// Tốc độ hồi quy về θ
+    pub kappa: f64,
+    /// Phương sai dài hạn
+    pub theta: f64,
+    /// Volatility của phương sai (vol of vol)
+    pub xi: f64,
+    /// Tương quan giữa giá và phương sai, thường âm (leverage effect)
+    pub rho: f64,
+}
+
+impl HestonParams {
+    /// Đổi sang đơn vị thời gian dài gấp `factor` lần (vd 252 để từ phiên sang năm):
+    /// μ, v0, θ, κ và ξ đều nhân `factor`, ρ giữ nguyên
+    pub fn rescale(&self, factor: f64) -> Self {
+        HestonParams {
+            mu: self.mu * factor,
+            v0: self.v0 * factor,
+            kappa: self.kappa * factor,
+            theta: self.theta * factor,
+            xi: self.xi * factor,
+            rho: self.rho,
+        }
+    }
+
+    /// Điều kiện Feller 2κθ ≥ ξ²: khi đúng, phương sai liên tục không chạm 0
+    pub fn satisfies_feller(&self) -> bool {
+        2.0 * self.kappa * self.theta >= self.xi * self.xi
+    }
+}
+
+/// Cách rời rạc hóa phương sai, cả 2 đều giữ phương sai không âm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HestonScheme {
+    /// Euler full truncation (Lord et al. 2010): dùng max(v, 0) trong drift và diffusion
+    FullTruncation,
+    /// Quadratic-Exponential (Andersen 2008): khớp 2 moment đầu của phân phối v chính xác,
+    /// sai số nhỏ ngay cả với bước thời gian lớn
+    #[default]
+    QuadraticExponential,
+}
+
+impl HestonScheme {
+    pub const ALL: [HestonScheme; 2] = [
+        HestonScheme::QuadraticExponential,
+        HestonScheme::FullTruncation,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HestonScheme::FullTruncation => "Full truncation",
+            HestonScheme::QuadraticExponential => "QE",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        HestonScheme::ALL
+            .into_iter()
+            .find(|scheme| scheme.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Path giá và phương sai (cùng độ dài, vị trí 0 là giá / phương sai ban đầu)
+#[derive(Debug, Clone, PartialEq)]
+pub struct HestonPath {
+    pub prices: Vec<f64>,
+    pub variances: Vec<f64>,
+}
+
+/// (1 - e^(-κΔ)) / κ, liên tục tại κ = 0
+fn decay_integral(kappa: f64, dt: f64) -> f64 {
+    if kappa.abs() < 1e-12 {
+        dt
+    } else {
+        -(-kappa * dt).exp_m1() / kappa
+    }
+}
+
+/// Simulate 1 path Heston với seed cố định
+///
+/// QE cần ξ > 0; với ξ = 0 phương sai là tất định nên dùng full truncation.
+pub fn simulate_one_path_heston(
+    initial_price: f64,
+    params: &HestonParams,
+    scheme: HestonScheme,
+    days: usize,
+    dt: f64,
+    seed: u64,
+) -> HestonPath {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let HestonParams {
+        mu,
+        kappa,
+        theta,
+        xi,
+        rho,
+        ..
+    } = *params;
+    let rho_perp = (1.0 - rho * rho).max(0.0).sqrt();
+
+    let mut prices = Vec::with_capacity(days + 1);
+    let mut variances = Vec::with_capacity(days + 1);
+    prices.push(initial_price);
+    variances.push(params.v0.max(0.0));
+
+    let scheme = if xi > 0.0 {
+        scheme
+    } else {
+        HestonScheme::FullTruncation
+    };
+
+    match scheme {
+        HestonScheme::FullTruncation => {
+            // v có thể âm bên trong, chỉ phần dương được dùng và được lưu lại
+            let mut v = params.v0;
+            for _ in 0..days {
+                let z_v: f64 = StandardNormal.sample(&mut rng);
+                let z: f64 = StandardNormal.sample(&mut rng);
+                let z_s = rho * z_v + rho_perp * z;
+                let v_plus = v.max(0.0);
+
+                let log_return = (mu - 0.5 * v_plus) * dt + (v_plus * dt).sqrt() * z_s;
+                v += kappa * (theta - v_plus) * dt + xi * (v_plus * dt).sqrt() * z_v;
+
+                prices.push(prices[prices.len() - 1] * log_return.exp());
+                variances.push(v.max(0.0));
+            }
+        }
+        HestonScheme::QuadraticExponential => {
+            let decay = (-kappa * dt).exp();
+            let integral = decay_integral(kappa, dt);
+            // Hệ số của log giá với γ₁ = γ₂ = 1/2 (trung bình hình thang của v trên bước)
+            let k0 = -rho * kappa * theta * dt / xi;
+            let k1 = 0.5 * dt * (kappa * rho / xi - 0.5) - rho / xi;
+            let k2 = 0.5 * dt * (kappa * rho / xi - 0.5) + rho / xi;
+            let k3 = 0.5 * dt * (1.0 - rho * rho);
+
+            let mut v = params.v0.max(0.0);
+            for _ in 0..days {
+                // 2 moment đầu của v(t + Δ) | v(t)
+                let m = theta + (v - theta) * decay;
+                let s2 = v * xi * xi * decay * integral
+                    + theta * xi * xi * 0.5 * kappa * integral * integral;
+                let psi = s2 / (m * m);
+
+                let next_v = if m <= 0.0 {
+                    0.0
+                } else if psi <= QE_PSI_CRITICAL {
+                    // Phương sai lớn so với độ phân tán: v' = a(b + Z)²
+                    let inv = 2.0 / psi;
+                    let b2 = inv - 1.0 + inv.sqrt() * (inv - 1.0).sqrt();
+                    let a = m / (1.0 + b2);
+                    let z_v: f64 = StandardNormal.sample(&mut rng);
+                    a * (b2.sqrt() + z_v).powi(2)
+                } else {
+                    // Gần 0: khối xác suất tại 0 cộng phân phối mũ
+                    let p = (psi - 1.0) / (psi + 1.0);
+                    let beta = (1.0 - p) / m;
+                    let u: f64 = rng.gen();
+                    if u <= p {
+                        0.0
+                    } else {
+                        ((1.0 - p) / (1.0 - u)).ln() / beta
+                    }
+                };
+
+                let z: f64 = StandardNormal.sample(&mut rng);
+                let log_return =
+                    mu * dt + k0 + k1 * v + k2 * next_v + (k3 * (v + next_v)).max(0.0).sqrt() * z;
+
+                v = next_v;
+                prices.push(prices[prices.len() - 1] * log_return.exp());
+                variances.push(v);
+            }
+        }
+    }
+
+    HestonPath { prices, variances }
+}
+
+/// Simulate nhiều paths Heston song song, seed = base_seed + index
+pub fn simulate_multiple_paths_heston(
+    initial_price: f64,
+    params: &HestonParams,
+    scheme: HestonScheme,
+    days: usize,
+    dt: f64,
+    num_paths: usize,
+    base_seed: u64,
+) -> Vec<HestonPath> {
+    (0..num_paths)
+        .into_par_iter()
+        .map(|i| {
+            simulate_one_path_heston(
+                initial_price,
+                params,
+                scheme,
+                days,
+                dt,
+                base_seed + i as u64,
+            )
+        })
+        .collect()
+}
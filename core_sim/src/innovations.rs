@@ -0,0 +1,197 @@
+use rand::distributions::Distribution;
+use rand::Rng;
+use rand_distr::{NormalInverseGaussian, StandardNormal, StudentT};
+
+/// ν nhỏ nhất khi rút Student-t, dưới 2 thì phương sai không tồn tại
+pub const MIN_STUDENT_T_DOF: f64 = 2.05;
+/// |λ| lớn nhất của skewed-t và |β/α| lớn nhất của NIG
+const MAX_ASYMMETRY: f64 = 0.99;
+
+/// Phân phối của cú sốc z trong mỗi bước, luôn chuẩn hóa về trung bình 0, phương sai 1
+/// nên μ/σ giữ nguyên ý nghĩa: log-return mỗi bước có trung bình (μ - σ²/2)Δt và độ lệch
+/// chuẩn σ√Δt như GBM, chỉ hình dạng (đuôi, độ lệch) thay đổi.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Innovation {
+    #[default]
+    Normal,
+    /// Student-t với ν > 2 bậc tự do, excess kurtosis 6 / (ν - 4) khi ν > 4
+    StudentT { nu: f64 },
+    /// Skewed-t của Hansen (1994): λ ∈ (-1, 1), λ < 0 lệch trái (đuôi giảm dày hơn)
+    SkewedT { nu: f64, lambda: f64 },
+    /// Normal-inverse-Gaussian: α > 0 quyết định độ dày đuôi (càng lớn càng gần chuẩn),
+    /// β ∈ (-α, α) quyết định độ lệch
+    NormalInverseGaussian { alpha: f64, beta: f64 },
+}
+
+impl Innovation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Innovation::Normal => "Normal",
+            Innovation::StudentT { .. } => "Student-t",
+            Innovation::SkewedT { .. } => "Skewed-t",
+            Innovation::NormalInverseGaussian { .. } => "NIG",
+        }
+    }
+
+    /// Bộ rút mẫu với các hằng số chuẩn hóa đã tính sẵn; tham số ngoài miền hợp lệ được
+    /// kẹp về biên (ν ≥ `MIN_STUDENT_T_DOF`, |λ|, |β/α| ≤ 0.99), tham số không hữu hạn
+    /// (NaN từ ô nhập sai, ν = ∞) cho phân phối chuẩn
+    pub fn sampler(&self) -> InnovationSampler {
+        if !self.has_finite_parameters() {
+            return InnovationSampler::Normal;
+        }
+        let student = |nu: f64| {
+            let nu = nu.max(MIN_STUDENT_T_DOF);
+            StudentT::new(nu)
+                .ok()
+                .map(|distribution| (distribution, ((nu - 2.0) / nu).sqrt(), nu))
+        };
+        match *self {
+            Innovation::Normal => InnovationSampler::Normal,
+            Innovation::StudentT { nu } => {
+                let Some((distribution, scale, _)) = student(nu) else {
+                    return InnovationSampler::Normal;
+                };
+                InnovationSampler::StudentT {
+                    distribution,
+                    scale,
+                }
+            }
+            Innovation::SkewedT { nu, lambda } => {
+                let Some((distribution, scale, nu)) = student(nu) else {
+                    return InnovationSampler::Normal;
+                };
+                let lambda = lambda.clamp(-MAX_ASYMMETRY, MAX_ASYMMETRY);
+                // y = (1 ± λ)|w| là t 2 nửa với E[y] = a = 2λ E|w|, E[y²] = 1 + 3λ²
+                let a = 2.0 * lambda * student_t_mean_abs(nu);
+                let b = (1.0 + 3.0 * lambda * lambda - a * a).sqrt();
+                InnovationSampler::SkewedT {
+                    distribution,
+                    scale,
+                    lambda,
+                    a,
+                    b,
+                }
+            }
+            Innovation::NormalInverseGaussian { alpha, beta } => {
+                let alpha = if alpha > 0.0 { alpha } else { 1.0 };
+                let beta = beta.clamp(-MAX_ASYMMETRY * alpha, MAX_ASYMMETRY * alpha);
+                // NIG(α, β, δ = 1, 0) có trung bình β/γ, phương sai α²/γ³ với γ = √(α² - β²)
+                let gamma = (alpha * alpha - beta * beta).sqrt();
+                match NormalInverseGaussian::new(alpha, beta) {
+                    Ok(distribution) => InnovationSampler::NormalInverseGaussian {
+                        distribution,
+                        mean: beta / gamma,
+                        scale: gamma.powf(1.5) / alpha,
+                    },
+                    Err(_) => InnovationSampler::Normal,
+                }
+            }
+        }
+    }
+
+    fn has_finite_parameters(&self) -> bool {
+        match *self {
+            Innovation::Normal => true,
+            Innovation::StudentT { nu } => nu.is_finite(),
+            Innovation::SkewedT { nu, lambda } => nu.is_finite() && lambda.is_finite(),
+            Innovation::NormalInverseGaussian { alpha, beta } => {
+                alpha.is_finite() && beta.is_finite()
+            }
+        }
+    }
+}
+
+/// Bộ rút mẫu z (trung bình 0, phương sai 1) tạo từ `Innovation::sampler`
+#[derive(Debug, Clone, Copy)]
+pub enum InnovationSampler {
+    Normal,
+    StudentT {
+        distribution: StudentT<f64>,
+        scale: f64,
+    },
+    SkewedT {
+        distribution: StudentT<f64>,
+        scale: f64,
+        lambda: f64,
+        a: f64,
+        b: f64,
+    },
+    NormalInverseGaussian {
+        distribution: NormalInverseGaussian<f64>,
+        mean: f64,
+        scale: f64,
+    },
+}
+
+impl Distribution<f64> for InnovationSampler {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match self {
+            InnovationSampler::Normal => StandardNormal.sample(rng),
+            InnovationSampler::StudentT {
+                distribution,
+                scale,
+            } => scale * distribution.sample(rng),
+            InnovationSampler::SkewedT {
+                distribution,
+                scale,
+                lambda,
+                a,
+                b,
+            } => {
+                // Nửa trái có xác suất (1 - λ)/2 và bị co (1 - λ), nửa phải giãn (1 + λ)
+                let w = (scale * distribution.sample(rng)).abs();
+                let u: f64 = rng.gen();
+                let y = if u < 0.5 * (1.0 - lambda) {
+                    -(1.0 - lambda) * w
+                } else {
+                    (1.0 + lambda) * w
+                };
+                (y - a) / b
+            }
+            InnovationSampler::NormalInverseGaussian {
+                distribution,
+                mean,
+                scale,
+            } => (distribution.sample(rng) - mean) * scale,
+        }
+    }
+}
+
+/// E|w| của Student-t chuẩn hóa phương sai 1:
+/// √(ν - 2) Γ((ν - 1)/2) / (√π Γ(ν/2))
+fn student_t_mean_abs(nu: f64) -> f64 {
+    ((nu - 2.0).sqrt() / std::f64::consts::PI.sqrt())
+        * (ln_gamma(0.5 * (nu - 1.0)) - ln_gamma(0.5 * nu)).exp()
+}
+
+/// ln Γ(x) với x > 0 theo xấp xỉ Lanczos (g = 7, 9 hệ số), sai số tương đối ~1e-15
+///
+/// Bản riêng của core_sim, cùng công thức với `ln_gamma` của data_io, để 2 crate không
+/// phụ thuộc nhau.
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Công thức phản xạ Γ(x)Γ(1 - x) = π / sin(πx)
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
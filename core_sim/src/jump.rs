@@ -0,0 +1,82 @@
+use rand::distributions::Distribution;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Poisson, StandardNormal};
+use rayon::prelude::*;
+
+/// Tham số Merton jump-diffusion theo phiên
+///
+/// d ln S = (μ - σ²/2 - λκ) dt + σ dW + Σ ln J, với số jump ~ Poisson(λ dt)
+/// và ln J ~ N(jump_mean, jump_std²). Phần bù λκ (κ = E[J] - 1) giữ E[S_t] = S_0 · e^(μt)
+/// như GBM cùng μ, nên jump chỉ làm đuôi phân phối dày hơn chứ không đổi drift.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JumpParams {
+    pub mu: f64,
+    pub sigma: f64,
+    /// Số jump kỳ vọng mỗi phiên
+    pub lambda: f64,
+    /// Trung bình log của hệ số jump
+    pub jump_mean: f64,
+    /// Độ lệch chuẩn log của hệ số jump
+    pub jump_std: f64,
+}
+
+impl JumpParams {
+    /// κ = E[J] - 1, mức thay đổi giá trung bình của 1 jump
+    pub fn mean_jump_size(&self) -> f64 {
+        (self.jump_mean + 0.5 * self.jump_std.powi(2)).exp() - 1.0
+    }
+}
+
+/// Simulate 1 path Merton jump-diffusion với seed cố định
+pub fn simulate_one_path_jump_diffusion(
+    initial_price: f64,
+    params: &JumpParams,
+    days: usize,
+    dt: f64,
+    seed: u64,
+) -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let drift =
+        (params.mu - 0.5 * params.sigma.powi(2) - params.lambda * params.mean_jump_size()) * dt;
+    let random_term = params.sigma * dt.sqrt();
+    // Poisson cần λ dt > 0; λ = 0 thì trùng GBM
+    let jumps = Poisson::new(params.lambda * dt).ok();
+
+    let mut prices = Vec::with_capacity(days + 1);
+    prices.push(initial_price);
+    for _ in 0..days {
+        let z: f64 = StandardNormal.sample(&mut rng);
+        let mut log_return = drift + random_term * z;
+
+        // Tổng n jump log-normal độc lập ~ N(n · jump_mean, n · jump_std²)
+        let count: f64 = jumps.as_ref().map_or(0.0, |jumps| jumps.sample(&mut rng));
+        if count > 0.0 {
+            let z_jump: f64 = StandardNormal.sample(&mut rng);
+            log_return += count * params.jump_mean + count.sqrt() * params.jump_std * z_jump;
+        }
+
+        let next_price = prices[prices.len() - 1] * log_return.exp();
+        prices.push(next_price);
+    }
+
+    prices
+}
+
+/// Simulate nhiều paths jump-diffusion song song, seed = base_seed + index
+pub fn simulate_multiple_paths_jump_diffusion(
+    initial_price: f64,
+    params: &JumpParams,
+    days: usize,
+    dt: f64,
+    num_paths: usize,
+    base_seed: u64,
+) -> Vec<Vec<f64>> {
+    (0..num_paths)
+        .into_par_iter()
+        .map(|i| {
+            simulate_one_path_jump_diffusion(initial_price, params, days, dt, base_seed + i as u64)
+        })
+        .collect()
+}
@@ -1,9 +1,19 @@
+pub mod garch;
 pub mod gbm;
+pub mod heston;
+pub mod innovations;
 pub mod bootstrap;
+pub mod jump;
+pub mod params;
 pub mod simulation;
 
+pub use garch::*;
 pub use gbm::*;
+pub use heston::*;
+pub use innovations::*;
 pub use bootstrap::*;
+pub use jump::*;
+pub use params::*;
 pub use simulation::*;
 
 #[cfg(test)]
@@ -13,17 +23,12 @@ mod tests {
 
     #[test]
     fn test_gbm_reproducibility() {
-        use rand::{SeedableRng, rngs::StdRng};
-        
-        let mut rng1 = StdRng::seed_from_u64(42);
-        let mut rng2 = StdRng::seed_from_u64(42);
-        
-       
-        let path1 = simulate_one_path(100.0, 0.05, 0.2, 10, 1.0);
-        let path2 = simulate_one_path(100.0, 0.05, 0.2, 10, 1.0);
-        
-        
+        let path1 = simulate_one_path(100.0, 0.05, 0.2, 10, 1.0, 42);
+        let path2 = simulate_one_path(100.0, 0.05, 0.2, 10, 1.0, 42);
+
         assert_eq!(path1.len(), path2.len());
+        assert_eq!(path1, path2);
+        assert_relative_eq!(path1[0], 100.0);
     }
     
     #[test]
@@ -38,9 +43,434 @@ mod tests {
             seed: Some(42),
         };
         
+        let start = std::time::Instant::now();
         let result = run_simulation(config);
+        let elapsed_ms = start.elapsed().as_millis();
         assert_eq!(result.paths.len(), 10);
         assert_eq!(result.paths[0].len(), 6); // initial + 5 days
-        assert!(result.execution_time_ms > 0);
+        // Thời gian run_simulation tự đo nằm trong khoảng đo từ bên ngoài
+        assert!(result.execution_time_ms <= elapsed_ms);
+    }
+
+    #[test]
+    fn test_param_unit_conversion() {
+        let daily = GbmParams::daily(0.0004, 0.02);
+        let annual = daily.to_annual(TRADING_DAYS_252);
+        assert_relative_eq!(annual.mu, 0.0004 * 252.0, epsilon = 1e-12);
+        assert_relative_eq!(annual.sigma, 0.02 * 252f64.sqrt(), epsilon = 1e-12);
+
+        // Đổi qua lại không mất độ chính xác, kể cả giữa 2 quy ước 250/252
+        let back = annual.to_annual(TRADING_DAYS_250).to_daily();
+        assert_relative_eq!(back.mu, daily.mu, epsilon = 1e-15);
+        assert_relative_eq!(back.sigma, daily.sigma, epsilon = 1e-15);
+
+        for unit in TimeUnit::ALL {
+            assert_eq!(TimeUnit::from_name(&unit.name()), Some(unit));
+        }
+        assert_eq!(TimeUnit::from_name("weekly"), None);
+
+        // μ/σ theo năm với dt = 1/252 năm cho cùng path với μ/σ theo phiên và dt = 1 phiên
+        let unit = annual.unit;
+        let path_annual =
+            simulate_one_path(100.0, annual.mu, annual.sigma, 20, unit.days_to_unit(1.0), 7);
+        let path_daily = simulate_one_path(100.0, daily.mu, daily.sigma, 20, 1.0, 7);
+        for (a, d) in path_annual.iter().zip(&path_daily) {
+            assert_relative_eq!(a, d, max_relative = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_parameter_uncertainty_widens_distribution() {
+        let config = |model| SimulationConfig {
+            initial_price: 100.0,
+            horizon_days: 250,
+            num_paths: 2000,
+            dt: 1.0,
+            model,
+            use_antithetic: false,
+            seed: Some(42),
+        };
+        let spread = |paths: &[Vec<f64>]| {
+            let logs: Vec<f64> = paths.iter().map(|p| p[p.len() - 1].ln()).collect();
+            let mean = logs.iter().sum::<f64>() / logs.len() as f64;
+            (logs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / logs.len() as f64).sqrt()
+        };
+
+        let plain = run_simulation(config(SimulationModel::GBM { mu: 0.0005, sigma: 0.02 }));
+        // Chỉ 60 returns → sai số của μ ≈ 0.02/√60 mỗi phiên, cộng dồn qua 250 phiên
+        let uncertain = run_simulation(config(SimulationModel::GBMWithUncertainty {
+            mu: 0.0005,
+            sigma: 0.02,
+            sample_size: 60,
+        }));
+        assert!(spread(&uncertain.paths) > 1.5 * spread(&plain.paths));
+
+        let again = run_simulation(config(SimulationModel::GBMWithUncertainty {
+            mu: 0.0005,
+            sigma: 0.02,
+            sample_size: 60,
+        }));
+        assert_eq!(uncertain.paths, again.paths);
+    }
+
+    #[test]
+    fn test_jump_diffusion() {
+        // λ = 0: không có jump, trùng GBM cùng seed
+        let params = JumpParams { mu: 0.0005, sigma: 0.02, lambda: 0.0, jump_mean: -0.05, jump_std: 0.03 };
+        assert_eq!(
+            simulate_one_path_jump_diffusion(100.0, &params, 30, 1.0, 7),
+            simulate_one_path(100.0, 0.0005, 0.02, 30, 1.0, 7)
+        );
+
+        // Trung bình 1 jump -8% trong 20 phiên: cùng E[S_T] như GBM nhưng lệch trái rõ
+        let params = JumpParams { lambda: 0.05, jump_mean: -0.08, ..params };
+        let paths = simulate_multiple_paths_jump_diffusion(100.0, &params, 20, 1.0, 20_000, 1);
+        let finals: Vec<f64> = paths.iter().map(|p| p[p.len() - 1]).collect();
+        let mean = finals.iter().sum::<f64>() / finals.len() as f64;
+        assert_relative_eq!(mean, 100.0 * (0.0005f64 * 20.0).exp(), max_relative = 0.005);
+
+        let logs: Vec<f64> = finals.iter().map(|p| p.ln()).collect();
+        let m = logs.iter().sum::<f64>() / logs.len() as f64;
+        let m2 = logs.iter().map(|x| (x - m).powi(2)).sum::<f64>() / logs.len() as f64;
+        let m3 = logs.iter().map(|x| (x - m).powi(3)).sum::<f64>() / logs.len() as f64;
+        assert!(m3 / m2.powf(1.5) < -0.2);
+
+        let config = SimulationConfig {
+            initial_price: 100.0,
+            horizon_days: 10,
+            num_paths: 50,
+            dt: 1.0,
+            model: SimulationModel::JumpDiffusion {
+                mu: params.mu,
+                sigma: params.sigma,
+                lambda: params.lambda,
+                jump_mean: params.jump_mean,
+                jump_std: params.jump_std,
+            },
+            use_antithetic: true,
+            seed: Some(1),
+        };
+        let result = run_simulation(config.clone());
+        assert_eq!(result.paths[3], simulate_one_path_jump_diffusion(100.0, &params, 10, 1.0, 4));
+        let folded = fold_terminal_prices(&config, || 0usize, |n, _| *n += 1, |a, b| a + b);
+        assert_eq!(folded, 50);
+    }
+
+    #[test]
+    fn test_heston_schemes() {
+        // Tham số theo năm đổi về theo phiên; ξ lớn nên vi phạm Feller, v hay chạm 0
+        let annual = HestonParams { mu: 0.05, v0: 0.09, kappa: 2.0, theta: 0.04, xi: 0.6, rho: -0.7 };
+        let params = annual.rescale(1.0 / 252.0);
+        assert!(!params.satisfies_feller());
+        assert_relative_eq!(params.rescale(252.0).kappa, 2.0, epsilon = 1e-12);
+
+        for scheme in [HestonScheme::FullTruncation, HestonScheme::QuadraticExponential] {
+            let paths = simulate_multiple_paths_heston(100.0, &params, scheme, 126, 1.0, 10_000, 3);
+            assert!(paths.iter().all(|p| p.variances.iter().all(|&v| v >= 0.0)));
+            assert!(paths.iter().all(|p| p.prices.len() == 127 && p.variances.len() == 127));
+
+            // E[v_T] = θ + (v0 - θ)e^(-κT), E[S_T] = S0 · e^(μT)
+            let n = paths.len() as f64;
+            let mean_v = paths.iter().map(|p| p.variances[126]).sum::<f64>() / n;
+            let expected_v = params.theta + (params.v0 - params.theta) * (-params.kappa * 126.0).exp();
+            assert_relative_eq!(mean_v, expected_v, max_relative = 0.05);
+            let mean_s = paths.iter().map(|p| p.prices[126]).sum::<f64>() / n;
+            assert_relative_eq!(mean_s, 100.0 * (params.mu * 126.0).exp(), max_relative = 0.01);
+
+            // ρ < 0: giá giảm đi kèm volatility tăng → log giá cuối lệch trái
+            let logs: Vec<f64> = paths.iter().map(|p| p.prices[126].ln()).collect();
+            let m = logs.iter().sum::<f64>() / n;
+            let m2 = logs.iter().map(|x| (x - m).powi(2)).sum::<f64>() / n;
+            let m3 = logs.iter().map(|x| (x - m).powi(3)).sum::<f64>() / n;
+            assert!(m3 / m2.powf(1.5) < -0.5, "{:?}", scheme);
+        }
+
+        let config = SimulationConfig {
+            initial_price: 100.0,
+            horizon_days: 10,
+            num_paths: 20,
+            dt: 1.0,
+            model: SimulationModel::Heston {
+                mu: params.mu,
+                v0: params.v0,
+                kappa: params.kappa,
+                theta: params.theta,
+                xi: params.xi,
+                rho: params.rho,
+                scheme: HestonScheme::FullTruncation,
+            },
+            use_antithetic: false,
+            seed: Some(8),
+        };
+        let result = run_simulation(config.clone());
+        let variances = result.variance_paths.as_ref().unwrap();
+        assert_eq!((variances.len(), variances[0].len()), (20, 11));
+        // Scheme trong config được dùng thật, không cố định QE
+        let direct = simulate_one_path_heston(100.0, &params, HestonScheme::FullTruncation, 10, 1.0, 8);
+        assert_eq!(result.paths[0], direct.prices);
+        for scheme in HestonScheme::ALL {
+            assert_eq!(HestonScheme::from_name(scheme.name()), Some(scheme));
+        }
+        let folded = fold_terminal_prices(&config, Vec::new, |acc: &mut Vec<f64>, p| acc.push(p), |mut a, b| {
+            a.extend(b);
+            a
+        });
+        let mut finals: Vec<f64> = result.paths.iter().map(|p| p[10]).collect();
+        let mut folded = folded;
+        finals.sort_by(f64::total_cmp);
+        folded.sort_by(f64::total_cmp);
+        assert_eq!(finals, folded);
+
+        let gbm = run_simulation(SimulationConfig { model: SimulationModel::GBM { mu: 0.0, sigma: 0.02 }, ..config });
+        assert!(gbm.variance_paths.is_none());
+    }
+
+    #[test]
+    fn test_garch_simulation() {
+        // Phương sai dài hạn 1e-4 (σ = 1%/phiên), bắt đầu ở chế độ biến động gấp 4 lần
+        let params = GarchParams { mu: 0.0, omega: 4e-6, alpha: 0.05, gamma: 0.06, beta: 0.88, nu: None };
+        assert_relative_eq!(params.long_run_variance().unwrap(), 1e-4, max_relative = 1e-9);
+        let paths = simulate_multiple_paths_garch(100.0, &params, 4e-4, 60, 5000, 2);
+        let mean_variance = |t: usize| paths.iter().map(|(_, v)| v[t]).sum::<f64>() / paths.len() as f64;
+
+        // E[σ²ₜ] = σ²∞ + persistenceᵗ (σ²₀ - σ²∞)
+        for t in [1, 10, 60] {
+            let expected = 1e-4 + params.persistence().powi(t as i32) * 3e-4;
+            assert_relative_eq!(mean_variance(t), expected, max_relative = 0.05);
+        }
+        // Returns phiên đầu có độ lệch chuẩn √σ²₀ = 2%, không phải σ dài hạn
+        let first: Vec<f64> = paths.iter().map(|(p, _)| (p[1] / p[0]).ln()).collect();
+        let sd = (first.iter().map(|r| r * r).sum::<f64>() / first.len() as f64).sqrt();
+        assert_relative_eq!(sd, 0.02, max_relative = 0.05);
+
+        // Student-t giữ phương sai 1 nhưng đuôi dày hơn
+        let fat = GarchParams { nu: Some(5.0), ..params };
+        let paths = simulate_multiple_paths_garch(100.0, &fat, 1e-4, 1, 20_000, 2);
+        let returns: Vec<f64> = paths.iter().map(|(p, _)| (p[1] / p[0]).ln()).collect();
+        let m2 = returns.iter().map(|r| r * r).sum::<f64>() / returns.len() as f64;
+        let m4 = returns.iter().map(|r| r.powi(4)).sum::<f64>() / returns.len() as f64;
+        assert_relative_eq!(m2, 1e-4, max_relative = 0.05);
+        assert!(m4 / (m2 * m2) > 4.0);
+
+        let config = SimulationConfig {
+            initial_price: 100.0,
+            horizon_days: 10,
+            num_paths: 20,
+            dt: 1.0 / 252.0,
+            model: SimulationModel::Garch {
+                mu: params.mu,
+                omega: params.omega,
+                alpha: params.alpha,
+                gamma: params.gamma,
+                beta: params.beta,
+                nu: params.nu,
+                initial_variance: 4e-4,
+            },
+            use_antithetic: true,
+            seed: Some(5),
+        };
+        let result = run_simulation(config.clone());
+        assert_eq!(result.paths[2], simulate_one_path_garch(100.0, &params, 4e-4, 10, 7).0);
+        assert_eq!(result.variance_paths.unwrap()[0].len(), 11);
+        assert_eq!(fold_terminal_prices(&config, || 0usize, |n, _| *n += 1, |a, b| a + b), 20);
+    }
+
+    #[test]
+    fn test_innovations_are_standardized() {
+        use rand::distributions::Distribution;
+        use rand::SeedableRng;
+
+        let moments = |innovation: Innovation| {
+            let sampler = innovation.sampler();
+            let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+            let z: Vec<f64> = (0..200_000).map(|_| sampler.sample(&mut rng)).collect();
+            let n = z.len() as f64;
+            let mean = z.iter().sum::<f64>() / n;
+            let m2 = z.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+            let m3 = z.iter().map(|x| (x - mean).powi(3)).sum::<f64>() / n;
+            let m4 = z.iter().map(|x| (x - mean).powi(4)).sum::<f64>() / n;
+            (mean, m2, m3 / m2.powf(1.5), m4 / (m2 * m2) - 3.0)
+        };
+
+        for innovation in [
+            Innovation::Normal,
+            Innovation::StudentT { nu: 8.0 },
+            Innovation::SkewedT { nu: 8.0, lambda: -0.3 },
+            Innovation::NormalInverseGaussian { alpha: 1.5, beta: -0.5 },
+        ] {
+            let (mean, variance, _, _) = moments(innovation);
+            assert!(mean.abs() < 0.01, "{:?}: mean {}", innovation, mean);
+            assert_relative_eq!(variance, 1.0, max_relative = 0.02);
+        }
+
+        // Student-t(8): excess kurtosis 6 / (ν - 4) = 1.5
+        let (_, _, skew, kurtosis) = moments(Innovation::StudentT { nu: 8.0 });
+        assert!(skew.abs() < 0.05 && (kurtosis - 1.5).abs() < 0.3);
+        // λ < 0 và β < 0 đều lệch trái
+        assert!(moments(Innovation::SkewedT { nu: 8.0, lambda: -0.3 }).2 < -0.4);
+        // NIG(α = 1.5, β = -0.5): skewness 3β / (α√γ), excess kurtosis 3(1 + 4β²/α²) / γ
+        let gamma = (1.5f64 * 1.5 - 0.25).sqrt();
+        let (_, _, skew, kurtosis) = moments(Innovation::NormalInverseGaussian { alpha: 1.5, beta: -0.5 });
+        assert!((skew - 3.0 * -0.5 / (1.5 * gamma.sqrt())).abs() < 0.1);
+        assert!((kurtosis - 3.0 * (1.0 + 4.0 / 9.0) / gamma).abs() < 0.3);
+        // Γ(5) = 24, Γ(1/2) = √π (nhánh phản xạ cho x < 0.5 qua Γ(0.3))
+        assert_relative_eq!(innovations::ln_gamma(5.0), 24f64.ln(), max_relative = 1e-13);
+        assert_relative_eq!(innovations::ln_gamma(0.5), std::f64::consts::PI.sqrt().ln(), max_relative = 1e-13);
+        assert_relative_eq!(innovations::ln_gamma(0.3) + innovations::ln_gamma(0.7), (std::f64::consts::PI / (0.3 * std::f64::consts::PI).sin()).ln(), max_relative = 1e-12);
+
+        // Tham số NaN / vô hạn từ GUI không làm panic mà rơi về phân phối chuẩn
+        for innovation in [
+            Innovation::NormalInverseGaussian { alpha: 1.5, beta: f64::NAN },
+            Innovation::NormalInverseGaussian { alpha: f64::INFINITY, beta: 0.0 },
+            Innovation::SkewedT { nu: 6.0, lambda: f64::NAN },
+            Innovation::StudentT { nu: f64::NAN },
+        ] {
+            assert!(matches!(innovation.sampler(), InnovationSampler::Normal), "{:?}", innovation);
+        }
+
+        // Innovation chuẩn cho đúng path GBM cùng seed
+        let params = FatTailParams { mu: 0.0005, sigma: 0.02, innovation: Innovation::Normal };
+        assert_eq!(
+            simulate_one_path_with_innovation(100.0, &params, 30, 1.0, 7),
+            simulate_one_path(100.0, 0.0005, 0.02, 30, 1.0, 7)
+        );
+        let config = SimulationConfig {
+            initial_price: 100.0,
+            horizon_days: 10,
+            num_paths: 20,
+            dt: 1.0,
+            model: SimulationModel::GBMWithInnovation {
+                mu: params.mu,
+                sigma: params.sigma,
+                innovation: Innovation::SkewedT { nu: 5.0, lambda: -0.2 },
+            },
+            use_antithetic: true,
+            seed: Some(4),
+        };
+        let result = run_simulation(config.clone());
+        assert_eq!(
+            result.paths[1],
+            simulate_one_path_with_innovation(
+                100.0,
+                &FatTailParams { innovation: Innovation::SkewedT { nu: 5.0, lambda: -0.2 }, ..params },
+                10,
+                1.0,
+                5
+            )
+        );
+        assert_eq!(fold_terminal_prices(&config, || 0usize, |n, _| *n += 1, |a, b| a + b), 20);
+    }
+
+    #[test]
+    fn test_block_bootstrap_keeps_dependence() {
+        use rand::distributions::Distribution;
+        use rand::SeedableRng;
+
+        // Returns AR(1) φ = 0.6 và nhiễu i.i.d. cùng độ dài
+        let mut rng = rand::rngs::StdRng::seed_from_u64(12);
+        let noise: Vec<f64> = (0..2000).map(|_| 0.01 * Distribution::<f64>::sample(&rand_distr::StandardNormal, &mut rng)).collect();
+        let mut ar = Vec::with_capacity(noise.len());
+        let mut previous = 0.0;
+        for e in &noise {
+            previous = 0.6 * previous + e;
+            ar.push(previous);
+        }
+        let lag1 = |returns: &[f64]| {
+            let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+            let c0: f64 = returns.iter().map(|r| (r - mean).powi(2)).sum();
+            let c1: f64 = returns.windows(2).map(|w| (w[0] - mean) * (w[1] - mean)).sum();
+            c1 / c0
+        };
+        let simulated_lag1 = |method, block_length| {
+            let paths = simulate_multiple_paths_block_bootstrap(100.0, &ar, method, block_length, 250, 40, 3);
+            let returns: Vec<Vec<f64>> = paths.iter().map(|p| p.windows(2).map(|w| (w[1] / w[0]).ln()).collect()).collect();
+            returns.iter().map(|r| lag1(r)).sum::<f64>() / returns.len() as f64
+        };
+
+        assert!(simulated_lag1(BootstrapMethod::Iid, 1.0).abs() < 0.05);
+        for method in [BootstrapMethod::MovingBlock, BootstrapMethod::CircularBlock, BootstrapMethod::Stationary] {
+            // Mỗi ranh giới khối làm mất 1 cặp liền kề → tự tương quan ≈ φ(1 - 1/b)
+            assert!(simulated_lag1(method, 25.0) > 0.45, "{:?}", method);
+            assert_eq!(BootstrapMethod::from_name(method.name()), Some(method));
+        }
+
+        // Tự chọn: khối ngắn với nhiễu i.i.d., dài hơn khi có phụ thuộc
+        let automatic = automatic_block_length(&ar, BootstrapMethod::Stationary);
+        assert!(automatic_block_length(&noise, BootstrapMethod::Stationary) < 3.0);
+        assert!(automatic > 5.0 && automatic <= 3.0 * 2000f64.sqrt());
+        assert_eq!(automatic_block_length(&ar, BootstrapMethod::Iid), 1.0);
+
+        // Đầu vào không hợp lệ: path chỉ gồm giá ban đầu thay vì panic / tắt khối
+        assert_eq!(simulate_one_path_block_bootstrap(100.0, &[], BootstrapMethod::Stationary, 5.0, 30, 1), vec![100.0]);
+        assert_eq!(simulate_one_path_block_bootstrap(100.0, &[], BootstrapMethod::Iid, 1.0, 30, 1), vec![100.0]);
+        for block_length in [f64::NAN, f64::INFINITY, 0.5] {
+            assert_eq!(simulate_one_path_block_bootstrap(100.0, &ar, BootstrapMethod::MovingBlock, block_length, 30, 1), vec![100.0]);
+        }
+
+        // i.i.d. trùng bootstrap cũ; None dùng độ dài tự chọn ở cả run_simulation và fold
+        assert_eq!(
+            simulate_one_path_block_bootstrap(100.0, &ar, BootstrapMethod::Iid, 10.0, 30, 5),
+            simulate_one_path_bootstrap(100.0, &ar, 30, 5)
+        );
+        let config = SimulationConfig {
+            initial_price: 100.0,
+            horizon_days: 30,
+            num_paths: 16,
+            dt: 1.0,
+            model: SimulationModel::Bootstrap {
+                historical_returns: ar.clone(),
+                method: BootstrapMethod::Stationary,
+                block_length: None,
+            },
+            use_antithetic: false,
+            seed: Some(2),
+        };
+        let result = run_simulation(config.clone());
+        assert_eq!(
+            result.paths[1],
+            simulate_one_path_block_bootstrap(100.0, &ar, BootstrapMethod::Stationary, automatic, 30, 3)
+        );
+        let mut folded = fold_terminal_prices(&config, Vec::new, |acc: &mut Vec<f64>, p| acc.push(p), |mut a, b| {
+            a.extend(b);
+            a
+        });
+        let mut finals: Vec<f64> = result.paths.iter().map(|p| p[30]).collect();
+        folded.sort_by(f64::total_cmp);
+        finals.sort_by(f64::total_cmp);
+        assert_eq!(folded, finals);
+    }
+
+    #[test]
+    fn test_fold_terminal_prices_matches_paths() {
+        for use_antithetic in [false, true] {
+            let config = SimulationConfig {
+                initial_price: 50.0,
+                horizon_days: 20,
+                num_paths: 501,
+                dt: 1.0,
+                model: SimulationModel::GBM { mu: 0.0003, sigma: 0.02 },
+                use_antithetic,
+                seed: Some(9),
+            };
+
+            // Gom giá cuối theo chunk rồi nối lại, so với giá cuối của run_simulation
+            let mut folded = fold_terminal_prices(
+                &config,
+                Vec::new,
+                |acc: &mut Vec<f64>, price| acc.push(price),
+                |mut a, b| {
+                    a.extend(b);
+                    a
+                },
+            );
+            let mut expected: Vec<f64> = run_simulation(config)
+                .paths
+                .iter()
+                .map(|path| path[path.len() - 1])
+                .collect();
+            folded.sort_by(|a, b| a.total_cmp(b));
+            expected.sort_by(|a, b| a.total_cmp(b));
+            assert_eq!(folded, expected);
+        }
     }
-}
\ No newline at end of file
+}
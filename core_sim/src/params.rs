@@ -0,0 +1,132 @@
+use std::fmt;
+
+/// Số phiên giao dịch trong 1 năm theo quy ước phổ biến (NYSE, RiskMetrics)
+pub const TRADING_DAYS_252: u32 = 252;
+/// Quy ước 250 phiên/năm, hay dùng cho thị trường Việt Nam
+pub const TRADING_DAYS_250: u32 = 250;
+
+/// Đơn vị thời gian của μ/σ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeUnit {
+    /// Theo 1 phiên giao dịch
+    #[default]
+    TradingDay,
+    /// Theo năm, với số phiên giao dịch trong năm
+    Year { trading_days: u32 },
+}
+
+impl TimeUnit {
+    /// Các lựa chọn hiển thị trên GUI
+    pub const ALL: [TimeUnit; 3] = [
+        TimeUnit::TradingDay,
+        TimeUnit::Year {
+            trading_days: TRADING_DAYS_252,
+        },
+        TimeUnit::Year {
+            trading_days: TRADING_DAYS_250,
+        },
+    ];
+
+    /// Số phiên giao dịch trong 1 đơn vị
+    pub fn trading_days(&self) -> f64 {
+        match *self {
+            TimeUnit::TradingDay => 1.0,
+            TimeUnit::Year { trading_days } => trading_days as f64,
+        }
+    }
+
+    /// Đổi bước thời gian tính bằng phiên sang đơn vị này (vd 1 phiên = 1/252 năm)
+    pub fn days_to_unit(&self, days: f64) -> f64 {
+        days / self.trading_days()
+    }
+
+    pub fn name(&self) -> String {
+        match *self {
+            TimeUnit::TradingDay => "Daily".to_string(),
+            TimeUnit::Year { trading_days } => format!("Annual ({}d)", trading_days),
+        }
+    }
+
+    /// Parse tên hiển thị ("Daily", "Annual (252d)"...), không phân biệt hoa thường
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        if name == "daily" || name == "day" {
+            return Some(TimeUnit::TradingDay);
+        }
+        let days = name
+            .strip_prefix("annual")?
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .trim_end_matches('d');
+        if days.is_empty() {
+            return Some(TimeUnit::Year {
+                trading_days: TRADING_DAYS_252,
+            });
+        }
+        match days.trim().parse::<u32>() {
+            Ok(trading_days) if trading_days > 0 => Some(TimeUnit::Year { trading_days }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TimeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+/// μ/σ của log-returns kèm đơn vị thời gian
+///
+/// Log-returns các phiên độc lập nên khi đổi đơn vị từ phiên sang khoảng dài gấp N lần:
+/// μ_N = N · μ_1 và σ_N = √N · σ_1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GbmParams {
+    pub mu: f64,
+    pub sigma: f64,
+    pub unit: TimeUnit,
+}
+
+impl GbmParams {
+    pub fn new(mu: f64, sigma: f64, unit: TimeUnit) -> Self {
+        GbmParams { mu, sigma, unit }
+    }
+
+    /// μ/σ theo phiên, như kết quả ước lượng từ giá ngày
+    pub fn daily(mu: f64, sigma: f64) -> Self {
+        GbmParams::new(mu, sigma, TimeUnit::TradingDay)
+    }
+
+    /// μ/σ theo năm với `trading_days` phiên/năm
+    pub fn annual(mu: f64, sigma: f64, trading_days: u32) -> Self {
+        GbmParams::new(mu, sigma, TimeUnit::Year { trading_days })
+    }
+
+    /// Đổi sang đơn vị khác
+    pub fn to_unit(&self, unit: TimeUnit) -> Self {
+        let scale = unit.trading_days() / self.unit.trading_days();
+        GbmParams {
+            mu: self.mu * scale,
+            sigma: self.sigma * scale.sqrt(),
+            unit,
+        }
+    }
+
+    pub fn to_daily(&self) -> Self {
+        self.to_unit(TimeUnit::TradingDay)
+    }
+
+    pub fn to_annual(&self, trading_days: u32) -> Self {
+        self.to_unit(TimeUnit::Year { trading_days })
+    }
+
+    /// Model GBM cho `SimulationConfig`, μ/σ đổi về theo phiên vì `dt` tính bằng phiên
+    pub fn to_model(&self) -> crate::simulation::SimulationModel {
+        let daily = self.to_daily();
+        crate::simulation::SimulationModel::GBM {
+            mu: daily.mu,
+            sigma: daily.sigma,
+        }
+    }
+}
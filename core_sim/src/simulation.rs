@@ -1,9 +1,26 @@
-use crate::{gbm, bootstrap};
+use crate::{garch, gbm, bootstrap, heston, innovations, jump};
 
 #[derive(Debug, Clone)]
 pub enum SimulationModel {
     GBM { mu: f64, sigma: f64 },
-    Bootstrap { historical_returns: Vec<f64> },
+    /// GBM với μ/σ rút lại cho từng path từ phân phối lấy mẫu của ước lượng
+    /// trên `sample_size` returns (không dùng antithetic)
+    GBMWithUncertainty { mu: f64, sigma: f64, sample_size: usize },
+    /// GBM với cú sốc đuôi dày / lệch thay cho N(0,1), chuẩn hóa phương sai 1 nên σ giữ
+    /// nguyên ý nghĩa (không dùng antithetic), xem `innovations::Innovation`
+    GBMWithInnovation { mu: f64, sigma: f64, innovation: innovations::Innovation },
+    /// Merton jump-diffusion: GBM cộng jump log-normal đến theo Poisson với cường độ
+    /// `lambda` mỗi phiên (không dùng antithetic), xem `jump::JumpParams`
+    JumpDiffusion { mu: f64, sigma: f64, lambda: f64, jump_mean: f64, jump_std: f64 },
+    /// Heston stochastic volatility theo phiên, rời rạc hóa phương sai theo `scheme` (không dùng
+    /// antithetic); kết quả có thêm path phương sai, xem `heston::HestonParams`
+    Heston { mu: f64, v0: f64, kappa: f64, theta: f64, xi: f64, rho: f64, scheme: heston::HestonScheme },
+    /// GJR-GARCH(1,1) theo phiên bắt đầu từ phương sai `initial_variance` (không dùng `dt`
+    /// và antithetic); kết quả có thêm path phương sai, xem `garch::GarchParams`
+    Garch { mu: f64, omega: f64, alpha: f64, gamma: f64, beta: f64, nu: Option<f64>, initial_variance: f64 },
+    /// Lấy mẫu lại log-returns lịch sử theo `method`; `block_length` là độ dài khối (trung bình
+    /// với stationary), None thì tự chọn theo `bootstrap::automatic_block_length`
+    Bootstrap { historical_returns: Vec<f64>, method: bootstrap::BootstrapMethod, block_length: Option<f64> },
 }
 
 #[derive(Debug, Clone)]
@@ -11,6 +28,9 @@ pub struct SimulationConfig {
     pub initial_price: f64,
     pub horizon_days: usize,
     pub num_paths: usize,
+    /// Bước thời gian tính bằng số phiên giao dịch (dt = 1.0 là 1 phiên), nên μ/σ và các
+    /// cường độ trong `model` phải theo phiên như `GbmParams::to_model` trả về; không dùng
+    /// dt = 1/252 cho μ/σ đã đổi về theo phiên
     pub dt: f64,
     pub model: SimulationModel,
     pub use_antithetic: bool,
@@ -19,6 +39,8 @@ pub struct SimulationConfig {
 
 pub struct SimulationResult {
     pub paths: Vec<Vec<f64>>,
+    /// Path phương sai theo phiên, chỉ có với model volatility thay đổi (Heston, GARCH)
+    pub variance_paths: Option<Vec<Vec<f64>>>,
     pub execution_time_ms: u128,
 }
 
@@ -28,6 +50,7 @@ pub fn run_simulation(config: SimulationConfig) -> SimulationResult {
     // Lấy seed từ config, nếu không có thì dùng 42 làm default
     let seed = config.seed.unwrap_or(42);
 
+    let mut variance_paths = None;
     let paths = match config.model {
         SimulationModel::GBM { mu, sigma } => {
             if config.use_antithetic {
@@ -54,11 +77,75 @@ pub fn run_simulation(config: SimulationConfig) -> SimulationResult {
                 )
             }
         }
-        SimulationModel::Bootstrap { historical_returns } => {
+        SimulationModel::GBMWithUncertainty { mu, sigma, sample_size } => {
+            gbm::simulate_multiple_paths_with_uncertainty(
+                config.initial_price,
+                &gbm::EstimatedParams { mu, sigma, sample_size },
+                config.horizon_days,
+                config.dt,
+                config.num_paths,
+                seed,
+            )
+        }
+        SimulationModel::GBMWithInnovation { mu, sigma, innovation } => {
+            gbm::simulate_multiple_paths_with_innovation(
+                config.initial_price,
+                &gbm::FatTailParams { mu, sigma, innovation },
+                config.horizon_days,
+                config.dt,
+                config.num_paths,
+                seed,
+            )
+        }
+        SimulationModel::JumpDiffusion { mu, sigma, lambda, jump_mean, jump_std } => {
+            jump::simulate_multiple_paths_jump_diffusion(
+                config.initial_price,
+                &jump::JumpParams { mu, sigma, lambda, jump_mean, jump_std },
+                config.horizon_days,
+                config.dt,
+                config.num_paths,
+                seed,
+            )
+        }
+        SimulationModel::Heston { mu, v0, kappa, theta, xi, rho, scheme } => {
+            let (paths, variances) = heston::simulate_multiple_paths_heston(
+                config.initial_price,
+                &heston::HestonParams { mu, v0, kappa, theta, xi, rho },
+                scheme,
+                config.horizon_days,
+                config.dt,
+                config.num_paths,
+                seed,
+            )
+            .into_iter()
+            .map(|path| (path.prices, path.variances))
+            .unzip();
+            variance_paths = Some(variances);
+            paths
+        }
+        SimulationModel::Garch { mu, omega, alpha, gamma, beta, nu, initial_variance } => {
+            let (paths, variances) = garch::simulate_multiple_paths_garch(
+                config.initial_price,
+                &garch::GarchParams { mu, omega, alpha, gamma, beta, nu },
+                initial_variance,
+                config.horizon_days,
+                config.num_paths,
+                seed,
+            )
+            .into_iter()
+            .unzip();
+            variance_paths = Some(variances);
+            paths
+        }
+        SimulationModel::Bootstrap { historical_returns, method, block_length } => {
             // Bootstrap simulation
-            bootstrap::simulate_multiple_paths_bootstrap(
+            let block_length = block_length
+                .unwrap_or_else(|| bootstrap::automatic_block_length(&historical_returns, method));
+            bootstrap::simulate_multiple_paths_block_bootstrap(
                 config.initial_price,
                 &historical_returns,
+                method,
+                block_length,
                 config.horizon_days,
                 config.num_paths,
                 seed, // Truyền seed vào
@@ -70,6 +157,170 @@ pub fn run_simulation(config: SimulationConfig) -> SimulationResult {
 
     SimulationResult {
         paths,
+        variance_paths,
         execution_time_ms,
     }
-}
\ No newline at end of file
+}
+/// Số đơn vị công việc độc lập: mỗi path, hoặc mỗi cặp path khi dùng antithetic với GBM
+fn work_units(config: &SimulationConfig) -> usize {
+    match config.model {
+        SimulationModel::GBM { .. } if config.use_antithetic => config.num_paths / 2,
+        _ => config.num_paths,
+    }
+}
+
+/// Mô phỏng đơn vị thứ `index` và gọi `f` với giá cuối của từng path (cùng seed như `run_simulation`)
+fn for_each_terminal_price(config: &SimulationConfig, index: usize, f: &mut impl FnMut(f64)) {
+    let seed = config.seed.unwrap_or(42) + index as u64;
+    let last = |path: Vec<f64>| path[path.len() - 1];
+
+    match &config.model {
+        SimulationModel::GBM { mu, sigma } if config.use_antithetic => {
+            let (path1, path2) = gbm::simulate_antithetic_pair(
+                config.initial_price,
+                *mu,
+                *sigma,
+                config.horizon_days,
+                config.dt,
+                seed,
+            );
+            f(last(path1));
+            f(last(path2));
+        }
+        SimulationModel::GBM { mu, sigma } => f(last(gbm::simulate_one_path(
+            config.initial_price,
+            *mu,
+            *sigma,
+            config.horizon_days,
+            config.dt,
+            seed,
+        ))),
+        SimulationModel::GBMWithUncertainty { mu, sigma, sample_size } => {
+            f(last(gbm::simulate_one_path_with_uncertainty(
+                config.initial_price,
+                &gbm::EstimatedParams {
+                    mu: *mu,
+                    sigma: *sigma,
+                    sample_size: *sample_size,
+                },
+                config.horizon_days,
+                config.dt,
+                seed,
+            )))
+        }
+        SimulationModel::GBMWithInnovation { mu, sigma, innovation } => {
+            f(last(gbm::simulate_one_path_with_innovation(
+                config.initial_price,
+                &gbm::FatTailParams {
+                    mu: *mu,
+                    sigma: *sigma,
+                    innovation: *innovation,
+                },
+                config.horizon_days,
+                config.dt,
+                seed,
+            )))
+        }
+        SimulationModel::JumpDiffusion { mu, sigma, lambda, jump_mean, jump_std } => {
+            f(last(jump::simulate_one_path_jump_diffusion(
+                config.initial_price,
+                &jump::JumpParams {
+                    mu: *mu,
+                    sigma: *sigma,
+                    lambda: *lambda,
+                    jump_mean: *jump_mean,
+                    jump_std: *jump_std,
+                },
+                config.horizon_days,
+                config.dt,
+                seed,
+            )))
+        }
+        SimulationModel::Heston { mu, v0, kappa, theta, xi, rho, scheme } => {
+            let path = heston::simulate_one_path_heston(
+                config.initial_price,
+                &heston::HestonParams {
+                    mu: *mu,
+                    v0: *v0,
+                    kappa: *kappa,
+                    theta: *theta,
+                    xi: *xi,
+                    rho: *rho,
+                },
+                *scheme,
+                config.horizon_days,
+                config.dt,
+                seed,
+            );
+            f(last(path.prices))
+        }
+        SimulationModel::Garch { mu, omega, alpha, gamma, beta, nu, initial_variance } => {
+            let (prices, _) = garch::simulate_one_path_garch(
+                config.initial_price,
+                &garch::GarchParams {
+                    mu: *mu,
+                    omega: *omega,
+                    alpha: *alpha,
+                    gamma: *gamma,
+                    beta: *beta,
+                    nu: *nu,
+                },
+                *initial_variance,
+                config.horizon_days,
+                seed,
+            );
+            f(last(prices))
+        }
+        SimulationModel::Bootstrap { historical_returns, method, block_length } => {
+            f(last(bootstrap::simulate_one_path_block_bootstrap(
+                config.initial_price,
+                historical_returns,
+                *method,
+                // fold_terminal_prices đã thay None bằng độ dài khối tự chọn
+                block_length.unwrap_or(1.0),
+                config.horizon_days,
+                seed,
+            )))
+        }
+    }
+}
+
+/// Gộp giá cuối của mọi path mà không giữ lại paths (dùng cho hàng chục triệu paths)
+///
+/// Rayon chia các path thành nhiều chunk: mỗi chunk bắt đầu từ `init()`, cộng dồn giá cuối
+/// bằng `fold`, rồi các kết quả được gộp lại bằng `merge`. Bộ nhớ chỉ cỡ 1 path mỗi thread.
+/// Giá cuối trùng với `run_simulation` cùng config (cùng seed cho từng path).
+pub fn fold_terminal_prices<A, I, F, M>(config: &SimulationConfig, init: I, fold: F, merge: M) -> A
+where
+    A: Send,
+    I: Fn() -> A + Sync + Send,
+    F: Fn(&mut A, f64) + Sync + Send,
+    M: Fn(A, A) -> A + Sync + Send,
+{
+    use rayon::prelude::*;
+
+    // Độ dài khối tự chọn được tính 1 lần cho mọi path
+    let resolved;
+    let config = match &config.model {
+        SimulationModel::Bootstrap { historical_returns, method, block_length: None } => {
+            resolved = SimulationConfig {
+                model: SimulationModel::Bootstrap {
+                    historical_returns: historical_returns.clone(),
+                    method: *method,
+                    block_length: Some(bootstrap::automatic_block_length(historical_returns, *method)),
+                },
+                ..config.clone()
+            };
+            &resolved
+        }
+        _ => config,
+    };
+
+    (0..work_units(config))
+        .into_par_iter()
+        .fold(&init, |mut acc, index| {
+            for_each_terminal_price(config, index, &mut |price| fold(&mut acc, price));
+            acc
+        })
+        .reduce(&init, merge)
+}
@@ -0,0 +1,305 @@
+use chrono::{Datelike, NaiveDate};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::error::{DataIoError, DataIoResult};
+use crate::exchange::Exchange;
+use crate::market_data::MarketDataStore;
+use crate::merge::{source_files, MergePolicy, MergeReport};
+use crate::price_series::{PriceBar, PriceSeries};
+
+/// 4 byte đầu của file cache
+const CACHE_MAGIC: &[u8; 4] = b"SSMC";
+/// Tăng khi đổi layout, cache cũ sẽ bị bỏ qua và tạo lại
+const CACHE_VERSION: u32 = 1;
+
+/// Dấu vết của 1 file nguồn lúc tạo cache: đổi kích thước hoặc thời gian sửa → cache hết hạn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFingerprint {
+    pub path: String,
+    pub size: u64,
+    /// Thời gian sửa file, tính bằng nanosecond từ UNIX epoch
+    pub modified_nanos: u64,
+}
+
+impl SourceFingerprint {
+    pub fn of(path: &str) -> DataIoResult<Self> {
+        let metadata = std::fs::metadata(path).map_err(|e| DataIoError::from_io(path, e))?;
+        let modified_nanos = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Ok(SourceFingerprint {
+            path: path.to_string(),
+            size: metadata.len(),
+            modified_nanos,
+        })
+    }
+}
+
+/// Dấu vết của mọi file thuộc nguồn dữ liệu (file, thư mục hoặc glob)
+pub fn source_fingerprints(source: &str) -> DataIoResult<Vec<SourceFingerprint>> {
+    source_files(source)?
+        .iter()
+        .map(|path| SourceFingerprint::of(&path.to_string_lossy()))
+        .collect()
+}
+
+/// Ghi store ra file cache nhị phân
+///
+/// Layout (little-endian): magic, version, source, danh sách fingerprint, rồi từng ticker
+/// gồm tên, sàn, số phiên và các cột ngày (i32, số ngày từ 01/01/0001), OHLC (f64), volume (i64),
+/// cuối cùng là các ngày bị trùng. Ghi ra file tạm rồi rename để không để lại cache ghi dở.
+pub fn write_cache(
+    store: &MarketDataStore,
+    sources: &[SourceFingerprint],
+    cache_path: &str,
+) -> DataIoResult<()> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(CACHE_MAGIC);
+    put_u32(&mut buf, CACHE_VERSION);
+    put_str(&mut buf, store.source_path());
+
+    put_u32(&mut buf, sources.len() as u32);
+    for source in sources {
+        put_str(&mut buf, &source.path);
+        buf.extend_from_slice(&source.size.to_le_bytes());
+        buf.extend_from_slice(&source.modified_nanos.to_le_bytes());
+    }
+
+    put_u32(&mut buf, store.len() as u32);
+    for series in store.iter() {
+        put_str(&mut buf, series.ticker());
+        buf.push(exchange_code(store.exchange(series.ticker())));
+        put_u32(&mut buf, series.len() as u32);
+        for &date in series.dates() {
+            buf.extend_from_slice(&date.num_days_from_ce().to_le_bytes());
+        }
+        for column in [
+            series.opens(),
+            series.highs(),
+            series.lows(),
+            series.closes(),
+        ] {
+            for value in column {
+                buf.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        for volume in series.volumes() {
+            buf.extend_from_slice(&volume.to_le_bytes());
+        }
+        put_u32(&mut buf, series.duplicate_dates().len() as u32);
+        for &date in series.duplicate_dates() {
+            buf.extend_from_slice(&date.num_days_from_ce().to_le_bytes());
+        }
+    }
+
+    let tmp_path = format!("{}.tmp", cache_path);
+    std::fs::write(&tmp_path, &buf).map_err(|e| DataIoError::from_io(&tmp_path, e))?;
+    std::fs::rename(&tmp_path, cache_path).map_err(|e| DataIoError::from_io(cache_path, e))
+}
+
+/// Đọc file cache, trả về store và fingerprint của các file nguồn lúc ghi cache
+pub fn read_cache(cache_path: &str) -> DataIoResult<(MarketDataStore, Vec<SourceFingerprint>)> {
+    let bytes = std::fs::read(cache_path).map_err(|e| DataIoError::from_io(cache_path, e))?;
+    let mut reader = CacheReader {
+        bytes: &bytes,
+        pos: 0,
+        path: cache_path,
+    };
+
+    if reader.take(4)? != CACHE_MAGIC {
+        return Err(reader.invalid("not a SimuStock cache file"));
+    }
+    let version = reader.u32()?;
+    if version != CACHE_VERSION {
+        return Err(reader.invalid(&format!(
+            "version {} is not supported (expected {})",
+            version, CACHE_VERSION
+        )));
+    }
+    let source = reader.string()?;
+
+    let mut sources = Vec::new();
+    for _ in 0..reader.u32()? {
+        sources.push(SourceFingerprint {
+            path: reader.string()?,
+            size: reader.u64()?,
+            modified_nanos: reader.u64()?,
+        });
+    }
+
+    let mut store = MarketDataStore::default();
+    for _ in 0..reader.u32()? {
+        let ticker = reader.string()?;
+        let exchange = reader.exchange()?;
+        let len = reader.u32()? as usize;
+
+        let dates = (0..len)
+            .map(|_| reader.date())
+            .collect::<DataIoResult<Vec<_>>>()?;
+        let mut prices = Vec::with_capacity(4);
+        for _ in 0..4 {
+            prices.push(
+                (0..len)
+                    .map(|_| reader.f64())
+                    .collect::<DataIoResult<Vec<_>>>()?,
+            );
+        }
+        let volumes = (0..len)
+            .map(|_| reader.i64())
+            .collect::<DataIoResult<Vec<_>>>()?;
+        let duplicates = (0..reader.u32()?)
+            .map(|_| reader.date())
+            .collect::<DataIoResult<Vec<_>>>()?;
+
+        let bars = (0..len)
+            .map(|i| PriceBar {
+                date: dates[i],
+                open: prices[0][i],
+                high: prices[1][i],
+                low: prices[2][i],
+                close: prices[3][i],
+                volume: volumes[i],
+            })
+            .collect();
+        let mut series = PriceSeries::from_bars(&ticker, bars)?;
+        series.set_duplicate_dates(duplicates);
+        store.insert_series(series);
+        if let Some(exchange) = exchange {
+            store.set_exchange(&ticker, exchange);
+        }
+    }
+
+    store.set_source_path(&source);
+    Ok((store, sources))
+}
+
+impl MarketDataStore {
+    /// Ghi store ra cache kèm fingerprint hiện tại của nguồn (`source_path`)
+    pub fn save_cache(&self, cache_path: &str) -> DataIoResult<()> {
+        let sources = source_fingerprints(self.source_path())?;
+        write_cache(self, &sources, cache_path)
+    }
+
+    /// Load nguồn dữ liệu qua cache
+    ///
+    /// Nếu cache còn hợp lệ (cùng nguồn, các file nguồn không đổi) thì đọc cache và trả về
+    /// `None`, kể cả các phiên đã append sau khi tạo cache. Ngược lại parse lại nguồn bằng
+    /// `load_any`, ghi cache mới và trả về report của lần gộp.
+    pub fn load_cached(
+        source: &str,
+        cache_path: &str,
+        policy: MergePolicy,
+    ) -> DataIoResult<(Self, Option<MergeReport>)> {
+        let current = source_fingerprints(source)?;
+        if Path::new(cache_path).exists() {
+            // Cache hỏng / khác phiên bản không phải lỗi, chỉ cần tạo lại
+            if let Ok((store, cached)) = read_cache(cache_path) {
+                if store.source_path() == source && cached == current {
+                    return Ok((store, None));
+                }
+            }
+        }
+
+        let (mut store, report) = Self::load_any(source, policy)?;
+        store.set_source_path(source);
+        write_cache(&store, &current, cache_path)?;
+        Ok((store, Some(report)))
+    }
+}
+
+fn exchange_code(exchange: Option<Exchange>) -> u8 {
+    match exchange {
+        None => 0,
+        Some(Exchange::Hsx) => 1,
+        Some(Exchange::Hnx) => 2,
+        Some(Exchange::Upcom) => 3,
+    }
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_str(buf: &mut Vec<u8>, text: &str) {
+    put_u32(buf, text.len() as u32);
+    buf.extend_from_slice(text.as_bytes());
+}
+
+/// Đọc tuần tự các trường của file cache
+struct CacheReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    path: &'a str,
+}
+
+impl<'a> CacheReader<'a> {
+    fn invalid(&self, message: &str) -> DataIoError {
+        DataIoError::InvalidCache {
+            path: self.path.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn take(&mut self, n: usize) -> DataIoResult<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len());
+        match end {
+            Some(end) => {
+                let slice = &self.bytes[self.pos..end];
+                self.pos = end;
+                Ok(slice)
+            }
+            None => Err(self.invalid("unexpected end of file")),
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> DataIoResult<[u8; N]> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> DataIoResult<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> DataIoResult<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> DataIoResult<i64> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    fn f64(&mut self) -> DataIoResult<f64> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    fn string(&mut self) -> DataIoResult<String> {
+        let len = self.u32()? as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| self.invalid("invalid UTF-8 string"))
+    }
+
+    fn date(&mut self) -> DataIoResult<NaiveDate> {
+        let days = i32::from_le_bytes(self.array()?);
+        NaiveDate::from_num_days_from_ce_opt(days).ok_or_else(|| self.invalid("invalid date"))
+    }
+
+    fn exchange(&mut self) -> DataIoResult<Option<Exchange>> {
+        match self.array::<1>()?[0] {
+            0 => Ok(None),
+            1 => Ok(Some(Exchange::Hsx)),
+            2 => Ok(Some(Exchange::Hnx)),
+            3 => Ok(Some(Exchange::Upcom)),
+            code => Err(self.invalid(&format!("unknown exchange code {}", code))),
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use arrow::array::{
+    Array, ArrayRef, Date32Array, Float64Array, Int64Array, RecordBatch, StringArray, UInt32Array,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use chrono::NaiveDate;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::{DataIoError, DataIoResult};
+use crate::market_data::MarketDataStore;
+use crate::price_series::PriceBar;
+
+/// Số dòng mỗi record batch khi ghi paths (long format)
+const PATH_BATCH_ROWS: usize = 64 * 1024;
+
+fn columnar_error(path: &str, err: impl std::fmt::Display) -> DataIoError {
+    DataIoError::Columnar {
+        path: path.to_string(),
+        message: err.to_string(),
+    }
+}
+
+fn open_file(path: &str) -> DataIoResult<File> {
+    File::open(path).map_err(|e| DataIoError::from_io(path, e))
+}
+
+fn create_file(path: &str) -> DataIoResult<File> {
+    File::create(path).map_err(|e| DataIoError::from_io(path, e))
+}
+
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default()
+}
+
+/// Schema bảng giá: ticker, date, open, high, low, close, volume
+pub fn price_table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("ticker", DataType::Utf8, false),
+        Field::new("date", DataType::Date32, false),
+        Field::new("open", DataType::Float64, false),
+        Field::new("high", DataType::Float64, false),
+        Field::new("low", DataType::Float64, false),
+        Field::new("close", DataType::Float64, false),
+        Field::new("volume", DataType::Int64, false),
+    ]))
+}
+
+/// Schema paths mô phỏng dạng long: mỗi dòng là (path, step, price)
+pub fn paths_table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("path", DataType::UInt32, false),
+        Field::new("step", DataType::UInt32, false),
+        Field::new("price", DataType::Float64, false),
+    ]))
+}
+
+/// Đọc bảng giá từ file Parquet
+///
+/// Cột được tìm theo tên (không phân biệt hoa thường) và tự cast về kiểu cần thiết,
+/// nên file có `close` kiểu float32 hay `volume` kiểu float64 vẫn đọc được.
+/// Nếu không có cột `ticker`, ticker lấy từ tên file.
+pub fn read_parquet_prices(path: &str) -> DataIoResult<MarketDataStore> {
+    let file = open_file(path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .and_then(|builder| builder.build())
+        .map_err(|e| columnar_error(path, e))?;
+
+    let batches = reader
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| columnar_error(path, e))?;
+    store_from_batches(path, &batches)
+}
+
+/// Đọc bảng giá từ file Arrow IPC (Feather v2)
+pub fn read_arrow_ipc_prices(path: &str) -> DataIoResult<MarketDataStore> {
+    let file = open_file(path)?;
+    let reader = FileReader::try_new(file, None).map_err(|e| columnar_error(path, e))?;
+
+    let batches = reader
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| columnar_error(path, e))?;
+    store_from_batches(path, &batches)
+}
+
+/// Lấy cột theo tên và cast về `data_type`
+fn column_as(
+    path: &str,
+    batch: &RecordBatch,
+    name: &str,
+    data_type: &DataType,
+) -> DataIoResult<Option<ArrayRef>> {
+    let schema = batch.schema();
+    let Some(index) = schema
+        .fields()
+        .iter()
+        .position(|field| field.name().eq_ignore_ascii_case(name))
+    else {
+        return Ok(None);
+    };
+
+    cast(batch.column(index), data_type)
+        .map(Some)
+        .map_err(|e| columnar_error(path, format!("column '{}': {}", name, e)))
+}
+
+fn required_column(
+    path: &str,
+    batch: &RecordBatch,
+    name: &str,
+    data_type: &DataType,
+) -> DataIoResult<ArrayRef> {
+    column_as(path, batch, name, data_type)?.ok_or_else(|| DataIoError::BadHeader {
+        path: path.to_string(),
+        message: format!("missing column '{}'", name),
+    })
+}
+
+fn store_from_batches(path: &str, batches: &[RecordBatch]) -> DataIoResult<MarketDataStore> {
+    let fallback_ticker = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_uppercase())
+        .unwrap_or_default();
+
+    let mut bars_by_ticker: BTreeMap<String, Vec<PriceBar>> = BTreeMap::new();
+    // Số dòng đã đọc, dùng làm "line" khi báo lỗi
+    let mut row_offset: u64 = 0;
+
+    for batch in batches {
+        let dates = required_column(path, batch, "date", &DataType::Date32)?;
+        let close = required_column(path, batch, "close", &DataType::Float64)?;
+        let tickers = column_as(path, batch, "ticker", &DataType::Utf8)?;
+        let open = column_as(path, batch, "open", &DataType::Float64)?;
+        let high = column_as(path, batch, "high", &DataType::Float64)?;
+        let low = column_as(path, batch, "low", &DataType::Float64)?;
+        let volume = column_as(path, batch, "volume", &DataType::Int64)?;
+
+        let dates = downcast::<Date32Array>(&dates);
+        let close = downcast::<Float64Array>(&close);
+        let tickers = tickers.as_ref().map(downcast::<StringArray>);
+        let open = open.as_ref().map(downcast::<Float64Array>);
+        let high = high.as_ref().map(downcast::<Float64Array>);
+        let low = low.as_ref().map(downcast::<Float64Array>);
+        let volume = volume.as_ref().map(downcast::<Int64Array>);
+
+        for row in 0..batch.num_rows() {
+            let line = row_offset + row as u64 + 1;
+
+            // Bỏ qua dòng không có giá (null)
+            if close.is_null(row) {
+                continue;
+            }
+            if dates.is_null(row) {
+                return Err(DataIoError::MalformedRow {
+                    line,
+                    message: "null date".to_string(),
+                });
+            }
+
+            let ticker = match tickers {
+                Some(tickers) if tickers.is_valid(row) => tickers.value(row).to_string(),
+                _ => fallback_ticker.clone(),
+            };
+            let close_value = close.value(row);
+            let value_or_close = |array: Option<&Float64Array>| match array {
+                Some(array) if array.is_valid(row) => array.value(row),
+                _ => close_value,
+            };
+            let date = epoch() + chrono::Duration::days(dates.value(row) as i64);
+
+            bars_by_ticker.entry(ticker).or_default().push(PriceBar {
+                date,
+                open: value_or_close(open),
+                high: value_or_close(high),
+                low: value_or_close(low),
+                close: close_value,
+                volume: match volume {
+                    Some(volume) if volume.is_valid(row) => volume.value(row),
+                    _ => 0,
+                },
+            });
+        }
+
+        row_offset += batch.num_rows() as u64;
+    }
+
+    let mut store = MarketDataStore::from_bars(bars_by_ticker)?;
+    store.set_source_path(path);
+    Ok(store)
+}
+
+fn downcast<T: 'static>(array: &ArrayRef) -> &T {
+    // An toàn vì cột đã được cast đúng kiểu ở `column_as`
+    array
+        .as_any()
+        .downcast_ref::<T>()
+        .expect("column was cast to the expected type")
+}
+
+/// Gom toàn bộ store thành 1 record batch theo `price_table_schema`
+fn prices_batch(path: &str, store: &MarketDataStore) -> DataIoResult<RecordBatch> {
+    let mut tickers = Vec::new();
+    let mut dates = Vec::new();
+    let mut open = Vec::new();
+    let mut high = Vec::new();
+    let mut low = Vec::new();
+    let mut close = Vec::new();
+    let mut volume = Vec::new();
+
+    for series in store.iter() {
+        tickers.extend(std::iter::repeat_n(series.ticker(), series.len()));
+        dates.extend(
+            series
+                .dates()
+                .iter()
+                .map(|&date| (date - epoch()).num_days() as i32),
+        );
+        open.extend_from_slice(series.opens());
+        high.extend_from_slice(series.highs());
+        low.extend_from_slice(series.lows());
+        close.extend_from_slice(series.closes());
+        volume.extend_from_slice(series.volumes());
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(tickers)),
+        Arc::new(Date32Array::from(dates)),
+        Arc::new(Float64Array::from(open)),
+        Arc::new(Float64Array::from(high)),
+        Arc::new(Float64Array::from(low)),
+        Arc::new(Float64Array::from(close)),
+        Arc::new(Int64Array::from(volume)),
+    ];
+
+    RecordBatch::try_new(price_table_schema(), columns).map_err(|e| columnar_error(path, e))
+}
+
+/// Chia paths thành các record batch dạng long (path, step, price)
+fn path_batches(paths: &[Vec<f64>]) -> impl Iterator<Item = Result<RecordBatch, arrow::error::ArrowError>> + '_ {
+    let rows = paths
+        .iter()
+        .enumerate()
+        .flat_map(|(path_index, path)| {
+            path.iter()
+                .enumerate()
+                .map(move |(step, &price)| (path_index as u32, step as u32, price))
+        });
+
+    let mut rows = rows.peekable();
+    std::iter::from_fn(move || {
+        rows.peek()?;
+        let mut path_ids = Vec::with_capacity(PATH_BATCH_ROWS);
+        let mut steps = Vec::with_capacity(PATH_BATCH_ROWS);
+        let mut prices = Vec::with_capacity(PATH_BATCH_ROWS);
+        for (path_id, step, price) in rows.by_ref().take(PATH_BATCH_ROWS) {
+            path_ids.push(path_id);
+            steps.push(step);
+            prices.push(price);
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt32Array::from(path_ids)),
+            Arc::new(UInt32Array::from(steps)),
+            Arc::new(Float64Array::from(prices)),
+        ];
+        Some(RecordBatch::try_new(paths_table_schema(), columns))
+    })
+}
+
+fn parquet_properties() -> WriterProperties {
+    WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build()
+}
+
+/// Ghi toàn bộ store ra file Parquet (nén Snappy)
+pub fn write_parquet_prices(store: &MarketDataStore, path: &str) -> DataIoResult<()> {
+    let batch = prices_batch(path, store)?;
+    let file = create_file(path)?;
+    let mut writer = ArrowWriter::try_new(file, price_table_schema(), Some(parquet_properties()))
+        .map_err(|e| columnar_error(path, e))?;
+    writer.write(&batch).map_err(|e| columnar_error(path, e))?;
+    writer.close().map_err(|e| columnar_error(path, e))?;
+    Ok(())
+}
+
+/// Ghi toàn bộ store ra file Arrow IPC
+pub fn write_arrow_ipc_prices(store: &MarketDataStore, path: &str) -> DataIoResult<()> {
+    let batch = prices_batch(path, store)?;
+    let file = create_file(path)?;
+    let mut writer =
+        FileWriter::try_new(file, &price_table_schema()).map_err(|e| columnar_error(path, e))?;
+    writer.write(&batch).map_err(|e| columnar_error(path, e))?;
+    writer.finish().map_err(|e| columnar_error(path, e))?;
+    Ok(())
+}
+
+/// Ghi toàn bộ paths mô phỏng ra file Parquet (không giới hạn số paths)
+pub fn write_paths_parquet(paths: &[Vec<f64>], path: &str) -> DataIoResult<()> {
+    let file = create_file(path)?;
+    let mut writer = ArrowWriter::try_new(file, paths_table_schema(), Some(parquet_properties()))
+        .map_err(|e| columnar_error(path, e))?;
+    for batch in path_batches(paths) {
+        let batch = batch.map_err(|e| columnar_error(path, e))?;
+        writer.write(&batch).map_err(|e| columnar_error(path, e))?;
+    }
+    writer.close().map_err(|e| columnar_error(path, e))?;
+    Ok(())
+}
+
+/// Ghi toàn bộ paths mô phỏng ra file Arrow IPC
+pub fn write_paths_arrow_ipc(paths: &[Vec<f64>], path: &str) -> DataIoResult<()> {
+    let file = create_file(path)?;
+    let mut writer =
+        FileWriter::try_new(file, &paths_table_schema()).map_err(|e| columnar_error(path, e))?;
+    for batch in path_batches(paths) {
+        let batch = batch.map_err(|e| columnar_error(path, e))?;
+        writer.write(&batch).map_err(|e| columnar_error(path, e))?;
+    }
+    writer.finish().map_err(|e| columnar_error(path, e))?;
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use chrono::NaiveDate;
+use csv::ReaderBuilder;
+
+use crate::error::{DataIoError, DataIoResult};
+use crate::market_data::MarketDataStore;
+use crate::price_series::{PriceBar, PriceSeries};
+
+/// Định dạng ngày chấp nhận trong file sự kiện doanh nghiệp
+const ACTION_DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%Y%m%d", "%d/%m/%Y"];
+
+/// Một sự kiện doanh nghiệp (chia tách, cổ tức cổ phiếu, thưởng, cổ tức tiền mặt)
+///
+/// - `ratio`: số cổ phiếu sau sự kiện / trước sự kiện. Ví dụ chia tách 1:2 → 2.0,
+///   cổ tức cổ phiếu 20% → 1.2, chỉ trả cổ tức tiền mặt → 1.0
+/// - `cash_dividend`: cổ tức tiền mặt trên 1 cổ phiếu (cùng đơn vị với giá, CafeF dùng nghìn đồng)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorporateAction {
+    pub ticker: String,
+    pub ex_date: NaiveDate,
+    pub ratio: f64,
+    pub cash_dividend: f64,
+}
+
+impl CorporateAction {
+    /// Hệ số điều chỉnh cho các giá trước ngày GDKHQ
+    ///
+    /// factor = (C - D) / (C × ratio), với C là giá đóng cửa phiên liền trước ngày GDKHQ
+    pub fn adjustment_factor(&self, previous_close: f64) -> DataIoResult<f64> {
+        let invalid = |message: String| DataIoError::InvalidCorporateAction {
+            ticker: self.ticker.clone(),
+            ex_date: self.ex_date,
+            message,
+        };
+
+        if !self.ratio.is_finite() || self.ratio <= 0.0 {
+            return Err(invalid(format!("ratio must be positive, got {}", self.ratio)));
+        }
+        if !self.cash_dividend.is_finite() || self.cash_dividend < 0.0 {
+            return Err(invalid(format!(
+                "cash dividend must be non-negative, got {}",
+                self.cash_dividend
+            )));
+        }
+        if self.cash_dividend >= previous_close {
+            return Err(invalid(format!(
+                "cash dividend {} is not below previous close {}",
+                self.cash_dividend, previous_close
+            )));
+        }
+
+        Ok((previous_close - self.cash_dividend) / (previous_close * self.ratio))
+    }
+}
+
+/// Đọc file sự kiện doanh nghiệp với header `Ticker,ExDate,Ratio,CashDividend`
+///
+/// Cột `Ratio` / `CashDividend` để trống được hiểu là 1.0 / 0.0
+pub fn load_corporate_actions(csv_path: &str) -> DataIoResult<Vec<CorporateAction>> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .trim(csv::Trim::All)
+        .from_path(csv_path)
+        .map_err(|e| DataIoError::from_csv(csv_path, e))?;
+
+    let headers = reader
+        .headers()
+        .map_err(|e| DataIoError::from_csv(csv_path, e))?
+        .clone();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+
+    let (ticker_col, date_col) = match (column("Ticker"), column("ExDate")) {
+        (Some(ticker), Some(date)) => (ticker, date),
+        _ => {
+            return Err(DataIoError::BadHeader {
+                path: csv_path.to_string(),
+                message: "expected columns Ticker,ExDate,Ratio,CashDividend".to_string(),
+            })
+        }
+    };
+    let ratio_col = column("Ratio");
+    let cash_col = column("CashDividend");
+
+    let mut actions = Vec::new();
+
+    for result in reader.records() {
+        let row = result.map_err(|e| DataIoError::from_csv(csv_path, e))?;
+        let line = row.position().map(|pos| pos.line()).unwrap_or(0);
+        let malformed = |message: String| DataIoError::MalformedRow { line, message };
+
+        let date_text = row.get(date_col).unwrap_or("");
+        let ex_date = ACTION_DATE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(date_text, format).ok())
+            .ok_or_else(|| malformed(format!("invalid ex-date '{}'", date_text)))?;
+
+        let number = |index: Option<usize>, default: f64| -> DataIoResult<f64> {
+            match index.and_then(|i| row.get(i)).filter(|text| !text.is_empty()) {
+                Some(text) => text
+                    .parse::<f64>()
+                    .map_err(|_| malformed(format!("invalid number '{}'", text))),
+                None => Ok(default),
+            }
+        };
+
+        actions.push(CorporateAction {
+            ticker: row.get(ticker_col).unwrap_or("").to_string(),
+            ex_date,
+            ratio: number(ratio_col, 1.0)?,
+            cash_dividend: number(cash_col, 0.0)?,
+        });
+    }
+
+    Ok(actions)
+}
+
+/// Điều chỉnh ngược (back-adjust) giá lịch sử theo các sự kiện của đúng ticker của series
+///
+/// Giá OHLC trước mỗi ngày GDKHQ được nhân với tích các hệ số điều chỉnh của những sự kiện
+/// xảy ra sau đó, volume được nhân với tỷ lệ chia tách. Giá từ ngày GDKHQ cuối cùng trở đi
+/// giữ nguyên, nên log-returns không còn các cú "rơi" giả do chia cổ tức.
+/// Sự kiện nằm ngoài khoảng dữ liệu bị bỏ qua.
+pub fn adjust_for_corporate_actions(
+    series: &PriceSeries,
+    actions: &[CorporateAction],
+) -> DataIoResult<PriceSeries> {
+    let dates = series.dates();
+    let closes = series.closes();
+
+    // Hệ số giá và hệ số volume cho từng phiên, tích lũy từ sự kiện mới nhất về quá khứ
+    let mut price_factors = vec![1.0; series.len()];
+    let mut volume_factors = vec![1.0; series.len()];
+
+    for action in actions.iter().filter(|a| a.ticker == series.ticker()) {
+        // Phiên đầu tiên có ngày >= ngày GDKHQ
+        let ex_index = dates.partition_point(|&d| d < action.ex_date);
+        if ex_index == 0 || ex_index >= series.len() {
+            continue;
+        }
+
+        let factor = action.adjustment_factor(closes[ex_index - 1])?;
+        for (price_factor, volume_factor) in price_factors[..ex_index]
+            .iter_mut()
+            .zip(&mut volume_factors[..ex_index])
+        {
+            *price_factor *= factor;
+            *volume_factor *= action.ratio;
+        }
+    }
+
+    let bars = (0..series.len())
+        .filter_map(|i| series.bar(i))
+        .zip(price_factors.iter().zip(&volume_factors))
+        .map(|(bar, (&price_factor, &volume_factor))| PriceBar {
+            date: bar.date,
+            open: bar.open * price_factor,
+            high: bar.high * price_factor,
+            low: bar.low * price_factor,
+            close: bar.close * price_factor,
+            volume: (bar.volume as f64 * volume_factor).round() as i64,
+        })
+        .collect();
+
+    let mut adjusted = PriceSeries::from_bars(series.ticker(), bars)?;
+    adjusted.set_duplicate_dates(series.duplicate_dates().to_vec());
+    Ok(adjusted)
+}
+
+impl MarketDataStore {
+    /// Trả về store mới với mọi series đã được điều chỉnh theo sự kiện doanh nghiệp
+    pub fn adjusted(&self, actions: &[CorporateAction]) -> DataIoResult<MarketDataStore> {
+        let mut adjusted = self.clone();
+        adjusted.map_series(|series| adjust_for_corporate_actions(series, actions))?;
+        Ok(adjusted)
+    }
+}
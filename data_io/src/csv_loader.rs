@@ -1,69 +1,49 @@
-use csv::ReaderBuilder;
-use std::error::Error;
-use crate::stock_price::StockPrice;
+use crate::error::{DataIoError, DataIoResult};
+use crate::market_data::MarketDataStore;
+use crate::price_series::PriceSeries;
+
+/// Load chuỗi giá (đã sort theo ngày tăng dần) của 1 ticker
+///
+/// Lưu ý: mỗi lần gọi đều đọc lại cả file. Nếu cần tra cứu nhiều lần,
+/// dùng `MarketDataStore::load_csv` 1 lần rồi gọi `get`.
+pub fn load_price_series(
+    csv_path: &str,
+    ticker: &str
+) -> DataIoResult<PriceSeries> {
+    let store = MarketDataStore::load_csv(csv_path)?;
+    store.require(ticker).cloned()
+}
 
+/// Load giá đóng cửa của 1 ticker theo thứ tự thời gian (cũ → mới)
 pub fn load_closes_for_ticker(
     csv_path: &str,
     ticker: &str
-) -> Result<Vec<f64>, Box<dyn Error>> {
-    let mut reader = ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(csv_path)?;
-
-    let mut closes = Vec::new();
-
-    for result in reader.deserialize() {
-        let record: StockPrice = result?;
-        if record.ticker == ticker {
-            closes.push(record.close);
-        }
-    }
-
-    Ok(closes)
+) -> DataIoResult<Vec<f64>> {
+    let series = load_price_series(csv_path, ticker)?;
+    Ok(series.closes().to_vec())
 }
 
 /// Load all tickers 
-pub fn load_available_tickers(csv_path: &str) -> Result<Vec<String>, Box<dyn Error>> {
-    let mut reader = ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(csv_path)?;
-
-    let mut tickers = std::collections::HashSet::new();
-
-    for result in reader.deserialize() {
-        let record: StockPrice = result?;
-        tickers.insert(record.ticker);
-    }
-
-    let mut ticker_list: Vec<String> = tickers.into_iter().collect();
-    ticker_list.sort();
-    Ok(ticker_list)
+pub fn load_available_tickers(csv_path: &str) -> DataIoResult<Vec<String>> {
+    let store = MarketDataStore::load_csv(csv_path)?;
+    Ok(store.tickers())
 }
 
 /// Get info ticker
-pub fn get_stock_info(csv_path: &str, ticker: &str) -> Result<(String, String, usize, f64), Box<dyn Error>> {
-    let mut reader = ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(csv_path)?;
-
-    let mut records = Vec::new();
-    for result in reader.deserialize() {
-        let record: StockPrice = result?;
-        if record.ticker == ticker {
-            records.push(record);
-        }
-    }
-
-    if records.is_empty() {
-        return Err(format!("No data found for ticker {}", ticker).into());
-    }
+pub fn get_stock_info(csv_path: &str, ticker: &str) -> DataIoResult<(String, String, usize, f64)> {
+    let series = load_price_series(csv_path, ticker)?;
+    stock_info(&series).ok_or_else(|| DataIoError::UnknownTicker {
+        ticker: ticker.to_string(),
+    })
+}
 
-    let first_date = &records[0].date;
-    let last_date = &records[records.len() - 1].date;
-    let record_count = records.len();
-    let last_price = records[records.len() - 1].close;
+/// Thông tin tóm tắt của series: (ticker, date range, số records, giá cuối)
+pub fn stock_info(series: &PriceSeries) -> Option<(String, String, usize, f64)> {
+    let first_date = series.first_date()?;
+    let last_date = series.last_date()?;
+    let last_price = series.last_close()?;
 
     let date_range = format!("{} to {}", first_date, last_date);
 
-    Ok((ticker.to_string(), date_range, record_count, last_price))
-}
\ No newline at end of file
+    Some((series.ticker().to_string(), date_range, series.len(), last_price))
+}
@@ -0,0 +1,194 @@
+use chrono::NaiveDate;
+use std::fmt;
+
+/// Các loại lỗi khi đọc / kiểm tra dữ liệu giá
+///
+/// Mỗi variant mang đủ thông tin (đường dẫn, số dòng, ticker, ngày...) để GUI
+/// hiển thị thông báo cụ thể và để caller xử lý riêng từng trường hợp.
+#[derive(Debug)]
+pub enum DataIoError {
+    /// Không tìm thấy file dữ liệu
+    FileNotFound { path: String },
+    /// Lỗi I/O khác khi đọc file
+    Io { path: String, source: std::io::Error },
+    /// Dòng header thiếu cột bắt buộc hoặc không đúng format
+    BadHeader { path: String, message: String },
+    /// Một dòng dữ liệu không parse được (line tính từ 1, bao gồm dòng header)
+    MalformedRow { line: u64, message: String },
+    /// Ticker không có trong dữ liệu
+    UnknownTicker { ticker: String },
+    /// Giá <= 0 hoặc không hữu hạn (NaN, inf)
+    NonPositivePrice { ticker: String, date: NaiveDate, field: &'static str, price: f64 },
+    /// Volume âm
+    NegativeVolume { ticker: String, date: NaiveDate, volume: i64 },
+    /// Không đủ số phiên lịch sử để tính toán
+    InsufficientHistory { ticker: String, required: usize, available: usize },
+    /// Lỗi đọc/ghi file dạng cột (Parquet, Arrow IPC) hoặc định dạng chưa được hỗ trợ
+    Columnar { path: String, message: String },
+    /// Sự kiện doanh nghiệp không hợp lệ (tỷ lệ <= 0, cổ tức >= giá...)
+    InvalidCorporateAction { ticker: String, ex_date: NaiveDate, message: String },
+    /// Thư mục / glob pattern không hợp lệ hoặc không khớp file dữ liệu nào
+    InvalidSource { path: String, message: String },
+    /// 2 nguồn dữ liệu có giá khác nhau cho cùng 1 ticker và ngày
+    ConflictingBar { ticker: String, date: NaiveDate, message: String },
+    /// Phiên mới nằm trước phiên cuối cùng đã có (append chỉ nhận dữ liệu nối tiếp)
+    OutOfOrderBar { ticker: String, date: NaiveDate, last_date: NaiveDate },
+    /// File cache hỏng hoặc khác phiên bản
+    InvalidCache { path: String, message: String },
+    /// Lỗi đọc/ghi database SQLite hoặc backend chưa được bật
+    Database { path: String, message: String },
+}
+
+pub type DataIoResult<T> = Result<T, DataIoError>;
+
+impl DataIoError {
+    /// Chuyển lỗi của crate `csv` sang DataIoError (giữ lại số dòng nếu có)
+    pub(crate) fn from_csv(path: &str, err: csv::Error) -> Self {
+        let line = err.position().map(|pos| pos.line());
+        match err.into_kind() {
+            csv::ErrorKind::Io(source) => Self::from_io(path, source),
+            kind => {
+                let message = match &kind {
+                    csv::ErrorKind::Deserialize { err, .. } => err.to_string(),
+                    csv::ErrorKind::UnequalLengths { expected_len, len, .. } => {
+                        format!("expected {} fields, found {}", expected_len, len)
+                    }
+                    csv::ErrorKind::Utf8 { err, .. } => err.to_string(),
+                    other => format!("{:?}", other),
+                };
+                DataIoError::MalformedRow {
+                    line: line.unwrap_or(0),
+                    message,
+                }
+            }
+        }
+    }
+
+    pub(crate) fn from_io(path: &str, source: std::io::Error) -> Self {
+        if source.kind() == std::io::ErrorKind::NotFound {
+            DataIoError::FileNotFound {
+                path: path.to_string(),
+            }
+        } else {
+            DataIoError::Io {
+                path: path.to_string(),
+                source,
+            }
+        }
+    }
+}
+
+impl fmt::Display for DataIoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataIoError::FileNotFound { path } => write!(f, "File not found: {}", path),
+            DataIoError::Io { path, source } => write!(f, "Cannot read {}: {}", path, source),
+            DataIoError::BadHeader { path, message } => {
+                write!(f, "Invalid header in {}: {}", path, message)
+            }
+            DataIoError::MalformedRow { line, message } => {
+                write!(f, "Malformed row at line {}: {}", line, message)
+            }
+            DataIoError::UnknownTicker { ticker } => write!(f, "No data found for ticker {}", ticker),
+            DataIoError::NonPositivePrice { ticker, date, field, price } => write!(
+                f,
+                "Invalid {} price {} for ticker {} on {}",
+                field, price, ticker, date
+            ),
+            DataIoError::NegativeVolume { ticker, date, volume } => write!(
+                f,
+                "Negative volume {} for ticker {} on {}",
+                volume, ticker, date
+            ),
+            DataIoError::InsufficientHistory { ticker, required, available } => write!(
+                f,
+                "Ticker {} has {} records, at least {} are required",
+                ticker, available, required
+            ),
+            DataIoError::Columnar { path, message } => write!(f, "Cannot process {}: {}", path, message),
+            DataIoError::InvalidCorporateAction { ticker, ex_date, message } => write!(
+                f,
+                "Invalid corporate action for {} on {}: {}",
+                ticker, ex_date, message
+            ),
+            DataIoError::InvalidSource { path, message } => {
+                write!(f, "Invalid data source {}: {}", path, message)
+            }
+            DataIoError::ConflictingBar { ticker, date, message } => write!(
+                f,
+                "Conflicting data for {} on {}: {}",
+                ticker, date, message
+            ),
+            DataIoError::OutOfOrderBar { ticker, date, last_date } => write!(
+                f,
+                "Bar for {} on {} is older than the last loaded day {}",
+                ticker, date, last_date
+            ),
+            DataIoError::InvalidCache { path, message } => {
+                write!(f, "Invalid cache file {}: {}", path, message)
+            }
+            DataIoError::Database { path, message } => {
+                write!(f, "Database error in {}: {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DataIoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DataIoError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Lỗi của các hàm thống kê có kiểm tra đầu vào (`mean`, `std_dev`, `quantile`...)
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatsError {
+    /// Không có giá trị nào (hoặc chỉ toàn NaN đã bị bỏ qua)
+    Empty,
+    /// Không đủ số giá trị, vd độ lệch chuẩn mẫu cần ít nhất 2
+    InsufficientData { required: usize, available: usize },
+    /// Gặp NaN khi dùng `NanPolicy::Reject` (index trong slice đầu vào)
+    NanValue { index: usize },
+    /// Xác suất của quantile nằm ngoài [0, 1]
+    InvalidProbability { p: f64 },
+    /// Tối ưu hóa (vd maximum likelihood) không dừng ở giá trị hữu hạn
+    NoConvergence,
+    /// Mọi giá trị bằng nhau nên các moment chuẩn hóa (skewness, kurtosis) không xác định
+    ZeroVariance,    /// Chuỗi thứ `index` có độ dài `found` khác độ dài `expected` của chuỗi đầu tiên
+    LengthMismatch { index: usize, expected: usize, found: usize },
+}
+
+pub type StatsResult<T> = Result<T, StatsError>;
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsError::Empty => write!(f, "No values to compute the statistic from"),
+            StatsError::InsufficientData { required, available } => write!(
+                f,
+                "At least {} values are required, got {}",
+                required, available
+            ),
+            StatsError::NanValue { index } => write!(f, "NaN value at index {}", index),
+            StatsError::InvalidProbability { p } => {
+                write!(f, "Probability {} is outside [0, 1]", p)
+            }
+            StatsError::NoConvergence => {
+                write!(f, "The optimizer did not converge to a finite likelihood")
+            }
+            StatsError::ZeroVariance => {
+                write!(f, "All values are equal, so their distribution shape is undefined")
+            }
+            StatsError::LengthMismatch { index, expected, found } => write!(
+                f,
+                "Series {} has {} values, expected {} like the first series",
+                index, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
@@ -0,0 +1,409 @@
+use chrono::NaiveDate;
+
+use crate::error::{StatsError, StatsResult};
+use crate::price_series::PriceSeries;
+use crate::statistics::{
+    calculate_excess_kurtosis, calculate_mean, calculate_skewness, calculate_stdev,
+    estimate_volatility, ln_gamma, VolatilityEstimator,
+};
+
+/// λ chuẩn của RiskMetrics cho dữ liệu ngày
+pub const RISKMETRICS_LAMBDA: f64 = 0.94;
+
+/// Khoảng dữ liệu dùng để ước lượng μ/σ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EstimationWindow {
+    /// Toàn bộ lịch sử
+    #[default]
+    Full,
+    /// N phiên gần nhất (N returns → cần N + 1 giá)
+    LastDays(usize),
+    /// Khoảng ngày [from, to], bao gồm 2 đầu
+    DateRange { from: NaiveDate, to: NaiveDate },
+}
+
+impl EstimationWindow {
+    /// Cắt series theo cửa sổ ước lượng
+    pub fn apply(&self, series: &PriceSeries) -> PriceSeries {
+        match *self {
+            EstimationWindow::Full => series.clone(),
+            EstimationWindow::LastDays(days) => {
+                let start = series.len().saturating_sub(days + 1);
+                match (series.dates().get(start), series.last_date()) {
+                    (Some(&from), Some(to)) => series.slice_by_date(from, to),
+                    _ => series.clone(),
+                }
+            }
+            EstimationWindow::DateRange { from, to } => series.slice_by_date(from, to),
+        }
+    }
+}
+
+/// Trọng số EWMA cho n quan sát theo thứ tự thời gian: quan sát mới nhất có trọng số 1,
+/// quan sát trước đó λ, λ², ...
+fn ewma_weights(n: usize, lambda: f64) -> impl Iterator<Item = f64> {
+    (0..n).map(move |i| lambda.powi((n - 1 - i) as i32))
+}
+
+/// Trung bình có trọng số mũ của returns (theo thứ tự thời gian, cũ → mới)
+pub fn ewma_mean(returns: &[f64], lambda: f64) -> f64 {
+    let weights: Vec<f64> = ewma_weights(returns.len(), lambda).collect();
+    let total: f64 = weights.iter().sum();
+    returns.iter().zip(&weights).map(|(r, w)| r * w).sum::<f64>() / total
+}
+
+/// σ theo RiskMetrics: σ² = Σ λ^k · r²_{t-k} / Σ λ^k (giả định mean = 0 như RiskMetrics)
+///
+/// Chuẩn hóa bằng tổng trọng số nên không bị lệch khi chuỗi ngắn;
+/// với chuỗi dài kết quả trùng với dạng đệ quy σ²_t = λσ²_{t-1} + (1-λ)r²_t.
+pub fn ewma_volatility(returns: &[f64], lambda: f64) -> f64 {
+    let weights: Vec<f64> = ewma_weights(returns.len(), lambda).collect();
+    let total: f64 = weights.iter().sum();
+    (returns.iter().zip(&weights).map(|(r, w)| w * r * r).sum::<f64>() / total).sqrt()
+}
+
+/// μ/σ ước lượng trên cửa sổ kết thúc tại `date`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollingEstimate {
+    pub date: NaiveDate,
+    pub mu: f64,
+    pub sigma: f64,
+}
+
+/// Ước lượng μ/σ trên cửa sổ trượt `window` returns, mỗi phiên 1 điểm
+///
+/// μ là trung bình log-returns trong cửa sổ, σ theo `estimator`.
+/// Điểm đầu tiên ứng với phiên thứ `window` (cần `window + 1` giá).
+pub fn rolling_estimates(
+    series: &PriceSeries,
+    window: usize,
+    estimator: VolatilityEstimator,
+) -> Vec<RollingEstimate> {
+    if window < 2 || series.len() <= window {
+        return Vec::new();
+    }
+
+    let returns = series.log_returns();
+    (window..series.len())
+        .map(|end| {
+            // Giá [end - window, end] ↔ returns [end - window, end)
+            let prices = end - window..end + 1;
+            RollingEstimate {
+                date: series.dates()[end],
+                mu: calculate_mean(&returns[end - window..end]),
+                sigma: estimate_volatility(
+                    estimator,
+                    &series.opens()[prices.clone()],
+                    &series.highs()[prices.clone()],
+                    &series.lows()[prices.clone()],
+                    &series.closes()[prices],
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Ngưỡng mặc định (số lần σ) để coi 1 log-return là jump khi khởi tạo
+pub const DEFAULT_JUMP_THRESHOLD: f64 = 3.0;
+/// Số vòng lặp tối đa của bước lọc ngưỡng và của EM
+const JUMP_FILTER_MAX_ITERATIONS: usize = 50;
+const JUMP_EM_MAX_ITERATIONS: usize = 500;
+/// jump_std² không nhỏ hơn tỷ lệ này của σ² để EM không suy biến quanh 1 điểm
+const JUMP_VARIANCE_FLOOR: f64 = 0.01;
+/// λ lớn hơn mức này (jump hơn 1/10 số phiên) là chế độ biến động chứ không còn là jump,
+/// thường gặp ở mã ít thanh khoản có nhiều phiên đứng giá; khi đó giữ kết quả lọc ngưỡng
+const JUMP_MAX_INTENSITY: f64 = 0.1;
+
+/// Tham số Merton jump-diffusion theo phiên ước lượng từ log-returns ngày
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JumpDiffusionEstimate {
+    pub mu: f64,
+    /// σ của phần diffusion
+    pub sigma: f64,
+    /// Số jump kỳ vọng mỗi phiên
+    pub lambda: f64,
+    /// Trung bình / độ lệch chuẩn log của hệ số jump
+    pub jump_mean: f64,
+    pub jump_std: f64,
+    /// Số phiên có xác suất hậu nghiệm là jump > 50%
+    pub jump_count: usize,
+    pub sample_size: usize,
+}
+
+/// Mật độ N(mean, variance) tại x
+fn normal_density(x: f64, mean: f64, variance: f64) -> f64 {
+    (-(x - mean).powi(2) / (2.0 * variance)).exp() / (2.0 * std::f64::consts::PI * variance).sqrt()
+}
+
+/// Ước lượng jump-diffusion từ log-returns ngày
+///
+/// 1. Lọc ngưỡng lặp: tính mean / σ trên các phiên chưa bị đánh dấu, đánh dấu lại các phiên
+///    có |r - mean| > `threshold` · σ là jump, đến khi tập jump không đổi.
+/// 2. EM trên hỗn hợp 2 phân phối chuẩn (mỗi phiên có tối đa 1 jump, hợp lý khi λ nhỏ):
+///    r ~ (1 - λ)·N(a, σ²) + λ·N(a + jump_mean, σ² + jump_std²), khởi tạo từ bước 1.
+///    Bước này sửa lệch của lọc ngưỡng: phiên bình thường vượt ngưỡng do ngẫu nhiên
+///    và jump nhỏ nằm dưới ngưỡng. Nếu EM cho λ > 0.1 (hỗn hợp mô tả 2 chế độ biến động
+///    chứ không phải jump hiếm) thì giữ kết quả của bước 1.
+///
+/// μ được chọn để mô phỏng giữ đúng mean log-return lịch sử:
+/// μ = r̄ + σ²/2 + λ·(κ - jump_mean), với κ = e^(jump_mean + jump_std²/2) - 1.
+pub fn calibrate_jump_diffusion(
+    returns: &[f64],
+    threshold: f64,
+) -> StatsResult<JumpDiffusionEstimate> {
+    if let Some(index) = returns.iter().position(|r| r.is_nan()) {
+        return Err(StatsError::NanValue { index });
+    }
+    if returns.len() < 3 {
+        return Err(StatsError::InsufficientData {
+            required: 3,
+            available: returns.len(),
+        });
+    }
+
+    let mut is_jump = vec![false; returns.len()];
+    let (mut mean, mut sigma);
+    let mut iterations = 0;
+    loop {
+        let diffusion: Vec<f64> = returns
+            .iter()
+            .zip(&is_jump)
+            .filter(|(_, &jump)| !jump)
+            .map(|(&r, _)| r)
+            .collect();
+        if diffusion.len() < 2 {
+            return Err(StatsError::InsufficientData {
+                required: 2,
+                available: diffusion.len(),
+            });
+        }
+        mean = calculate_mean(&diffusion);
+        sigma = calculate_stdev(&diffusion, mean);
+
+        let flagged: Vec<bool> = returns
+            .iter()
+            .map(|r| (r - mean).abs() > threshold * sigma)
+            .collect();
+        iterations += 1;
+        if flagged == is_jump || iterations >= JUMP_FILTER_MAX_ITERATIONS {
+            break;
+        }
+        is_jump = flagged;
+    }
+
+    let jumps: Vec<f64> = returns
+        .iter()
+        .zip(&is_jump)
+        .filter(|(_, &jump)| jump)
+        .map(|(&r, _)| r)
+        .collect();
+    let mut variance = sigma * sigma;
+    let (mut lambda, mut jump_mean, mut jump_variance) = if jumps.is_empty() {
+        (0.0, 0.0, 0.0)
+    } else {
+        let jump_return_mean = calculate_mean(&jumps);
+        let jump_variance = if jumps.len() > 1 {
+            calculate_stdev(&jumps, jump_return_mean).powi(2) - variance
+        } else {
+            0.0
+        };
+        (
+            jumps.len() as f64 / returns.len() as f64,
+            jump_return_mean - mean,
+            jump_variance.max(JUMP_VARIANCE_FLOOR * variance),
+        )
+    };
+
+    let threshold_estimate = (variance, lambda, jump_mean, jump_variance);
+    let mut weights: Vec<f64> = is_jump.iter().map(|&jump| if jump { 1.0 } else { 0.0 }).collect();
+    if lambda > 0.0 {
+        let mut log_likelihood = f64::NEG_INFINITY;
+        for _ in 0..JUMP_EM_MAX_ITERATIONS {
+            // E-step: xác suất hậu nghiệm mỗi phiên có jump
+            let mut total = 0.0;
+            for (w, &r) in weights.iter_mut().zip(returns) {
+                let normal = (1.0 - lambda) * normal_density(r, mean, variance);
+                let jump = lambda * normal_density(r, mean + jump_mean, variance + jump_variance);
+                let density = normal + jump;
+                *w = if density > 0.0 { jump / density } else { 1.0 };
+                total += density.max(f64::MIN_POSITIVE).ln();
+            }
+
+            // M-step: a, σ² từ phần diffusion, jump_mean, jump_std² từ phần jump
+            let jump_weight: f64 = weights.iter().sum();
+            let normal_weight = returns.len() as f64 - jump_weight;
+            if jump_weight < 1e-6 || normal_weight < 2.0 {
+                break;
+            }
+            lambda = jump_weight / returns.len() as f64;
+            mean = returns.iter().zip(&weights).map(|(r, w)| (1.0 - w) * r).sum::<f64>()
+                / normal_weight;
+            variance = returns
+                .iter()
+                .zip(&weights)
+                .map(|(r, w)| (1.0 - w) * (r - mean).powi(2))
+                .sum::<f64>()
+                / normal_weight;
+            jump_mean = returns.iter().zip(&weights).map(|(r, w)| w * (r - mean)).sum::<f64>()
+                / jump_weight;
+            jump_variance = (returns
+                .iter()
+                .zip(&weights)
+                .map(|(r, w)| w * (r - mean - jump_mean).powi(2))
+                .sum::<f64>()
+                / jump_weight
+                - variance)
+                .max(JUMP_VARIANCE_FLOOR * variance);
+
+            if (total - log_likelihood).abs() < 1e-10 * total.abs().max(1.0) {
+                break;
+            }
+            log_likelihood = total;
+        }
+    }
+
+    if lambda > JUMP_MAX_INTENSITY {
+        (variance, lambda, jump_mean, jump_variance) = threshold_estimate;
+        weights = is_jump.iter().map(|&jump| if jump { 1.0 } else { 0.0 }).collect();
+    }
+
+    let (sigma, jump_std) = (variance.sqrt(), jump_variance.sqrt());
+    let kappa = (jump_mean + 0.5 * jump_variance).exp() - 1.0;
+    Ok(JumpDiffusionEstimate {
+        mu: calculate_mean(returns) + 0.5 * variance + lambda * (kappa - jump_mean),
+        sigma,
+        lambda,
+        jump_mean,
+        jump_std,
+        jump_count: weights.iter().filter(|&&w| w > 0.5).count(),
+        sample_size: returns.len(),
+    })
+}
+
+/// Số returns tối thiểu để ước lượng skewness / kurtosis
+pub const MIN_FAT_TAIL_OBSERVATIONS: usize = 30;
+/// ν lớn nhất khi dữ liệu không có đuôi dày (Student-t gần như chuẩn)
+const MAX_STUDENT_T_DOF: f64 = 100.0;
+/// |λ| của skewed-t và |β/α| của NIG giữ dưới mức này
+const MAX_ASYMMETRY: f64 = 0.95;
+/// ν nhỏ nhất của skewed-t khi khớp kurtosis (kurtosis tồn tại khi ν > 4)
+const SKEWED_T_MIN_DOF: f64 = 4.05;
+const SKEWED_T_FIT_ITERATIONS: usize = 30;
+/// Cận dưới của K - 4S²/3 trong moment NIG (ngoài miền này NIG không khớp được)
+const NIG_MIN_KURTOSIS_MARGIN: f64 = 0.03;
+
+/// Tham số hình dạng của các phân phối innovation đuôi dày, khớp theo moment của log-returns
+///
+/// Chỉ mô tả hình dạng của cú sốc chuẩn hóa (trung bình 0, phương sai 1), μ/σ ước lượng riêng.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FatTailEstimate {
+    pub skewness: f64,
+    pub excess_kurtosis: f64,
+    /// ν của Student-t: 6 / (ν - 4) = excess kurtosis
+    pub student_t_nu: f64,
+    /// ν, λ của skewed-t Hansen khớp cả skewness và kurtosis
+    pub skewed_t_nu: f64,
+    pub skewed_t_lambda: f64,
+    /// α, β của NIG khớp cả skewness và kurtosis
+    pub nig_alpha: f64,
+    pub nig_beta: f64,
+    pub sample_size: usize,
+}
+
+/// Skewness và excess kurtosis của skewed-t Hansen chuẩn hóa (Jondeau & Rockinger 2003), cần ν > 4
+fn skewed_t_moments(nu: f64, lambda: f64) -> (f64, f64) {
+    let c = (ln_gamma(0.5 * (nu + 1.0)) - ln_gamma(0.5 * nu)).exp()
+        / (std::f64::consts::PI * (nu - 2.0)).sqrt();
+    let l2 = lambda * lambda;
+    let a = 4.0 * lambda * c * (nu - 2.0) / (nu - 1.0);
+    let b = (1.0 + 3.0 * l2 - a * a).sqrt();
+    // Moment gốc bậc 2, 3, 4 của biến trước khi trừ a và chia b
+    let m2 = 1.0 + 3.0 * l2;
+    let m3 = 16.0 * c * lambda * (1.0 + l2) * (nu - 2.0).powi(2) / ((nu - 1.0) * (nu - 3.0));
+    let m4 = 3.0 * (nu - 2.0) * (1.0 + 10.0 * l2 + 5.0 * l2 * l2) / (nu - 4.0);
+    let skewness = (m3 - 3.0 * a * m2 + 2.0 * a.powi(3)) / b.powi(3);
+    let kurtosis = (m4 - 4.0 * a * m3 + 6.0 * a * a * m2 - 3.0 * a.powi(4)) / b.powi(4);
+    (skewness, kurtosis - 3.0)
+}
+
+/// Chia đôi trên [low, high] tìm x với f(x) = target, f tăng đơn điệu; kẹp về biên nếu ngoài miền
+fn solve_increasing(f: impl Fn(f64) -> f64, target: f64, mut low: f64, mut high: f64) -> f64 {
+    for _ in 0..60 {
+        let mid = 0.5 * (low + high);
+        if f(mid) < target {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    0.5 * (low + high)
+}
+
+/// Khớp Student-t, skewed-t và NIG với skewness S / excess kurtosis K của log-returns
+///
+/// - Student-t: ν = 4 + 6/K (K ≤ 0 → ν = 100, gần như chuẩn).
+/// - Skewed-t: lặp luân phiên λ khớp S với ν cố định, rồi ν khớp K với λ cố định.
+/// - NIG (δ = 1): S² = 9ρ²/ζ, K = 3(1 + 4ρ²)/ζ với ρ = β/α, ζ = √(α² - β²),
+///   suy ra ζ = 3 / (K - 4S²/3); cần K > 4S²/3, ngoài miền này K được nâng lên biên.
+pub fn fit_fat_tails(returns: &[f64]) -> StatsResult<FatTailEstimate> {
+    if let Some(index) = returns.iter().position(|r| r.is_nan()) {
+        return Err(StatsError::NanValue { index });
+    }
+    if returns.len() < MIN_FAT_TAIL_OBSERVATIONS {
+        return Err(StatsError::InsufficientData {
+            required: MIN_FAT_TAIL_OBSERVATIONS,
+            available: returns.len(),
+        });
+    }
+
+    // Mọi returns bằng nhau: sai số làm tròn của trung bình cho phương sai ~1e-35 thay vì 0
+    // nên so sánh trực tiếp các giá trị
+    if returns.iter().all(|&r| r == returns[0]) {
+        return Err(StatsError::ZeroVariance);
+    }
+    let skewness = calculate_skewness(returns);
+    let excess_kurtosis = calculate_excess_kurtosis(returns);
+    if !skewness.is_finite() || !excess_kurtosis.is_finite() {
+        return Err(StatsError::ZeroVariance);
+    }
+
+    let student_t_nu = if excess_kurtosis > 0.0 {
+        (4.0 + 6.0 / excess_kurtosis).min(MAX_STUDENT_T_DOF)
+    } else {
+        MAX_STUDENT_T_DOF
+    };
+
+    // Skewness tăng theo λ, kurtosis giảm theo ν (tìm theo 1/ν để hàm tăng)
+    let (mut skewed_t_nu, mut skewed_t_lambda) = (student_t_nu, 0.0);
+    for _ in 0..SKEWED_T_FIT_ITERATIONS {
+        skewed_t_lambda = solve_increasing(
+            |lambda| skewed_t_moments(skewed_t_nu, lambda).0,
+            skewness,
+            -MAX_ASYMMETRY,
+            MAX_ASYMMETRY,
+        );
+        let inverse_nu = solve_increasing(
+            |inverse_nu| skewed_t_moments(1.0 / inverse_nu, skewed_t_lambda).1,
+            excess_kurtosis,
+            1.0 / MAX_STUDENT_T_DOF,
+            1.0 / SKEWED_T_MIN_DOF,
+        );
+        skewed_t_nu = 1.0 / inverse_nu;
+    }
+
+    let margin = excess_kurtosis - 4.0 * skewness * skewness / 3.0;
+    let zeta = 3.0 / margin.max(NIG_MIN_KURTOSIS_MARGIN);
+    let rho = ((skewness * skewness * zeta / 9.0).sqrt().min(MAX_ASYMMETRY)).copysign(skewness);
+    let nig_alpha = zeta / (1.0 - rho * rho).sqrt();
+
+    Ok(FatTailEstimate {
+        skewness,
+        excess_kurtosis,
+        student_t_nu,
+        skewed_t_nu,
+        skewed_t_lambda,
+        nig_alpha,
+        nig_beta: rho * nig_alpha,
+        sample_size: returns.len(),
+    })
+}
@@ -0,0 +1,52 @@
+use std::fmt;
+use std::path::Path;
+
+/// Sàn niêm yết của 1 mã cổ phiếu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Exchange {
+    /// Sở GDCK TP.HCM (HOSE)
+    Hsx,
+    /// Sở GDCK Hà Nội
+    Hnx,
+    /// Thị trường UPCoM
+    Upcom,
+}
+
+impl Exchange {
+    pub const ALL: [Exchange; 3] = [Exchange::Hsx, Exchange::Hnx, Exchange::Upcom];
+
+    /// Tên ngắn như trong tên file CafeF: HSX, HNX, UPCOM
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Exchange::Hsx => "HSX",
+            Exchange::Hnx => "HNX",
+            Exchange::Upcom => "UPCOM",
+        }
+    }
+
+    /// Parse tên sàn, không phân biệt hoa thường ("HOSE" được coi là HSX)
+    pub fn parse(name: &str) -> Option<Exchange> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "HSX" | "HOSE" => Some(Exchange::Hsx),
+            "HNX" => Some(Exchange::Hnx),
+            "UPCOM" => Some(Exchange::Upcom),
+            _ => None,
+        }
+    }
+
+    /// Nhận diện sàn từ tên file, ví dụ `CafeF.HNX.Upto10.11.2025.csv` → HNX
+    ///
+    /// Tên file được tách theo ký tự không phải chữ/số, lấy token đầu tiên là tên sàn
+    pub fn from_path(path: &str) -> Option<Exchange> {
+        let file_name = Path::new(path).file_name()?.to_string_lossy().into_owned();
+        file_name
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .find_map(Exchange::parse)
+    }
+}
+
+impl fmt::Display for Exchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,377 @@
+use std::fmt;
+
+use crate::error::{StatsError, StatsResult};
+use crate::statistics::{calculate_mean, calculate_stdev, ln_gamma};
+
+/// Returns được nhân lên khi tối ưu (đơn vị %) để ω, α, β cùng cỡ, giống các thư viện GARCH phổ biến
+const RETURN_SCALE: f64 = 100.0;
+/// Số lần khởi động lại Nelder-Mead từ nghiệm tốt nhất
+const OPTIMIZER_RESTARTS: usize = 3;
+const OPTIMIZER_MAX_ITERATIONS: usize = 5_000;
+/// Cần đủ returns để ước lượng 4-6 tham số ổn định
+pub const MIN_GARCH_OBSERVATIONS: usize = 100;
+
+/// Dạng phương trình phương sai
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GarchKind {
+    /// σ²ₜ = ω + α ε²ₜ₋₁ + β σ²ₜ₋₁
+    #[default]
+    Garch,
+    /// GJR-GARCH: thêm γ ε²ₜ₋₁ khi εₜ₋₁ < 0, tin xấu làm volatility tăng mạnh hơn
+    GjrGarch,
+}
+
+impl GarchKind {
+    pub const ALL: [GarchKind; 2] = [GarchKind::Garch, GarchKind::GjrGarch];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GarchKind::Garch => "GARCH(1,1)",
+            GarchKind::GjrGarch => "GJR-GARCH(1,1)",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        GarchKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Phân phối của innovation chuẩn hóa zₜ = εₜ / σₜ khi tính likelihood
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GarchInnovation {
+    #[default]
+    Normal,
+    /// Student-t chuẩn hóa về phương sai 1, bậc tự do ν > 2 được ước lượng cùng
+    StudentT,
+}
+
+impl GarchInnovation {
+    pub const ALL: [GarchInnovation; 2] = [GarchInnovation::Normal, GarchInnovation::StudentT];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GarchInnovation::Normal => "Normal",
+            GarchInnovation::StudentT => "Student-t",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        GarchInnovation::ALL
+            .into_iter()
+            .find(|innovation| innovation.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Kết quả ước lượng GARCH theo phiên (đơn vị log-return, không phải %)
+///
+/// rₜ = μ + εₜ, εₜ = σₜ zₜ, σ²ₜ = ω + (α + γ·1[εₜ₋₁ < 0]) ε²ₜ₋₁ + β σ²ₜ₋₁.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GarchFit {
+    pub kind: GarchKind,
+    pub innovation: GarchInnovation,
+    pub mu: f64,
+    pub omega: f64,
+    pub alpha: f64,
+    /// 0 với GARCH thường
+    pub gamma: f64,
+    pub beta: f64,
+    /// Bậc tự do của Student-t, None với innovation chuẩn
+    pub nu: Option<f64>,
+    pub log_likelihood: f64,
+    pub sample_size: usize,
+    /// Phương sai có điều kiện của phiên cuối cùng trong mẫu
+    pub last_variance: f64,
+    /// Phương sai dự báo cho phiên kế tiếp, điểm xuất phát khi mô phỏng
+    pub next_variance: f64,
+}
+
+impl GarchFit {
+    /// α + γ/2 + β: mức độ lan truyền của cú sốc (innovation đối xứng nên P(ε < 0) = 1/2)
+    pub fn persistence(&self) -> f64 {
+        self.alpha + 0.5 * self.gamma + self.beta
+    }
+
+    /// Phương sai dài hạn ω / (1 - persistence), None nếu không dừng
+    pub fn long_run_variance(&self) -> Option<f64> {
+        let persistence = self.persistence();
+        (persistence < 1.0).then(|| self.omega / (1.0 - persistence))
+    }
+
+    /// Số phiên để độ lệch của phương sai so với dài hạn giảm một nửa
+    pub fn half_life(&self) -> Option<f64> {
+        let persistence = self.persistence();
+        (persistence > 0.0 && persistence < 1.0).then(|| 0.5f64.ln() / persistence.ln())
+    }
+
+    pub fn parameter_count(&self) -> usize {
+        4 + usize::from(self.kind == GarchKind::GjrGarch) + usize::from(self.nu.is_some())
+    }
+
+    pub fn aic(&self) -> f64 {
+        2.0 * self.parameter_count() as f64 - 2.0 * self.log_likelihood
+    }
+
+    pub fn bic(&self) -> f64 {
+        (self.sample_size as f64).ln() * self.parameter_count() as f64 - 2.0 * self.log_likelihood
+    }
+}
+
+impl fmt::Display for GarchFit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.kind.name(), self.innovation.name())
+    }
+}
+
+/// Tham số đang tối ưu, theo thang %: [μ, ω, α, β, (γ), (ν)]
+struct Layout {
+    kind: GarchKind,
+    innovation: GarchInnovation,
+}
+
+impl Layout {
+    fn gamma(&self, x: &[f64]) -> f64 {
+        match self.kind {
+            GarchKind::Garch => 0.0,
+            GarchKind::GjrGarch => x[4],
+        }
+    }
+
+    fn nu(&self, x: &[f64]) -> Option<f64> {
+        match self.innovation {
+            GarchInnovation::Normal => None,
+            GarchInnovation::StudentT => x.last().copied(),
+        }
+    }
+
+    fn initial(&self, mean: f64, variance: f64) -> Vec<f64> {
+        let mut x = vec![mean, 0.05 * variance, 0.05, 0.90];
+        if self.kind == GarchKind::GjrGarch {
+            x[2] = 0.03;
+            x.push(0.05);
+        }
+        if self.innovation == GarchInnovation::StudentT {
+            x.push(8.0);
+        }
+        x
+    }
+
+    /// Bước ban đầu của simplex cho từng tham số
+    fn steps(&self, x: &[f64], std_dev: f64) -> Vec<f64> {
+        let mut steps = vec![0.1 * std_dev, 0.5 * x[1], 0.03, 0.03];
+        if self.kind == GarchKind::GjrGarch {
+            steps.push(0.03);
+        }
+        if self.innovation == GarchInnovation::StudentT {
+            steps.push(2.0);
+        }
+        steps
+    }
+
+    fn is_feasible(&self, x: &[f64]) -> bool {
+        let (omega, alpha, beta, gamma) = (x[1], x[2], x[3], self.gamma(x));
+        omega > 0.0
+            && alpha >= 0.0
+            && alpha + gamma >= 0.0
+            && beta >= 0.0
+            && alpha + 0.5 * gamma + beta < 1.0
+            && self.nu(x).is_none_or(|nu| nu > 2.0 && nu < 200.0)
+    }
+}
+
+/// Dãy phương sai có điều kiện σ²ₜ với σ²₀ = phương sai mẫu, trả về cả σ² cho phiên kế tiếp
+fn conditional_variances(
+    residuals: &[f64],
+    omega: f64,
+    alpha: f64,
+    gamma: f64,
+    beta: f64,
+    initial: f64,
+) -> (Vec<f64>, f64) {
+    let mut variances = Vec::with_capacity(residuals.len());
+    let mut variance = initial;
+    for &e in residuals {
+        variances.push(variance);
+        let leverage = if e < 0.0 { gamma } else { 0.0 };
+        variance = omega + (alpha + leverage) * e * e + beta * variance;
+    }
+    (variances, variance)
+}
+
+/// Log-likelihood trên returns đã nhân `RETURN_SCALE`, -∞ nếu tham số không hợp lệ
+fn log_likelihood(layout: &Layout, x: &[f64], returns: &[f64], sample_variance: f64) -> f64 {
+    if !layout.is_feasible(x) {
+        return f64::NEG_INFINITY;
+    }
+    let residuals: Vec<f64> = returns.iter().map(|r| r - x[0]).collect();
+    let (variances, _) = conditional_variances(
+        &residuals,
+        x[1],
+        x[2],
+        layout.gamma(x),
+        x[3],
+        sample_variance,
+    );
+
+    let total: f64 = match layout.nu(x) {
+        None => residuals
+            .iter()
+            .zip(&variances)
+            .map(|(e, v)| -0.5 * ((2.0 * std::f64::consts::PI).ln() + v.ln() + e * e / v))
+            .sum(),
+        Some(nu) => {
+            let constant = ln_gamma(0.5 * (nu + 1.0))
+                - ln_gamma(0.5 * nu)
+                - 0.5 * (std::f64::consts::PI * (nu - 2.0)).ln();
+            residuals
+                .iter()
+                .zip(&variances)
+                .map(|(e, v)| {
+                    constant - 0.5 * v.ln() - 0.5 * (nu + 1.0) * (e * e / (v * (nu - 2.0))).ln_1p()
+                })
+                .sum()
+        }
+    };
+    if total.is_finite() {
+        total
+    } else {
+        f64::NEG_INFINITY
+    }
+}
+
+/// Cực tiểu hóa `f` bằng Nelder-Mead, bắt đầu từ simplex quanh `x0` với bước `steps`
+fn nelder_mead(f: &impl Fn(&[f64]) -> f64, x0: &[f64], steps: &[f64]) -> (Vec<f64>, f64) {
+    let n = x0.len();
+    let mut simplex: Vec<(Vec<f64>, f64)> = (0..=n)
+        .map(|i| {
+            let mut x = x0.to_vec();
+            if i > 0 {
+                x[i - 1] += steps[i - 1];
+            }
+            let value = f(&x);
+            (x, value)
+        })
+        .collect();
+
+    for _ in 0..OPTIMIZER_MAX_ITERATIONS {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, worst) = (simplex[0].1, simplex[n].1);
+        if (worst - best).abs() <= 1e-10 * (best.abs() + 1e-10) {
+            break;
+        }
+
+        let centroid: Vec<f64> = (0..n)
+            .map(|j| simplex[..n].iter().map(|(x, _)| x[j]).sum::<f64>() / n as f64)
+            .collect();
+        let toward = |t: f64| -> Vec<f64> {
+            centroid
+                .iter()
+                .zip(&simplex[n].0)
+                .map(|(c, w)| c + t * (w - c))
+                .collect()
+        };
+
+        let reflected = toward(-1.0);
+        let reflected_value = f(&reflected);
+        if reflected_value < best {
+            let expanded = toward(-2.0);
+            let expanded_value = f(&expanded);
+            simplex[n] = if expanded_value < reflected_value {
+                (expanded, expanded_value)
+            } else {
+                (reflected, reflected_value)
+            };
+        } else if reflected_value < simplex[n - 1].1 {
+            simplex[n] = (reflected, reflected_value);
+        } else {
+            let contracted = if reflected_value < worst {
+                toward(-0.5)
+            } else {
+                toward(0.5)
+            };
+            let contracted_value = f(&contracted);
+            if contracted_value < worst.min(reflected_value) {
+                simplex[n] = (contracted, contracted_value);
+            } else {
+                // Co cả simplex về điểm tốt nhất
+                let best_point = simplex[0].0.clone();
+                for (x, value) in simplex.iter_mut().skip(1) {
+                    for (xi, bi) in x.iter_mut().zip(&best_point) {
+                        *xi = bi + 0.5 * (*xi - bi);
+                    }
+                    *value = f(x);
+                }
+            }
+        }
+    }
+
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+    simplex.swap_remove(0)
+}
+
+/// Ước lượng GARCH(1,1) / GJR-GARCH(1,1) bằng maximum likelihood trên log-returns ngày
+///
+/// Returns được đổi sang % khi tối ưu rồi quy đổi lại; σ²₀ là phương sai mẫu.
+/// Cần ít nhất `MIN_GARCH_OBSERVATIONS` returns, không có NaN; trả về
+/// `StatsError::NoConvergence` nếu tối ưu kết thúc ở likelihood không hữu hạn.
+pub fn fit_garch(
+    returns: &[f64],
+    kind: GarchKind,
+    innovation: GarchInnovation,
+) -> StatsResult<GarchFit> {
+    if let Some(index) = returns.iter().position(|r| r.is_nan()) {
+        return Err(StatsError::NanValue { index });
+    }
+    if returns.len() < MIN_GARCH_OBSERVATIONS {
+        return Err(StatsError::InsufficientData {
+            required: MIN_GARCH_OBSERVATIONS,
+            available: returns.len(),
+        });
+    }
+
+    let scaled: Vec<f64> = returns.iter().map(|r| r * RETURN_SCALE).collect();
+    let mean = calculate_mean(&scaled);
+    let std_dev = calculate_stdev(&scaled, mean);
+    let sample_variance = std_dev * std_dev;
+
+    let layout = Layout { kind, innovation };
+    let objective = |x: &[f64]| -log_likelihood(&layout, x, &scaled, sample_variance);
+
+    let mut x = layout.initial(mean, sample_variance);
+    let mut value = objective(&x);
+    for _ in 0..OPTIMIZER_RESTARTS {
+        let steps = layout.steps(&x, std_dev);
+        let (next, next_value) = nelder_mead(&objective, &x, &steps);
+        let improved = next_value < value - 1e-9;
+        (x, value) = (next, next_value);
+        if !improved {
+            break;
+        }
+    }
+    if !value.is_finite() {
+        return Err(StatsError::NoConvergence);
+    }
+
+    let gamma = layout.gamma(&x);
+    let residuals: Vec<f64> = scaled.iter().map(|r| r - x[0]).collect();
+    let (variances, next_variance) =
+        conditional_variances(&residuals, x[1], x[2], gamma, x[3], sample_variance);
+
+    // Quy đổi từ thang %: log-likelihood cộng n·ln(scale) do Jacobian của phép đổi biến
+    let scale2 = RETURN_SCALE * RETURN_SCALE;
+    Ok(GarchFit {
+        kind,
+        innovation,
+        mu: x[0] / RETURN_SCALE,
+        omega: x[1] / scale2,
+        alpha: x[2],
+        gamma,
+        beta: x[3],
+        nu: layout.nu(&x),
+        log_likelihood: -value + returns.len() as f64 * RETURN_SCALE.ln(),
+        sample_size: returns.len(),
+        last_variance: variances[variances.len() - 1] / scale2,
+        next_variance: next_variance / scale2,
+    })
+}
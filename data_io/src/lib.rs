@@ -1,7 +1,1009 @@
+#[cfg(feature = "parquet")]
+pub mod columnar;
+pub mod cache;
+pub mod corporate_actions;
 pub mod csv_loader;
+pub mod error;
+pub mod estimation;
+pub mod exchange;
+pub mod garch;
+pub mod market_data;
+pub mod merge;
+pub mod path_metrics;
+pub mod price_series;
+pub mod quality;
+pub mod risk;
+pub mod schema;
+pub mod source;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 pub mod statistics;
 pub mod stock_price;
 
+#[cfg(feature = "parquet")]
+pub use columnar::*;
+pub use cache::*;
+pub use corporate_actions::*;
 pub use csv_loader::*;
+pub use error::*;
+pub use estimation::*;
+pub use exchange::*;
+pub use garch::*;
+pub use market_data::*;
+pub use merge::*;
+pub use path_metrics::*;
+pub use price_series::*;
+pub use quality::*;
+pub use risk::*;
+pub use schema::*;
+pub use source::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
 pub use statistics::*;
-pub use stock_price::*;
\ No newline at end of file
+pub use stock_price::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(date: &str, close: f64) -> StockPrice {
+        StockPrice {
+            ticker: "AAA".to_string(),
+            date: date.to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1000,
+        }
+    }
+
+    #[test]
+    fn test_price_series_sorted_and_deduplicated() {
+        // CafeF xếp ngày mới nhất lên đầu, có 1 ngày bị trùng
+        let records = vec![
+            record("20251110", 7.80),
+            record("20251107", 7.95),
+            record("20251107", 7.95),
+            record("20251106", 8.12),
+        ];
+
+        let series = PriceSeries::from_records("AAA", &records).unwrap();
+        assert_eq!(series.len(), 3);
+        assert_eq!(series.closes(), &[8.12, 7.95, 7.80]);
+        assert!(series.dates().windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(series.last_close(), Some(7.80));
+
+        let returns = series.log_returns();
+        assert!(returns.iter().all(|&r| r < 0.0));
+    }
+
+    #[test]
+    fn test_price_series_rejects_invalid_rows() {
+        assert!(matches!(
+            PriceSeries::from_records("AAA", &[record("20251110", 0.0)]),
+            Err(DataIoError::NonPositivePrice { field: "open", .. })
+        ));
+        assert!(matches!(
+            PriceSeries::from_records("AAA", &[record("20251110", 7.8), record("2025-11-10", 7.8)]),
+            Err(DataIoError::MalformedRow { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_load_errors_are_typed() {
+        assert!(matches!(
+            MarketDataStore::load_csv("../data/does_not_exist.csv"),
+            Err(DataIoError::FileNotFound { .. })
+        ));
+        assert!(matches!(
+            load_price_series("../data/CafeF.HSX.Upto10.11.2025.csv", "ZZZ"),
+            Err(DataIoError::UnknownTicker { .. })
+        ));
+
+        let series = PriceSeries::from_records("AAA", &[record("20251110", 7.8)]).unwrap();
+        assert!(matches!(
+            series.ensure_history(2),
+            Err(DataIoError::InsufficientHistory { required: 2, available: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_market_data_store_index_and_range() {
+        let store = MarketDataStore::load_csv("../data/CafeF.HSX.Upto10.11.2025.csv").unwrap();
+        let tickers = store.tickers();
+        assert!(!tickers.is_empty());
+        assert!(tickers.windows(2).all(|w| w[0] < w[1]));
+
+        let series = store.get("AAA").unwrap();
+        assert_eq!(series.last_date(), chrono::NaiveDate::from_ymd_opt(2025, 11, 10));
+
+        let from = chrono::NaiveDate::from_ymd_opt(2025, 11, 5).unwrap();
+        let to = chrono::NaiveDate::from_ymd_opt(2025, 11, 7).unwrap();
+        let window = store.get_range("AAA", from, to).unwrap();
+        assert_eq!(window.closes(), &[8.07, 8.12, 7.95]);
+        assert!(store.get("UNKNOWN").is_none());
+    }
+
+    #[test]
+    fn test_schema_detection_and_custom_layout() {
+        let cafef = csv::StringRecord::from(vec![
+            "<Ticker>", "<DTYYYYMMDD>", "<Open>", "<High>", "<Low>", "<Close>", "<Volume>",
+        ]);
+        assert_eq!(CsvSchema::detect(&cafef).unwrap().name, "CafeF");
+
+        let yahoo = csv::StringRecord::from(vec![
+            "Date", "Open", "High", "Low", "Close", "Adj Close", "Volume",
+        ]);
+        let schema = CsvSchema::detect(&yahoo).unwrap();
+        assert_eq!(schema.name, "Yahoo");
+
+        let columns = schema.resolve("VNM.csv", &yahoo).unwrap();
+        let row = csv::StringRecord::from(vec![
+            "2024-01-02", "66.1", "66.5", "65.8", "66.0", "63.2", "1500000",
+        ]);
+        let (ticker, bar) = schema.parse_row(&columns, &row, "VNM", 2).unwrap().unwrap();
+        assert_eq!(ticker, "VNM");
+        assert_eq!(bar.date, chrono::NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+        assert_eq!(bar.close, 66.0);
+
+        let null_row = csv::StringRecord::from(vec!["2024-01-03", "null", "null", "null", "null", "null", "null"]);
+        assert!(schema.parse_row(&columns, &null_row, "VNM", 3).unwrap().is_none());
+
+        // Layout tự định nghĩa: chỉ có ngày và giá đóng cửa, dấu chấm phẩy
+        let custom = CsvSchema::custom("Broker", "Ngay", "%d/%m/%Y", "GiaDongCua")
+            .with_default_ticker("FPT")
+            .with_delimiter(b';');
+        let headers = csv::StringRecord::from(vec!["Ngay", "GiaDongCua"]);
+        let columns = custom.resolve("x.csv", &headers).unwrap();
+        let row = csv::StringRecord::from(vec!["05/11/2025", "98.5"]);
+        let (ticker, bar) = custom.parse_row(&columns, &row, "X", 2).unwrap().unwrap();
+        assert_eq!(ticker, "FPT");
+        assert_eq!(bar.high, 98.5);
+
+        let unknown = csv::StringRecord::from(vec!["a", "b"]);
+        assert!(CsvSchema::detect(&unknown).is_none());
+        assert!(matches!(custom.resolve("x.csv", &unknown), Err(DataIoError::BadHeader { .. })));
+    }
+
+    #[test]
+    fn test_corporate_action_back_adjustment() {
+        // Cổ tức cổ phiếu 20% (ratio 1.2) + 0.5 tiền mặt, GDKHQ ngày 07/11
+        let records = vec![
+            record("20251106", 12.0),
+            record("20251107", 9.6),
+            record("20251110", 9.7),
+        ];
+        let series = PriceSeries::from_records("AAA", &records).unwrap();
+        let actions = vec![
+            CorporateAction {
+                ticker: "AAA".to_string(),
+                ex_date: chrono::NaiveDate::from_ymd_opt(2025, 11, 7).unwrap(),
+                ratio: 1.2,
+                cash_dividend: 0.5,
+            },
+            CorporateAction {
+                ticker: "BBB".to_string(),
+                ex_date: chrono::NaiveDate::from_ymd_opt(2025, 11, 7).unwrap(),
+                ratio: 2.0,
+                cash_dividend: 0.0,
+            },
+        ];
+
+        let adjusted = adjust_for_corporate_actions(&series, &actions).unwrap();
+        let expected_first = 12.0 * (12.0 - 0.5) / (12.0 * 1.2);
+        assert!((adjusted.closes()[0] - expected_first).abs() < 1e-12);
+        assert_eq!(&adjusted.closes()[1..], &[9.6, 9.7]);
+        assert_eq!(adjusted.volumes()[0], 1200);
+
+        // Không còn cú rơi -22% giả
+        assert!(adjusted.log_returns()[0].abs() < 0.01);
+
+        let bad = vec![CorporateAction { ratio: 0.0, ..actions[0].clone() }];
+        assert!(matches!(
+            adjust_for_corporate_actions(&series, &bad),
+            Err(DataIoError::InvalidCorporateAction { .. })
+        ));
+    }
+
+    #[test]
+    fn test_data_quality_report() {
+        let mut records = vec![
+            record("20250106", 10.0),
+            record("20250107", 10.1),
+            record("20250107", 10.1),
+            record("20250108", 10.0),
+            // Thiếu 2 tuần giao dịch
+            record("20250122", 10.2),
+        ];
+        records[1].volume = 0;
+        records[3].high = 9.5;
+        for day in 23..=31 {
+            records.push(record(&format!("202501{}", day), 10.2));
+        }
+
+        let series = PriceSeries::from_records("AAA", &records).unwrap();
+        let report = check_data_quality(&series, &QualityConfig::default());
+
+        assert_eq!(report.duplicates(), 1);
+        assert_eq!(report.zero_volume_days(), 1);
+        assert_eq!(report.ohlc_inconsistencies(), 1);
+        assert_eq!(report.gaps(), 1);
+        assert!(report.issues.windows(2).all(|w| w[0].date <= w[1].date));
+
+        let gap = report
+            .issues
+            .iter()
+            .find(|issue| matches!(issue.kind, QualityIssueKind::Gap { .. }))
+            .unwrap();
+        assert_eq!(gap.date, chrono::NaiveDate::from_ymd_opt(2025, 1, 22).unwrap());
+        assert_eq!(
+            gap.kind,
+            QualityIssueKind::Gap {
+                previous: chrono::NaiveDate::from_ymd_opt(2025, 1, 8).unwrap(),
+                missing_weekdays: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn test_range_volatility_estimators() {
+        // Phiên không đổi giá (O = C) với biên độ đối xứng H = O·e^a, L = O·e^-a
+        let a: f64 = 0.01;
+        let bars: Vec<PriceBar> = (0..20)
+            .map(|i| PriceBar {
+                date: chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap() + chrono::Days::new(i),
+                open: 10.0,
+                high: 10.0 * a.exp(),
+                low: 10.0 * (-a).exp(),
+                close: 10.0,
+                volume: 1000,
+            })
+            .collect();
+        let series = PriceSeries::from_bars("AAA", bars).unwrap();
+
+        let close_enough = |x: f64, y: f64| (x - y).abs() < 1e-12;
+        assert!(close_enough(series.volatility(VolatilityEstimator::CloseToClose), 0.0));
+        assert!(close_enough(
+            series.volatility(VolatilityEstimator::Parkinson),
+            2.0 * a / (4.0 * std::f64::consts::LN_2).sqrt()
+        ));
+        assert!(close_enough(series.volatility(VolatilityEstimator::GarmanKlass), 2f64.sqrt() * a));
+        assert!(close_enough(series.volatility(VolatilityEstimator::RogersSatchell), 2f64.sqrt() * a));
+
+        // Không có gap qua đêm và O = C → chỉ còn phần Rogers-Satchell với trọng số (1 - k)
+        let n = 19.0;
+        let k = 0.34 / (1.34 + (n + 1.0) / (n - 1.0));
+        assert!(close_enough(
+            series.volatility(VolatilityEstimator::YangZhang),
+            ((1.0 - k) * 2.0 * a * a).sqrt()
+        ));
+        assert!(yang_zhang_volatility(&[1.0, 1.0], &[1.0, 1.0], &[1.0, 1.0], &[1.0, 1.0]).is_nan());
+
+        // Slice OHLC lệch độ dài được cắt về slice ngắn nhất thay vì panic
+        let (h, l) = (a.exp(), (-a).exp());
+        let short = [1.0, 1.0, 1.0];
+        assert!(close_enough(garman_klass_volatility(&[1.0; 5], &[h; 4], &[l; 3], &short), 2f64.sqrt() * a));
+        assert!(close_enough(rogers_satchell_volatility(&short, &[h; 5], &[l; 4], &[1.0; 6]), 2f64.sqrt() * a));
+        assert!(yang_zhang_volatility(&[1.0; 6], &[h; 6], &[l; 6], &short).is_finite());
+
+        for estimator in VolatilityEstimator::ALL {
+            assert_eq!(VolatilityEstimator::from_name(estimator.name()), Some(estimator));
+        }
+    }
+
+    #[test]
+    fn test_estimation_window_and_rolling() {
+        let store = MarketDataStore::load_csv("../data/CafeF.HSX.Upto10.11.2025.csv").unwrap();
+        let series = store.get("AAA").unwrap();
+
+        let last_60 = EstimationWindow::LastDays(60).apply(series);
+        assert_eq!(last_60.len(), 61);
+        assert_eq!(last_60.last_date(), series.last_date());
+        assert_eq!(EstimationWindow::LastDays(100_000).apply(series).len(), series.len());
+        let from = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let to = chrono::NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let year = EstimationWindow::DateRange { from, to }.apply(series);
+        assert!(year.first_date().unwrap() >= from && year.last_date().unwrap() <= to);
+
+        // λ = 1 → trọng số bằng nhau: EWMA mean = mean, EWMA σ = RMS
+        let returns = [0.01, -0.02, 0.03, 0.0];
+        assert!((ewma_mean(&returns, 1.0) - calculate_mean(&returns)).abs() < 1e-15);
+        let rms = (returns.iter().map(|r| r * r).sum::<f64>() / 4.0).sqrt();
+        assert!((ewma_volatility(&returns, 1.0) - rms).abs() < 1e-15);
+        // λ nhỏ → gần như chỉ còn quan sát mới nhất
+        assert!(ewma_volatility(&[0.5, 0.5, 0.01], 1e-6) < 0.011);
+
+        let rolling = rolling_estimates(series, 60, VolatilityEstimator::CloseToClose);
+        assert_eq!(rolling.len(), series.len() - 60);
+        let last = rolling.last().unwrap();
+        let window_returns = last_60.log_returns();
+        assert_eq!(last.date, series.last_date().unwrap());
+        assert!((last.mu - calculate_mean(&window_returns)).abs() < 1e-12);
+        assert!((last.sigma - last_60.volatility(VolatilityEstimator::CloseToClose)).abs() < 1e-12);
+        assert!(rolling_estimates(series, series.len(), VolatilityEstimator::Parkinson).is_empty());
+    }
+
+    #[test]
+    fn test_parameter_inference() {
+        assert!(normal_quantile(0.5).abs() < 1e-9);
+        assert!((normal_quantile(0.975) - 1.959963985).abs() < 1e-8);
+        assert!((normal_quantile(0.01) + 2.326347874).abs() < 1e-8);
+
+        // Returns trải đều trong khoảng 0.001 ± 0.01 (thứ tự hoán vị theo i·37 mod 97)
+        let returns: Vec<f64> = (0..400)
+            .map(|i| 0.001 + 0.02 * (((i * 37 + 11) % 97) as f64 / 96.0 - 0.5))
+            .collect();
+        let inference = infer_drift_volatility(&returns, 0.95, 500, 7).unwrap();
+        let mu = calculate_mean(&returns);
+        let sigma = calculate_stdev(&returns, mu);
+        assert_eq!(inference.sample_size, 400);
+        assert!((inference.mu.value - mu).abs() < 1e-15);
+        assert!((inference.mu.std_error - sigma / 20.0).abs() < 1e-12);
+        assert!((inference.t_stat - mu / (sigma / 20.0)).abs() < 1e-9);
+        assert!((inference.sigma.std_error - sigma / (2.0 * 399.0f64).sqrt()).abs() < 1e-12);
+
+        // Khoảng bootstrap chứa ước lượng điểm, độ rộng cỡ ±2 SE và tái tạo được theo seed
+        assert!(inference.mu.interval.contains(inference.mu.value));
+        assert!(inference.sigma.interval.contains(inference.sigma.value));
+        let width = inference.mu.interval.high - inference.mu.interval.low;
+        assert!(width > 2.0 * inference.mu.std_error && width < 6.0 * inference.mu.std_error);
+        assert_eq!(infer_drift_volatility(&returns, 0.95, 500, 7), Some(inference));
+
+        assert_eq!(infer_drift_volatility(&[0.01], 0.95, 100, 7), None);
+        assert_eq!(
+            bootstrap_confidence_intervals(&[], 0.95, 100, 7),
+            Err(StatsError::InsufficientData { required: 2, available: 0 })
+        );
+        assert_eq!(
+            bootstrap_confidence_intervals(&returns, 0.95, 0, 7),
+            Err(StatsError::InsufficientData { required: 1, available: 0 })
+        );
+        assert_eq!(
+            bootstrap_confidence_intervals(&returns, 1.5, 100, 7),
+            Err(StatsError::InvalidProbability { p: 1.5 })
+        );
+    }
+
+    #[test]
+    fn test_return_diagnostics() {
+        use rand::{Rng, SeedableRng};
+
+        // Giá trị tới hạn 5% của χ²: 3.841 (1 bậc), 5.991 (2 bậc), 18.307 (10 bậc)
+        assert!((chi_squared_sf(3.841459, 1.0) - 0.05).abs() < 1e-6);
+        assert!((chi_squared_sf(5.991465, 2.0) - 0.05).abs() < 1e-6);
+        assert!((chi_squared_sf(18.307038, 10.0) - 0.05).abs() < 1e-6);
+
+        // Nhiễu chuẩn i.i.d. (Box-Muller) → không bác bỏ chuẩn / độc lập, returns dừng
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let noise: Vec<f64> = (0..2000)
+            .map(|_| {
+                let (u, v): (f64, f64) = (rng.gen_range(1e-12..1.0), rng.gen());
+                0.01 * (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+            })
+            .collect();
+        let mut closes = vec![100.0];
+        for r in &noise {
+            closes.push(closes[closes.len() - 1] * r.exp());
+        }
+        let diagnostics = diagnose_returns(&closes, 10).unwrap();
+        assert_eq!(diagnostics.n, 2000);
+        assert!(diagnostics.skewness.abs() < 0.2 && diagnostics.excess_kurtosis.abs() < 0.4);
+        assert!(!diagnostics.jarque_bera.rejects(0.01));
+        assert!(!diagnostics.ljung_box.rejects(0.01));
+        assert!(!diagnostics.ljung_box_squared.rejects(0.01));
+        assert!(diagnostics.adf_returns.unwrap().rejects_unit_root(0.01));
+        assert!(!diagnostics.adf_log_prices.unwrap().rejects_unit_root(0.05));
+
+        // Phân phối đều: đuôi mỏng (excess kurtosis -1.2) → Jarque-Bera bác bỏ
+        let uniform: Vec<f64> = (0..2000).map(|i| (i % 100) as f64 / 99.0 - 0.5).collect();
+        assert!((calculate_excess_kurtosis(&uniform) + 1.2).abs() < 0.01);
+        assert!(calculate_skewness(&uniform).abs() < 1e-9);
+        assert!(jarque_bera(&uniform).rejects(0.01));
+        // Chuỗi răng cưa có tự tương quan rất mạnh → Ljung-Box bác bỏ
+        assert!(ljung_box(&uniform, 5).rejects(0.01));
+        // Độ trễ ≥ n: NaN thay vì panic
+        assert!(autocorrelation(&uniform[..3], 3).is_nan());
+        assert!(!ljung_box(&uniform[..5], 5).rejects(0.05));
+
+        // QQ-plot: n điểm, trục x tăng dần và đối xứng quanh 0
+        let points = normal_qq_points(&noise);
+        assert_eq!(points.len(), noise.len());
+        assert!(points.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 <= w[1].1));
+        assert!((points[0].0 + points[points.len() - 1].0).abs() < 1e-9);
+
+        assert!(diagnose_returns(&[100.0, 101.0], 10).is_none());
+    }
+
+    #[test]
+    fn test_quantile_definitions() {
+        let values: Vec<f64> = (1..=10).map(|x| x as f64).collect();
+        // Giá trị tham chiếu từ quantile(1:10, 0.1, type = k) trong R
+        let expected = [1.0, 1.5, 1.0, 1.0, 1.5, 1.1, 1.9, 1.0 + 1.1 / 3.0, 1.4];
+        for (method, expected) in QuantileMethod::ALL.into_iter().zip(expected) {
+            let q = quantile(&values, 0.1, method, NanPolicy::Reject).unwrap();
+            assert!((q - expected).abs() < 1e-12, "type {}: {} != {}", method.number(), q, expected);
+            // p = 0 / 1 luôn là min / max
+            assert_eq!(quantile(&values, 0.0, method, NanPolicy::Reject), Ok(1.0));
+            assert_eq!(quantile(&values, 1.0, method, NanPolicy::Reject), Ok(10.0));
+            assert_eq!(QuantileMethod::from_number(method.number()), Some(method));
+        }
+        assert_eq!(QuantileMethod::from_number(0), None);
+
+        // Percentile cũ giờ nội suy (type 7) thay vì cắt index
+        assert!((calculate_percentile(&values, 0.25) - 3.25).abs() < 1e-12);
+        assert!((calculate_var(&[90.0, 110.0], 100.0, 0.5) - 0.0).abs() < 1e-12);
+        let q = quantiles(&values, &[0.5, 0.9], QuantileMethod::Type7, NanPolicy::Reject).unwrap();
+        assert!((q[0] - 5.5).abs() < 1e-12 && (q[1] - 9.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_statistics_degenerate_inputs() {
+        let with_nan = [1.0, f64::NAN, 3.0];
+
+        // Đầu vào rỗng / quá ngắn → lỗi thay vì NaN
+        assert_eq!(mean(&[], NanPolicy::Reject), Err(StatsError::Empty));
+        assert_eq!(
+            std_dev(&[1.0], NanPolicy::Reject),
+            Err(StatsError::InsufficientData { required: 2, available: 1 })
+        );
+        assert_eq!(
+            quantile(&[], 0.5, QuantileMethod::Type7, NanPolicy::Omit),
+            Err(StatsError::Empty)
+        );
+        assert_eq!(
+            quantile(&[1.0], 1.5, QuantileMethod::Type7, NanPolicy::Reject),
+            Err(StatsError::InvalidProbability { p: 1.5 })
+        );
+        assert!(matches!(
+            quantile(&[1.0], f64::NAN, QuantileMethod::Type7, NanPolicy::Reject),
+            Err(StatsError::InvalidProbability { .. })
+        ));
+        assert_eq!(quantile(&[4.0], 0.3, QuantileMethod::Type8, NanPolicy::Reject), Ok(4.0));
+
+        // 3 chính sách NaN
+        assert_eq!(mean(&with_nan, NanPolicy::Reject), Err(StatsError::NanValue { index: 1 }));
+        assert_eq!(mean(&with_nan, NanPolicy::Omit), Ok(2.0));
+        assert!(mean(&with_nan, NanPolicy::Propagate).unwrap().is_nan());
+        assert_eq!(std_dev(&with_nan, NanPolicy::Omit), Ok(2f64.sqrt()));
+        assert_eq!(mean(&[f64::NAN], NanPolicy::Omit), Err(StatsError::Empty));
+        assert_eq!(
+            quantile(&with_nan, 0.5, QuantileMethod::Type7, NanPolicy::Omit),
+            Ok(2.0)
+        );
+        assert!(summary_stats(&with_nan, QuantileMethod::Type7, NanPolicy::Propagate)
+            .unwrap()
+            .median
+            .is_nan());
+
+        // Các hàm cũ không còn panic với NaN
+        assert_eq!(calculate_percentile(&with_nan, 1.0), 3.0);
+        assert_eq!(calculate_percentile(&[], 0.5), 0.0);
+        assert_eq!((find_min(&[f64::NAN, 2.0]), find_max(&[f64::NAN, 2.0])), (2.0, 2.0));
+        let stats = calculate_summary_stats(&with_nan);
+        assert_eq!((stats.min, stats.median, stats.max), (1.0, 2.0, 3.0));
+
+        let stats = summary_stats(&[1.0, 2.0, 3.0, 4.0], QuantileMethod::Type7, NanPolicy::Reject)
+            .unwrap();
+        assert_eq!((stats.min, stats.median, stats.max, stats.mean), (1.0, 2.5, 4.0, 2.5));
+    }
+
+    #[test]
+    fn test_streaming_moments_and_tdigest() {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        // Phân phối lệch (log-normal) giống giá cuối của GBM
+        let values: Vec<f64> = (0..200_000)
+            .map(|_| {
+                let (u, v): (f64, f64) = (rng.gen_range(1e-12..1.0), rng.gen());
+                (0.3 * (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()).exp() * 100.0
+            })
+            .collect();
+
+        // Mỗi chunk 1 summary rồi gộp, như rayon fold/reduce
+        let summary = values
+            .chunks(7_919)
+            .map(|chunk| {
+                let mut summary = StreamingSummary::new();
+                chunk.iter().for_each(|&x| summary.push(x));
+                summary
+            })
+            .fold(StreamingSummary::new(), StreamingSummary::merge);
+        assert_eq!(summary.count(), values.len() as u64);
+        assert!(summary.digest().centroid_count() <= 2 * DEFAULT_TDIGEST_COMPRESSION as usize);
+
+        let exact = summary_stats(&values, QuantileMethod::Type7, NanPolicy::Reject).unwrap();
+        let streamed = summary.summary().unwrap();
+        assert!((streamed.mean - exact.mean).abs() < 1e-9 * exact.mean);
+        assert!((streamed.std_dev - exact.std_dev).abs() < 1e-9 * exact.std_dev);
+        assert_eq!((streamed.min, streamed.max), (exact.min, exact.max));
+
+        // Sai số quantile tính theo rank: cùng lắm vài phần nghìn
+        let mut sorted = values.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        for p in [0.001, 0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99, 0.999] {
+            let estimate = summary.quantile(p).unwrap();
+            let rank = sorted.partition_point(|&x| x < estimate) as f64 / sorted.len() as f64;
+            assert!((rank - p).abs() < 0.003, "p = {}: rank {}", p, rank);
+        }
+
+        // Edge case: rỗng, 1 giá trị, NaN, p ngoài [0, 1]
+        let mut empty = StreamingSummary::new();
+        assert_eq!(empty.quantile(0.5), Err(StatsError::Empty));
+        assert!(empty.summary().is_err());
+        empty.push(f64::NAN);
+        empty.push(5.0);
+        assert_eq!((empty.nan_count(), empty.count()), (1, 1));
+        assert_eq!(empty.quantile(0.3), Ok(5.0));
+        assert_eq!(empty.moments().variance(), None);
+        assert!(matches!(empty.quantile(2.0), Err(StatsError::InvalidProbability { .. })));
+    }
+
+    #[test]
+    fn test_var_and_expected_shortfall() {
+        let prices: Vec<f64> = (1..=100).map(|x| x as f64).collect();
+        let risk = simulated_risk(&prices, 100.0, &[0.9, 0.99]).unwrap();
+        // VaR90 = 100 - P10 (nội suy 10.9), ES90 = 100 - mean của 10 giá thấp nhất
+        assert!((risk[0].var - 89.1).abs() < 1e-9);
+        assert!((risk[0].es - 94.5).abs() < 1e-9);
+        assert!((expected_shortfall(&prices, 100.0, 0.99) - 99.0).abs() < 1e-9);
+        assert!((risk[0].var_percent(100.0) - 89.1).abs() < 1e-9);
+        assert!((risk[1].es_position(1000.0) - 99_000.0).abs() < 1e-6);
+        assert!(simulated_risk(&prices, 100.0, &[1.0]).is_err());
+        assert_eq!(simulated_risk(&[], 100.0, &[0.95]), Err(StatsError::Empty));
+
+        assert!((normal_cdf(1.959963985) - 0.975).abs() < 1e-7);
+        assert!((normal_cdf(-1.0) - 0.158655254).abs() < 1e-7);
+
+        // Mẫu chuẩn lớn: mô phỏng ≈ Normal ≈ Cornish-Fisher, và ES ≥ VaR
+        let values: Vec<f64> = (1..=100_000)
+            .map(|i| 100.0 + 10.0 * normal_quantile(i as f64 / 100_001.0))
+            .collect();
+        let moments = DistributionMoments::from_values(&values).unwrap();
+        let simulated = simulated_risk(&values, 100.0, &DEFAULT_RISK_LEVELS).unwrap();
+        let normal =
+            parametric_risk(&moments, 100.0, &DEFAULT_RISK_LEVELS, ParametricMethod::Normal).unwrap();
+        let cornish_fisher =
+            parametric_risk(&moments, 100.0, &DEFAULT_RISK_LEVELS, ParametricMethod::CornishFisher)
+                .unwrap();
+        for ((s, n), c) in simulated.iter().zip(&normal).zip(&cornish_fisher) {
+            assert!(s.es >= s.var && n.es >= n.var);
+            assert!((s.var - n.var).abs() < 0.05 && (s.es - n.es).abs() < 0.05);
+            assert!((c.var - n.var).abs() < 0.05 && (c.es - n.es).abs() < 0.05);
+        }
+        // ES chuẩn 97.5%: σ·φ(z)/α = 10 · 2.3378 ≈ 23.38
+        let es975 = parametric_risk(&moments, 100.0, &[0.975], ParametricMethod::Normal).unwrap();
+        assert!((es975[0].es - 23.378).abs() < 0.02);
+
+        // Đuôi trái dày (skew âm) → Cornish-Fisher cho VaR99 lớn hơn Normal
+        let skewed = DistributionMoments { skewness: -1.0, excess_kurtosis: 3.0, ..moments };
+        let cf = parametric_risk(&skewed, 100.0, &[0.99], ParametricMethod::CornishFisher).unwrap();
+        let n = parametric_risk(&skewed, 100.0, &[0.99], ParametricMethod::Normal).unwrap();
+        assert!(cf[0].var > n[0].var);
+
+        // Moment 1 lượt khớp với bản tính trên toàn mẫu, kể cả sau khi gộp chunk
+        let lognormal: Vec<f64> = values.iter().map(|v| (v / 50.0).exp()).collect();
+        let mut running = RunningMoments::new();
+        for chunk in lognormal.chunks(3_331) {
+            let mut part = RunningMoments::new();
+            chunk.iter().for_each(|&x| part.push(x));
+            running.merge(&part);
+        }
+        let streamed = DistributionMoments::from_running(&running).unwrap();
+        let exact = DistributionMoments::from_values(&lognormal).unwrap();
+        assert!((streamed.skewness - exact.skewness).abs() < 1e-9);
+        assert!((streamed.excess_kurtosis - exact.excess_kurtosis).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_jump_diffusion_calibration() {
+        use rand::{Rng, SeedableRng};
+
+        // 2000 phiên N(0.0005, 0.015²), 20 phiên có thêm jump ≈ -10% ± 1%
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let mut normal = move || normal_quantile(rng.gen_range(1e-12..1.0));
+        let returns: Vec<f64> = (0..2000)
+            .map(|i| {
+                let jump = if i % 100 == 50 { -0.10 + 0.01 * normal() } else { 0.0 };
+                0.0005 + 0.015 * normal() + jump
+            })
+            .collect();
+
+        let estimate = calibrate_jump_diffusion(&returns, DEFAULT_JUMP_THRESHOLD).unwrap();
+        assert_eq!(estimate.sample_size, 2000);
+        assert!((estimate.jump_count as i64 - 20).abs() <= 5, "{:?}", estimate);
+        assert!((estimate.lambda - 0.01).abs() < 0.003);
+        assert!((estimate.jump_mean + 0.10).abs() < 0.01, "{:?}", estimate);
+        assert!((estimate.sigma - 0.015).abs() < 0.001);
+
+        // Không có jump thật: chỉ vài phiên vượt 3σ do ngẫu nhiên
+        let calm: Vec<f64> = returns.iter().enumerate().filter(|(i, _)| i % 100 != 50).map(|(_, &r)| r).collect();
+        let estimate = calibrate_jump_diffusion(&calm, DEFAULT_JUMP_THRESHOLD).unwrap();
+        assert!(estimate.lambda < 0.005);
+
+        assert!(matches!(
+            calibrate_jump_diffusion(&[0.01, f64::NAN, 0.0], 3.0),
+            Err(StatsError::NanValue { index: 1 })
+        ));
+        assert!(calibrate_jump_diffusion(&[0.01, 0.02], 3.0).is_err());
+    }
+
+    #[test]
+    fn test_fat_tail_fit() {
+        use rand::{Rng, SeedableRng};
+
+        // Student-t(10) chuẩn hóa: excess kurtosis 6 / (ν - 4) = 1
+        let mut rng = rand::rngs::StdRng::seed_from_u64(17);
+        let mut normal = move || normal_quantile(rng.gen_range(1e-12..1.0));
+        let mut t10: Vec<f64> = (0..40_000)
+            .map(|_| {
+                let chi2: f64 = (0..10).map(|_| normal().powi(2)).sum();
+                normal() / (chi2 / 10.0).sqrt() * 0.8f64.sqrt()
+            })
+            .collect();
+        let fit = fit_fat_tails(&t10).unwrap();
+        assert!((fit.student_t_nu - 10.0).abs() < 2.0, "{:?}", fit);
+        assert!(fit.skewed_t_lambda.abs() < 0.05 && fit.nig_beta.abs() < 0.1 * fit.nig_alpha);
+
+        // Thêm cú sốc giảm mạnh: lệch trái, cả 2 phân phối lệch đều chọn tham số âm
+        for r in t10.iter_mut().step_by(50) {
+            *r -= 3.0;
+        }
+        let fit = fit_fat_tails(&t10).unwrap();
+        assert!(fit.skewness < -0.3 && fit.skewed_t_lambda < -0.1 && fit.nig_beta < 0.0, "{:?}", fit);
+        // NIG khớp đúng 2 moment: S = 3ρ/√ζ, K = 3(1 + 4ρ²)/ζ
+        let rho = fit.nig_beta / fit.nig_alpha;
+        let zeta = (fit.nig_alpha.powi(2) - fit.nig_beta.powi(2)).sqrt();
+        assert!((3.0 * rho / zeta.sqrt() - fit.skewness).abs() < 1e-6);
+        assert!((3.0 * (1.0 + 4.0 * rho * rho) / zeta - fit.excess_kurtosis).abs() < 1e-6);
+
+        // Phân phối đều có đuôi mỏng → Student-t gần như chuẩn
+        let uniform: Vec<f64> = (0..1000).map(|i| i as f64 / 1000.0).collect();
+        assert_eq!(fit_fat_tails(&uniform).unwrap().student_t_nu, 100.0);
+        assert_eq!(fit_fat_tails(&[0.01; 500]), Err(StatsError::ZeroVariance));
+        assert!(fit_fat_tails(&[0.01; 10]).is_err());
+        assert!(fit_fat_tails(&[0.0; 50]).is_err());
+    }
+
+    #[test]
+    fn test_garch_fit() {
+        use rand::{Rng, SeedableRng};
+
+        // 3000 phiên GJR-GARCH: ω = 2e-6, α = 0.04, γ = 0.08, β = 0.88, innovation t(6)
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut normal = move || normal_quantile(rng.gen_range(1e-12..1.0));
+        let nu = 6.0;
+        let (omega, alpha, gamma, beta) = (2e-6, 0.04, 0.08, 0.88);
+        let mut variance: f64 = omega / (1.0 - alpha - 0.5 * gamma - beta);
+        let mut returns = Vec::new();
+        for _ in 0..3000 {
+            let chi2: f64 = (0..6).map(|_| normal().powi(2)).sum();
+            let z = normal() / (chi2 / nu).sqrt() * ((nu - 2.0) / nu).sqrt();
+            let e = variance.sqrt() * z;
+            returns.push(0.0003 + e);
+            let leverage = if e < 0.0 { gamma } else { 0.0 };
+            variance = omega + (alpha + leverage) * e * e + beta * variance;
+        }
+
+        let fit = fit_garch(&returns, GarchKind::GjrGarch, GarchInnovation::StudentT).unwrap();
+        assert_eq!(fit.sample_size, 3000);
+        assert!((fit.persistence() - 0.96).abs() < 0.03, "{:?}", fit);
+        assert!(fit.gamma > fit.alpha, "{:?}", fit);
+        assert!((fit.beta - 0.88).abs() < 0.06, "{:?}", fit);
+        assert!((fit.nu.unwrap() - 6.0).abs() < 2.5, "{:?}", fit);
+        // σ dài hạn thật = √(2e-6 / 0.04) ≈ 0.71%
+        assert!((fit.long_run_variance().unwrap().sqrt() - 0.00707).abs() < 0.0015);
+        // Dự báo phiên kế tiếp đúng bằng 1 bước đệ quy từ phiên cuối
+        let e = returns[2999] - fit.mu;
+        let leverage = if e < 0.0 { fit.gamma } else { 0.0 };
+        let expected = fit.omega + (fit.alpha + leverage) * e * e + fit.beta * fit.last_variance;
+        assert!((fit.next_variance - expected).abs() < 1e-12);
+
+        // Innovation chuẩn không bắt được đuôi dày và đối xứng nên likelihood thấp hơn
+        let plain = fit_garch(&returns, GarchKind::Garch, GarchInnovation::Normal).unwrap();
+        assert_eq!((plain.gamma, plain.nu), (0.0, None));
+        assert!(plain.log_likelihood < fit.log_likelihood);
+        // Likelihood theo đơn vị log-return: cao hơn mô hình i.i.d. chuẩn với σ cố định
+        let mean = calculate_mean(&returns);
+        let variance = calculate_stdev(&returns, mean).powi(2);
+        let iid: f64 = returns
+            .iter()
+            .map(|r| -0.5 * ((2.0 * std::f64::consts::PI * variance).ln() + (r - mean).powi(2) / variance))
+            .sum();
+        assert!(plain.log_likelihood > iid && plain.log_likelihood < iid + 1000.0, "{} vs {}", plain.log_likelihood, iid);
+        assert!(plain.aic() > fit.aic());
+
+        assert!(matches!(
+            fit_garch(&[0.01, f64::NAN], GarchKind::Garch, GarchInnovation::Normal),
+            Err(StatsError::NanValue { index: 1 })
+        ));
+        assert!(fit_garch(&returns[..50], GarchKind::Garch, GarchInnovation::Normal).is_err());
+        assert_eq!(GarchKind::from_name("gjr-garch(1,1)"), Some(GarchKind::GjrGarch));
+    }
+
+    #[test]
+    fn test_path_metrics() {
+        let barriers = Barriers::from_percent(100.0, 10.0, 10.0);
+        assert!((barriers.upper.unwrap() - 110.0).abs() < 1e-9);
+        assert!((barriers.lower.unwrap() - 90.0).abs() < 1e-9);
+
+        // Đỉnh 110 → đáy 88 (-20%) ở bước 3, vượt lại đỉnh ở bước 5
+        let path = vec![100.0, 110.0, 99.0, 88.0, 105.0, 112.0, 100.0];
+        let barriers = Barriers { upper: Some(110.0), lower: Some(90.0) };
+        let m = path_metrics(&path, &barriers);
+        assert!((m.max_drawdown - 0.2).abs() < 1e-12);
+        assert!((m.max_run_up - 24.0 / 88.0).abs() < 1e-12);
+        assert_eq!(m.time_under_water, 3);
+        assert_eq!(m.recovery_steps, Some(2));
+        assert_eq!((m.upper_hit, m.lower_hit), (Some(1), Some(3)));
+
+        let paths = vec![path, vec![100.0, 101.0, 102.0], vec![100.0, 95.0, 90.0, 92.0]];
+        let summary = summarize_path_metrics(&paths, &barriers).unwrap();
+        assert_eq!(summary.path_count, 3);
+        assert!((summary.max_drawdown.max - 0.2).abs() < 1e-12);
+        assert_eq!(summary.max_drawdown.min, 0.0);
+        assert!((summary.recovered_fraction - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(summary.upper_hit_probability, Some(1.0 / 3.0));
+        assert_eq!(summary.lower_hit_probability, Some(2.0 / 3.0));
+        assert_eq!(summary.any_hit_probability, Some(2.0 / 3.0));
+
+        let no_barriers = summarize_path_metrics(&paths, &Barriers::default()).unwrap();
+        assert_eq!(no_barriers.any_hit_probability, None);
+        assert!(matches!(summarize_path_metrics(&[vec![]], &barriers), Err(StatsError::Empty)));
+
+        // Fan chart: quantile theo từng bước qua 5 path
+        let paths: Vec<Vec<f64>> = (0..5).map(|i| vec![100.0, 100.0 + i as f64, 100.0 - i as f64]).collect();
+        let fan = path_quantile_bands(&paths, &[0.95, 0.5, 0.05]).unwrap();
+        assert_eq!(fan.probabilities, vec![0.05, 0.5, 0.95]);
+        assert_eq!(fan.steps(), 3);
+        assert_eq!(fan.band(0.5).unwrap(), &[100.0, 102.0, 98.0]);
+        assert!((fan.band(0.95).unwrap()[1] - 103.8).abs() < 1e-9);
+        assert!(fan.band(0.25).is_none());
+        // Path ngắn hơn hoặc dài hơn path đầu đều là lệch độ dài
+        assert_eq!(
+            path_quantile_bands(&[vec![1.0, 2.0], vec![1.0]], &DEFAULT_FAN_QUANTILES),
+            Err(StatsError::LengthMismatch { index: 1, expected: 2, found: 1 })
+        );
+        assert_eq!(
+            path_quantile_bands(&[vec![1.0, 2.0], vec![1.0, 2.0], vec![1.0, 2.0, 3.0]], &DEFAULT_FAN_QUANTILES),
+            Err(StatsError::LengthMismatch { index: 2, expected: 2, found: 3 })
+        );
+    }
+
+    #[test]
+    fn test_multi_file_merge_and_exchange_tags() {
+        let dir = std::env::temp_dir().join("data_io_multi_file_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let header = "<Ticker>,<DTYYYYMMDD>,<Open>,<High>,<Low>,<Close>,<Volume>\n";
+        std::fs::write(
+            dir.join("CafeF.HSX.Upto07.11.2025.csv"),
+            format!("{}AAA,20251107,10,10,10,10,100\nAAA,20251106,9,9,9,9,100\n", header),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("CafeF.HNX.Upto07.11.2025.csv"),
+            format!("{}BBB,20251107,20,20,20,20,100\n", header),
+        )
+        .unwrap();
+        // File cập nhật: trùng 1 phiên cùng giá, 1 phiên khác giá và 1 phiên mới
+        std::fs::write(
+            dir.join("CafeF.HSX.Upto10.11.2025.csv"),
+            format!(
+                "{}AAA,20251110,11,11,11,11,100\nAAA,20251107,10.5,10.5,10.5,10.5,100\nAAA,20251106,9,9,9,9,100\n",
+                header
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(Exchange::from_path("data/CafeF.UPCOM.Upto10.11.2025.csv"), Some(Exchange::Upcom));
+        assert_eq!(Exchange::from_path("data/VNM.csv"), None);
+
+        let dir_path = dir.to_str().unwrap();
+        let (store, report) = MarketDataStore::load_dir(dir_path, MergePolicy::PreferIncoming).unwrap();
+        assert_eq!(report.files.len(), 3);
+        assert_eq!(report.identical_bars, 1);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].date, chrono::NaiveDate::from_ymd_opt(2025, 11, 7).unwrap());
+        assert_eq!(store.exchange("AAA"), Some(Exchange::Hsx));
+        assert_eq!(store.tickers_on(Exchange::Hnx), vec!["BBB".to_string()]);
+        assert_eq!(store.get("AAA").unwrap().closes(), &[9.0, 10.5, 11.0]);
+
+        let (kept, _) = MarketDataStore::load_dir(dir_path, MergePolicy::KeepExisting).unwrap();
+        assert_eq!(kept.get("AAA").unwrap().closes(), &[9.0, 10.0, 11.0]);
+        assert!(matches!(
+            MarketDataStore::load_dir(dir_path, MergePolicy::Reject),
+            Err(DataIoError::ConflictingBar { .. })
+        ));
+
+        let pattern = format!("{}/CafeF.HNX.*.csv", dir_path);
+        let (hnx, _) = MarketDataStore::load_any(&pattern, MergePolicy::Reject).unwrap();
+        assert_eq!(hnx.tickers(), vec!["BBB".to_string()]);
+        assert!(matches!(
+            MarketDataStore::load_glob(&format!("{}/*.parquet", dir_path), MergePolicy::Reject),
+            Err(DataIoError::InvalidSource { .. })
+        ));
+
+        // Upto05.12 đứng trước Upto10.11 theo tên nhưng mới hơn → phải được gộp sau cùng
+        std::fs::write(
+            dir.join("CafeF.HSX.Upto05.12.2025.csv"),
+            format!("{}AAA,20251205,13,13,13,13,100\nAAA,20251110,12,12,12,12,100\n", header),
+        )
+        .unwrap();
+        assert_eq!(
+            file_data_date(&dir.join("CafeF.HSX.Upto05.12.2025.csv")),
+            chrono::NaiveDate::from_ymd_opt(2025, 12, 5)
+        );
+        let files = glob_files(&format!("{}/CafeF.HSX.*.csv", dir_path)).unwrap();
+        let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(
+            names,
+            ["CafeF.HSX.Upto07.11.2025.csv", "CafeF.HSX.Upto10.11.2025.csv", "CafeF.HSX.Upto05.12.2025.csv"]
+        );
+        let (latest, _) = MarketDataStore::load_dir(dir_path, MergePolicy::PreferIncoming).unwrap();
+        assert_eq!(latest.get("AAA").unwrap().closes(), &[9.0, 10.5, 12.0, 13.0]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_append_delta_and_cache() {
+        let dir = std::env::temp_dir().join("data_io_append_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let header = "<Ticker>,<DTYYYYMMDD>,<Open>,<High>,<Low>,<Close>,<Volume>\n";
+        let write = |name: &str, rows: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, format!("{}{}", header, rows)).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+
+        let full = write(
+            "CafeF.HSX.Upto07.11.2025.csv",
+            "AAA,20251107,10,10,10,10,100\nAAA,20251106,9,9,9,9,100\n",
+        );
+        let cache = dir.join("market_data.cache");
+        let cache = cache.to_str().unwrap();
+
+        let (mut store, report) = MarketDataStore::load_cached(&full, cache, MergePolicy::Reject).unwrap();
+        assert!(report.is_some());
+
+        // Delta gửi lại phiên 07/11 (cùng giá) + 1 phiên mới + 1 mã mới
+        let delta = write("delta_ok.csv", "AAA,20251110,11,11,11,11,100\nAAA,20251107,10,10,10,10,100\nCCC,20251110,5,5,5,5,10\n");
+        let report = store.append_file(&delta).unwrap();
+        assert_eq!(report.new_bars, 2);
+        assert_eq!(report.identical_bars, 1);
+        assert_eq!(store.get("AAA").unwrap().closes(), &[9.0, 10.0, 11.0]);
+        assert!(store.contains("CCC"));
+
+        // Phiên cũ hơn chưa có, hoặc khác giá → từ chối cả file, store giữ nguyên
+        let out_of_order = write("delta_old.csv", "AAA,20251111,12,12,12,12,100\nAAA,20251105,8,8,8,8,100\n");
+        assert!(matches!(store.append_file(&out_of_order), Err(DataIoError::OutOfOrderBar { .. })));
+        let conflicting = write("delta_bad.csv", "AAA,20251110,11.5,11.5,11.5,11.5,100\n");
+        assert!(matches!(store.append_file(&conflicting), Err(DataIoError::ConflictingBar { .. })));
+        assert_eq!(store.get("AAA").unwrap().len(), 3);
+
+        // Cache giữ cả các phiên đã append và được dùng lại khi nguồn không đổi
+        store.save_cache(cache).unwrap();
+        let (cached, report) = MarketDataStore::load_cached(&full, cache, MergePolicy::Reject).unwrap();
+        assert!(report.is_none());
+        assert_eq!(cached.get("AAA"), store.get("AAA"));
+        assert_eq!(cached.exchange("AAA"), Some(Exchange::Hsx));
+
+        // File nguồn đổi → parse lại
+        write("CafeF.HSX.Upto07.11.2025.csv", "AAA,20251107,10,10,10,10,100\n");
+        let (reloaded, report) = MarketDataStore::load_cached(&full, cache, MergePolicy::Reject).unwrap();
+        assert!(report.is_some());
+        assert_eq!(reloaded.get("AAA").unwrap().len(), 1);
+
+        std::fs::write(cache, b"garbage").unwrap();
+        assert!(matches!(read_cache(cache), Err(DataIoError::InvalidCache { .. })));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_and_arrow_round_trip() {
+        let store = MarketDataStore::load_csv("../data/CafeF.HSX.Upto10.11.2025.csv").unwrap();
+        let dir = std::env::temp_dir();
+        let parquet_path = dir.join("data_io_prices_test.parquet");
+        let arrow_path = dir.join("data_io_prices_test.arrow");
+        let parquet_path = parquet_path.to_str().unwrap();
+        let arrow_path = arrow_path.to_str().unwrap();
+
+        write_parquet_prices(&store, parquet_path).unwrap();
+        write_arrow_ipc_prices(&store, arrow_path).unwrap();
+
+        for loaded in [MarketDataStore::load(parquet_path).unwrap(), MarketDataStore::load(arrow_path).unwrap()] {
+            assert_eq!(loaded.tickers(), store.tickers());
+            assert_eq!(loaded.get("AAA"), store.get("AAA"));
+        }
+
+        // Paths khác nhau, đủ nhiều dòng (300 x 251) để ghi thành nhiều record batch
+        let paths: Vec<Vec<f64>> = (0..300)
+            .map(|p| (0..251).map(|s| 100.0 + p as f64 * 0.5 + s as f64 * 0.01).collect())
+            .collect();
+        let expected: Vec<(u32, u32, f64)> = paths
+            .iter()
+            .enumerate()
+            .flat_map(|(p, path)| path.iter().enumerate().map(move |(s, &price)| (p as u32, s as u32, price)))
+            .collect();
+        let rows = |batches: Vec<arrow::array::RecordBatch>| {
+            use arrow::array::{Array, Float64Array, UInt32Array};
+            let mut rows = Vec::new();
+            for batch in &batches {
+                assert_eq!(batch.schema(), paths_table_schema());
+                let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+                let (path, step, price) = (column("path"), column("step"), column("price"));
+                let path = path.as_any().downcast_ref::<UInt32Array>().unwrap();
+                let step = step.as_any().downcast_ref::<UInt32Array>().unwrap();
+                let price = price.as_any().downcast_ref::<Float64Array>().unwrap();
+                rows.extend((0..batch.num_rows()).map(|i| (path.value(i), step.value(i), price.value(i))));
+            }
+            rows
+        };
+
+        let paths_parquet = dir.join("data_io_paths_test.parquet");
+        let paths_arrow = dir.join("data_io_paths_test.arrow");
+        write_paths_parquet(&paths, paths_parquet.to_str().unwrap()).unwrap();
+        write_paths_arrow_ipc(&paths, paths_arrow.to_str().unwrap()).unwrap();
+
+        let parquet_batches =
+            parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&paths_parquet).unwrap())
+                .unwrap()
+                .build()
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+        let arrow_batches = arrow::ipc::reader::FileReader::try_new(std::fs::File::open(&paths_arrow).unwrap(), None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(arrow_batches.len() > 1);
+        assert_eq!(rows(parquet_batches), expected);
+        assert_eq!(rows(arrow_batches), expected);
+
+        let _ = std::fs::remove_file(parquet_path);
+        let _ = std::fs::remove_file(arrow_path);
+        let _ = std::fs::remove_file(paths_parquet);
+        let _ = std::fs::remove_file(paths_arrow);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_database_matches_csv_store() {
+        let csv_path = "../data/CafeF.HSX.Upto10.11.2025.csv";
+        let db_path = std::env::temp_dir().join("data_io_prices_test.db");
+        let db_path = db_path.to_str().unwrap();
+        let _ = std::fs::remove_file(db_path);
+
+        let store = MarketDataStore::load(csv_path).unwrap();
+        let mut db = PriceDatabase::open(db_path).unwrap();
+        assert_eq!(db.import_csv(csv_path).unwrap(), 15935);
+        // Import lại cùng file chỉ ghi đè, không nhân đôi dữ liệu
+        db.import_csv(csv_path).unwrap();
+
+        let sources: [&dyn PriceSource; 2] = [&store, &db];
+        for source in sources {
+            assert_eq!(source.ticker_list().unwrap(), store.tickers());
+            assert_eq!(source.ticker_exchange("AAA").unwrap(), Some(Exchange::Hsx));
+            assert_eq!(source.price_series("AAA").unwrap(), *store.get("AAA").unwrap());
+            assert_eq!(source.ticker_info("AAA").unwrap(), get_stock_info(csv_path, "AAA").unwrap());
+            assert_eq!(source.close_prices("AAA").unwrap(), load_closes_for_ticker(csv_path, "AAA").unwrap());
+            assert!(matches!(source.price_series("ZZZ"), Err(DataIoError::UnknownTicker { .. })));
+        }
+
+        let from = chrono::NaiveDate::from_ymd_opt(2025, 11, 5).unwrap();
+        let to = chrono::NaiveDate::from_ymd_opt(2025, 11, 7).unwrap();
+        assert_eq!(db.price_range("AAA", from, to).unwrap(), store.get_range("AAA", from, to).unwrap());
+
+        drop(db);
+        let reloaded = MarketDataStore::load(db_path).unwrap();
+        assert_eq!(reloaded.get("AAA"), store.get("AAA"));
+
+        let _ = std::fs::remove_file(db_path);
+    }
+}
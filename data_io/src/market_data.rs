@@ -0,0 +1,256 @@
+use chrono::NaiveDate;
+use csv::ReaderBuilder;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::{DataIoError, DataIoResult};
+use crate::exchange::Exchange;
+use crate::price_series::{PriceBar, PriceSeries};
+use crate::schema::CsvSchema;
+
+/// Kho dữ liệu giá của nhiều ticker, parse file CSV đúng 1 lần
+///
+/// Mỗi ticker được lưu thành 1 `PriceSeries` dạng cột. `BTreeMap` đóng vai trò
+/// index theo ticker (luôn sort theo tên) để tra cứu nhanh mà không cần đọc lại file.
+/// Sàn niêm yết của từng ticker (nếu biết) được lưu riêng trong `exchanges`.
+#[derive(Debug, Clone, Default)]
+pub struct MarketDataStore {
+    source_path: String,
+    series: BTreeMap<String, PriceSeries>,
+    exchanges: BTreeMap<String, Exchange>,
+}
+
+impl MarketDataStore {
+    /// Load file giá, chọn reader theo phần mở rộng:
+    /// `.parquet` / `.arrow`, `.ipc`, `.feather` (cần feature `parquet`),
+    /// `.db`, `.sqlite` (cần feature `sqlite`), còn lại là CSV
+    ///
+    /// Nếu tên file chứa tên sàn (ví dụ `CafeF.HNX.Upto...csv`), mọi ticker được gắn sàn đó
+    pub fn load(path: &str) -> DataIoResult<Self> {
+        let mut store = Self::load_file(path)?;
+        if let Some(exchange) = Exchange::from_path(path) {
+            store.tag_exchange(exchange);
+        }
+        Ok(store)
+    }
+
+    fn load_file(path: &str) -> DataIoResult<Self> {
+        let extension = Path::new(path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            #[cfg(feature = "parquet")]
+            "parquet" => crate::columnar::read_parquet_prices(path),
+            #[cfg(feature = "parquet")]
+            "arrow" | "ipc" | "feather" => crate::columnar::read_arrow_ipc_prices(path),
+            #[cfg(not(feature = "parquet"))]
+            "parquet" | "arrow" | "ipc" | "feather" => Err(DataIoError::Columnar {
+                path: path.to_string(),
+                message: "columnar formats require data_io to be built with the `parquet` feature"
+                    .to_string(),
+            }),
+            #[cfg(feature = "sqlite")]
+            "db" | "sqlite" | "sqlite3" => crate::sqlite::PriceDatabase::open(path)?.to_store(),
+            #[cfg(not(feature = "sqlite"))]
+            "db" | "sqlite" | "sqlite3" => Err(DataIoError::Database {
+                path: path.to_string(),
+                message: "SQLite databases require data_io to be built with the `sqlite` feature"
+                    .to_string(),
+            }),
+            _ => Self::load_csv(path),
+        }
+    }
+
+    /// Đọc toàn bộ file CSV 1 lần và gom dòng theo ticker
+    ///
+    /// Layout được tự nhận diện từ header (CafeF, SSI, Vietstock, Yahoo)
+    pub fn load_csv(csv_path: &str) -> DataIoResult<Self> {
+        Self::load_csv_with(csv_path, None)
+    }
+
+    /// Giống `load_csv` nhưng dùng schema do người dùng chỉ định
+    pub fn load_csv_with_schema(csv_path: &str, schema: &CsvSchema) -> DataIoResult<Self> {
+        Self::load_csv_with(csv_path, Some(schema))
+    }
+
+    fn load_csv_with(csv_path: &str, schema: Option<&CsvSchema>) -> DataIoResult<Self> {
+        let mut bars_by_ticker: BTreeMap<String, Vec<PriceBar>> = BTreeMap::new();
+        for_each_csv_bar(csv_path, schema, |ticker, bar| {
+            bars_by_ticker.entry(ticker).or_default().push(bar);
+            Ok(())
+        })?;
+
+        let mut store = Self::from_bars(bars_by_ticker)?;
+        store.set_source_path(csv_path);
+        Ok(store)
+    }
+
+    /// Tạo store từ các bars đã được gom theo ticker
+    pub fn from_bars(bars_by_ticker: BTreeMap<String, Vec<PriceBar>>) -> DataIoResult<Self> {
+        let mut series = BTreeMap::new();
+        for (ticker, bars) in bars_by_ticker {
+            let ticker_series = PriceSeries::from_bars(&ticker, bars)?;
+            series.insert(ticker, ticker_series);
+        }
+
+        Ok(MarketDataStore {
+            source_path: String::new(),
+            series,
+            exchanges: BTreeMap::new(),
+        })
+    }
+
+    pub(crate) fn set_source_path(&mut self, path: &str) {
+        self.source_path = path.to_string();
+    }
+
+    /// Đường dẫn file đã load (rỗng nếu store được tạo trong bộ nhớ)
+    pub fn source_path(&self) -> &str {
+        &self.source_path
+    }
+
+    /// Gắn sàn cho mọi ticker hiện có trong store
+    pub fn tag_exchange(&mut self, exchange: Exchange) {
+        for ticker in self.series.keys() {
+            self.exchanges.insert(ticker.clone(), exchange);
+        }
+    }
+
+    /// Sàn niêm yết của ticker (None nếu nguồn dữ liệu không cho biết)
+    pub fn exchange(&self, ticker: &str) -> Option<Exchange> {
+        self.exchanges.get(ticker).copied()
+    }
+
+    pub(crate) fn set_exchange(&mut self, ticker: &str, exchange: Exchange) {
+        self.exchanges.insert(ticker.to_string(), exchange);
+    }
+
+    /// Danh sách ticker thuộc 1 sàn, đã sort theo tên
+    pub fn tickers_on(&self, exchange: Exchange) -> Vec<String> {
+        self.exchanges
+            .iter()
+            .filter(|(_, &e)| e == exchange)
+            .map(|(ticker, _)| ticker.clone())
+            .collect()
+    }
+
+    /// Danh sách ticker đã sort theo tên
+    pub fn tickers(&self) -> Vec<String> {
+        self.series.keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.series.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+
+    pub fn contains(&self, ticker: &str) -> bool {
+        self.series.contains_key(ticker)
+    }
+
+    /// Lấy toàn bộ lịch sử của 1 ticker
+    pub fn get(&self, ticker: &str) -> Option<&PriceSeries> {
+        self.series.get(ticker)
+    }
+
+    /// Giống `get` nhưng trả về lỗi `UnknownTicker` nếu không có
+    pub fn require(&self, ticker: &str) -> DataIoResult<&PriceSeries> {
+        self.series.get(ticker).ok_or_else(|| DataIoError::UnknownTicker {
+            ticker: ticker.to_string(),
+        })
+    }
+
+    /// Lấy lịch sử của 1 ticker trong khoảng ngày [from, to]
+    pub fn get_range(&self, ticker: &str, from: NaiveDate, to: NaiveDate) -> Option<PriceSeries> {
+        self.series
+            .get(ticker)
+            .map(|series| series.slice_by_date(from, to))
+    }
+
+    /// Thay từng series bằng kết quả của `f` (dùng cho các bước biến đổi như điều chỉnh giá)
+    pub(crate) fn map_series<F>(&mut self, mut f: F) -> DataIoResult<()>
+    where
+        F: FnMut(&PriceSeries) -> DataIoResult<PriceSeries>,
+    {
+        for series in self.series.values_mut() {
+            *series = f(series)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn series_mut(&mut self, ticker: &str) -> Option<&mut PriceSeries> {
+        self.series.get_mut(ticker)
+    }
+
+    /// Thêm hoặc thay thế series của 1 ticker
+    pub(crate) fn insert_series(&mut self, series: PriceSeries) {
+        self.series.insert(series.ticker().to_string(), series);
+    }
+
+    /// Duyệt tất cả series theo thứ tự ticker
+    pub fn iter(&self) -> impl Iterator<Item = &PriceSeries> {
+        self.series.values()
+    }
+}
+
+/// Đọc tuần tự từng dòng của file CSV giá, gọi `f(ticker, bar)` cho mỗi dòng có giá
+///
+/// Không giữ cả file trong bộ nhớ. `schema = None` → tự nhận diện layout từ header.
+pub(crate) fn for_each_csv_bar<F>(
+    csv_path: &str,
+    schema: Option<&CsvSchema>,
+    mut f: F,
+) -> DataIoResult<()>
+where
+    F: FnMut(String, PriceBar) -> DataIoResult<()>,
+{
+    let delimiter = schema.map(|s| s.delimiter).unwrap_or(b',');
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .delimiter(delimiter)
+        .from_path(csv_path)
+        .map_err(|e| DataIoError::from_csv(csv_path, e))?;
+
+    let headers = reader
+        .headers()
+        .map_err(|e| DataIoError::from_csv(csv_path, e))?
+        .clone();
+
+    let schema = match schema {
+        Some(schema) => schema.clone(),
+        None => CsvSchema::detect(&headers).ok_or_else(|| DataIoError::BadHeader {
+            path: csv_path.to_string(),
+            message: format!(
+                "unrecognized layout '{}' (supported: {})",
+                headers.iter().collect::<Vec<_>>().join(","),
+                CsvSchema::presets()
+                    .iter()
+                    .map(|s| s.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        })?,
+    };
+    let columns = schema.resolve(csv_path, &headers)?;
+
+    // File 1 mã không có cột ticker → dùng tên file (VNM.csv → VNM)
+    let fallback_ticker = Path::new(csv_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_uppercase())
+        .unwrap_or_default();
+
+    for result in reader.records() {
+        let row = result.map_err(|e| DataIoError::from_csv(csv_path, e))?;
+        let line = row.position().map(|pos| pos.line()).unwrap_or(0);
+        if let Some((ticker, bar)) = schema.parse_row(&columns, &row, &fallback_ticker, line)? {
+            f(ticker, bar)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,411 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use std::path::{Path, PathBuf};
+
+use crate::error::{DataIoError, DataIoResult};
+use crate::exchange::Exchange;
+use crate::market_data::MarketDataStore;
+use crate::price_series::{PriceBar, PriceSeries};
+
+/// Phần mở rộng được nhận khi load cả thư mục
+const DATA_FILE_EXTENSIONS: [&str; 5] = ["csv", "parquet", "arrow", "ipc", "feather"];
+
+/// Sai số tương đối cho phép khi so sánh giá của cùng 1 phiên từ 2 nguồn
+const PRICE_TOLERANCE: f64 = 1e-6;
+
+/// Cách xử lý khi 2 nguồn có giá khác nhau cho cùng ticker và ngày
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// Giữ bar đã có, bỏ bar mới
+    KeepExisting,
+    /// Dùng bar của nguồn load sau (file cập nhật mới hơn)
+    #[default]
+    PreferIncoming,
+    /// Trả về lỗi `ConflictingBar` ngay khi gặp xung đột
+    Reject,
+}
+
+/// 1 phiên có dữ liệu khác nhau giữa 2 nguồn
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarConflict {
+    pub ticker: String,
+    pub date: NaiveDate,
+    pub existing: PriceBar,
+    pub incoming: PriceBar,
+}
+
+impl BarConflict {
+    fn describe(&self) -> String {
+        format!(
+            "close {} vs {}, volume {} vs {}",
+            self.existing.close, self.incoming.close, self.existing.volume, self.incoming.volume
+        )
+    }
+}
+
+/// Kết quả gộp nhiều nguồn dữ liệu
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeReport {
+    /// Các file đã load, theo thứ tự gộp
+    pub files: Vec<String>,
+    /// Số phiên mới được thêm vào (không tính phiên đã có)
+    pub new_bars: usize,
+    /// Số phiên trùng ngày giữa các nguồn và có cùng giá
+    pub identical_bars: usize,
+    pub conflicts: Vec<BarConflict>,
+}
+
+impl MergeReport {
+    fn absorb(&mut self, other: MergeReport) {
+        self.files.extend(other.files);
+        self.new_bars += other.new_bars;
+        self.identical_bars += other.identical_bars;
+        self.conflicts.extend(other.conflicts);
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} files, {} new bars, {} overlapping, {} conflicts",
+            self.files.len(),
+            self.new_bars,
+            self.identical_bars,
+            self.conflicts.len()
+        )
+    }
+}
+
+/// 2 bar cùng ngày được coi là giống nhau nếu OHLC lệch không quá `PRICE_TOLERANCE` và cùng volume
+fn bars_agree(a: &PriceBar, b: &PriceBar) -> bool {
+    let close_enough = |x: f64, y: f64| (x - y).abs() <= PRICE_TOLERANCE * x.abs().max(y.abs());
+    close_enough(a.open, b.open)
+        && close_enough(a.high, b.high)
+        && close_enough(a.low, b.low)
+        && close_enough(a.close, b.close)
+        && a.volume == b.volume
+}
+
+/// Gộp 2 series của cùng ticker theo ngày, ghi nhận các phiên xung đột vào `report`
+fn merge_series(
+    existing: &PriceSeries,
+    incoming: &PriceSeries,
+    policy: MergePolicy,
+    report: &mut MergeReport,
+) -> DataIoResult<PriceSeries> {
+    let mut bars = Vec::with_capacity(existing.len() + incoming.len());
+    let (mut i, mut j) = (0, 0);
+
+    // Cả 2 series đã sort tăng dần theo ngày → gộp kiểu merge sort
+    while i < existing.len() || j < incoming.len() {
+        let old = existing.bar(i);
+        let new = incoming.bar(j);
+        match (old, new) {
+            (Some(old), Some(new)) if old.date == new.date => {
+                if bars_agree(&old, &new) {
+                    report.identical_bars += 1;
+                    bars.push(old);
+                } else {
+                    let conflict = BarConflict {
+                        ticker: existing.ticker().to_string(),
+                        date: old.date,
+                        existing: old.clone(),
+                        incoming: new.clone(),
+                    };
+                    if policy == MergePolicy::Reject {
+                        return Err(DataIoError::ConflictingBar {
+                            ticker: conflict.ticker.clone(),
+                            date: conflict.date,
+                            message: conflict.describe(),
+                        });
+                    }
+                    report.conflicts.push(conflict);
+                    bars.push(if policy == MergePolicy::KeepExisting {
+                        old
+                    } else {
+                        new
+                    });
+                }
+                i += 1;
+                j += 1;
+            }
+            (Some(old), Some(new)) if old.date < new.date => {
+                bars.push(old);
+                i += 1;
+            }
+            (Some(old), None) => {
+                bars.push(old);
+                i += 1;
+            }
+            (_, Some(new)) => {
+                report.new_bars += 1;
+                bars.push(new);
+                j += 1;
+            }
+            (None, None) => break,
+        }
+    }
+
+    let mut merged = PriceSeries::from_bars(existing.ticker(), bars)?;
+    let mut duplicate_dates = existing.duplicate_dates().to_vec();
+    duplicate_dates.extend_from_slice(incoming.duplicate_dates());
+    duplicate_dates.sort();
+    merged.set_duplicate_dates(duplicate_dates);
+    Ok(merged)
+}
+
+fn is_single_file(source: &str) -> bool {
+    !Path::new(source).is_dir() && !source.contains(['*', '?', '['])
+}
+
+/// Ngày dữ liệu trong tên file CafeF, ví dụ `CafeF.HSX.Upto05.12.2025.csv` → 2025-12-05
+pub fn file_data_date(path: &Path) -> Option<NaiveDate> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    let start = name.find("upto")? + "upto".len();
+    let date = name.get(start..start + "dd.mm.yyyy".len())?;
+    NaiveDate::parse_from_str(date, "%d.%m.%Y").ok()
+}
+
+/// Sắp xếp file từ cũ đến mới để file sau (mới hơn) thắng khi gộp với `PreferIncoming`
+///
+/// Dùng ngày `Upto` trong tên file, không có thì dùng thời điểm sửa file; cùng thời điểm
+/// thì theo tên.
+fn sort_chronologically(paths: &mut [PathBuf]) {
+    let data_time = |path: &PathBuf| -> NaiveDateTime {
+        file_data_date(path)
+            .map(|date| date.and_time(chrono::NaiveTime::MIN))
+            .or_else(|| {
+                let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+                Some(chrono::DateTime::<chrono::Utc>::from(modified).naive_utc())
+            })
+            .unwrap_or(NaiveDateTime::MIN)
+    };
+    paths.sort_by_cached_key(|path| (data_time(path), path.clone()));
+}
+
+/// Các file dữ liệu (csv, parquet, arrow...) trong 1 thư mục, từ cũ đến mới
+pub fn dir_files(dir: &str) -> DataIoResult<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| DataIoError::from_io(dir, e))?;
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| DataIoError::from_io(dir, e))?.path();
+        let supported = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .is_some_and(|ext| DATA_FILE_EXTENSIONS.contains(&ext.as_str()));
+        if path.is_file() && supported {
+            paths.push(path);
+        }
+    }
+    sort_chronologically(&mut paths);
+    Ok(paths)
+}
+
+/// Các file khớp glob pattern, từ cũ đến mới
+pub fn glob_files(pattern: &str) -> DataIoResult<Vec<PathBuf>> {
+    let invalid = |message: String| DataIoError::InvalidSource {
+        path: pattern.to_string(),
+        message,
+    };
+
+    let mut paths = Vec::new();
+    for entry in glob::glob(pattern).map_err(|e| invalid(e.to_string()))? {
+        let path = entry.map_err(|e| invalid(e.to_string()))?;
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    sort_chronologically(&mut paths);
+    Ok(paths)
+}
+
+/// Danh sách file ứng với 1 nguồn dữ liệu (file, thư mục hoặc glob), theo thứ tự load
+pub fn source_files(source: &str) -> DataIoResult<Vec<PathBuf>> {
+    if Path::new(source).is_dir() {
+        dir_files(source)
+    } else if is_single_file(source) {
+        Ok(vec![PathBuf::from(source)])
+    } else {
+        glob_files(source)
+    }
+}
+
+/// Kiểm tra delta của 1 ticker có nối tiếp được vào series đã có không
+///
+/// Trả về vị trí bar đầu tiên của `incoming` nằm sau phiên cuối của `existing`.
+/// Các bar trước đó phải trùng khớp với dữ liệu đã có.
+fn check_append(
+    existing: &PriceSeries,
+    incoming: &PriceSeries,
+    report: &mut MergeReport,
+) -> DataIoResult<usize> {
+    let Some(last_date) = existing.last_date() else {
+        return Ok(0);
+    };
+    let start = incoming.dates().partition_point(|&d| d <= last_date);
+
+    for new in (0..start).filter_map(|i| incoming.bar(i)) {
+        let old = existing
+            .dates()
+            .binary_search(&new.date)
+            .ok()
+            .and_then(|index| existing.bar(index))
+            .ok_or_else(|| DataIoError::OutOfOrderBar {
+                ticker: existing.ticker().to_string(),
+                date: new.date,
+                last_date,
+            })?;
+
+        if !bars_agree(&old, &new) {
+            let conflict = BarConflict {
+                ticker: existing.ticker().to_string(),
+                date: new.date,
+                existing: old,
+                incoming: new,
+            };
+            return Err(DataIoError::ConflictingBar {
+                ticker: conflict.ticker.clone(),
+                date: conflict.date,
+                message: conflict.describe(),
+            });
+        }
+        report.identical_bars += 1;
+    }
+
+    Ok(start)
+}
+
+impl MarketDataStore {
+    /// Nối dữ liệu mới (file delta hằng ngày) vào cuối lịch sử mà không load lại toàn bộ
+    ///
+    /// Khác `merge`, append không sửa lịch sử đã có: phiên trùng ngày và cùng giá (delta gửi lại)
+    /// được bỏ qua, phiên khác giá trả lỗi `ConflictingBar`, phiên cũ hơn phiên cuối mà chưa có
+    /// trả lỗi `OutOfOrderBar`. Toàn bộ delta được kiểm tra trước khi ghi nên khi lỗi store giữ nguyên.
+    pub fn append(&mut self, delta: MarketDataStore) -> DataIoResult<MergeReport> {
+        let mut report = MergeReport::default();
+        if !delta.source_path().is_empty() {
+            report.files.push(delta.source_path().to_string());
+        }
+
+        let mut starts = Vec::with_capacity(delta.len());
+        for incoming in delta.iter() {
+            let start = match self.get(incoming.ticker()) {
+                Some(existing) => check_append(existing, incoming, &mut report)?,
+                None => 0,
+            };
+            report.new_bars += incoming.len() - start;
+            starts.push(start);
+        }
+
+        for (incoming, start) in delta.iter().zip(starts) {
+            match self.series_mut(incoming.ticker()) {
+                Some(existing) => existing.extend_from(incoming, start),
+                None => self.insert_series(incoming.clone()),
+            }
+            if let Some(exchange) = delta.exchange(incoming.ticker()) {
+                self.set_exchange(incoming.ticker(), exchange);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Load 1 file delta (CSV, Parquet...) rồi `append` vào store
+    pub fn append_file(&mut self, path: &str) -> DataIoResult<MergeReport> {
+        let delta = MarketDataStore::load(path)?;
+        self.append(delta)
+    }
+
+    /// Gộp dữ liệu từ store khác vào store này
+    ///
+    /// Ticker mới được thêm nguyên series. Ticker đã có được gộp theo ngày: phiên trùng ngày
+    /// và cùng giá chỉ giữ 1 bản, phiên khác giá xử lý theo `policy` và ghi vào report.
+    /// Sàn của store mới ghi đè sàn cũ (mã chuyển sàn xuất hiện trong file mới hơn).
+    pub fn merge(
+        &mut self,
+        other: MarketDataStore,
+        policy: MergePolicy,
+    ) -> DataIoResult<MergeReport> {
+        let mut report = MergeReport::default();
+        if !other.source_path().is_empty() {
+            report.files.push(other.source_path().to_string());
+        }
+
+        for incoming in other.iter() {
+            let merged = match self.get(incoming.ticker()) {
+                Some(existing) => merge_series(existing, incoming, policy, &mut report)?,
+                None => {
+                    report.new_bars += incoming.len();
+                    incoming.clone()
+                }
+            };
+            if let Some(exchange) = other.exchange(incoming.ticker()) {
+                self.set_exchange(incoming.ticker(), exchange);
+            }
+            self.insert_series(merged);
+        }
+
+        Ok(report)
+    }
+
+    /// Load và gộp nhiều file theo đúng thứ tự truyền vào (file sau là nguồn "incoming")
+    pub fn load_many<P: AsRef<Path>>(
+        paths: &[P],
+        policy: MergePolicy,
+    ) -> DataIoResult<(Self, MergeReport)> {
+        let mut store = MarketDataStore::default();
+        let mut report = MergeReport::default();
+
+        for path in paths {
+            let path = path.as_ref().to_string_lossy();
+            let file_store = MarketDataStore::load(&path)?;
+            report.absorb(store.merge(file_store, policy)?);
+        }
+        store.set_source_path(&report.files.join("; "));
+
+        Ok((store, report))
+    }
+
+    /// Load mọi file dữ liệu (csv, parquet, arrow...) trong 1 thư mục, theo thứ tự tên file
+    pub fn load_dir(dir: &str, policy: MergePolicy) -> DataIoResult<(Self, MergeReport)> {
+        Self::load_sources(dir, dir_files(dir)?, policy)
+    }
+
+    /// Load mọi file khớp glob pattern, ví dụ `data/CafeF.*.csv`, theo thứ tự tên file
+    pub fn load_glob(pattern: &str, policy: MergePolicy) -> DataIoResult<(Self, MergeReport)> {
+        Self::load_sources(pattern, glob_files(pattern)?, policy)
+    }
+
+    /// Chọn cách load theo đầu vào: thư mục, glob pattern (có `*`, `?`, `[`) hoặc 1 file
+    pub fn load_any(source: &str, policy: MergePolicy) -> DataIoResult<(Self, MergeReport)> {
+        if is_single_file(source) {
+            Self::load_many(&[source], policy)
+        } else {
+            Self::load_sources(source, source_files(source)?, policy)
+        }
+    }
+
+    fn load_sources<P: AsRef<Path>>(
+        source: &str,
+        paths: Vec<P>,
+        policy: MergePolicy,
+    ) -> DataIoResult<(Self, MergeReport)> {
+        if paths.is_empty() {
+            return Err(DataIoError::InvalidSource {
+                path: source.to_string(),
+                message: "no data files found".to_string(),
+            });
+        }
+
+        let (mut store, report) = Self::load_many(&paths, policy)?;
+        store.set_source_path(source);
+        Ok((store, report))
+    }
+
+    /// Số ticker theo từng sàn (chỉ gồm các sàn có dữ liệu)
+    pub fn exchange_counts(&self) -> Vec<(Exchange, usize)> {
+        Exchange::ALL
+            .iter()
+            .map(|&exchange| (exchange, self.tickers_on(exchange).len()))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+}
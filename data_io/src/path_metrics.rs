@@ -0,0 +1,230 @@
+use crate::error::{StatsError, StatsResult};
+use crate::statistics::{quantiles, summary_stats, NanPolicy, QuantileMethod, SummaryStats};
+
+/// Các quantile mặc định cho fan chart
+pub const DEFAULT_FAN_QUANTILES: [f64; 5] = [0.05, 0.25, 0.5, 0.75, 0.95];
+
+/// Ngưỡng giá trên / dưới để tính xác suất chạm trong kỳ (None = không dùng)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Barriers {
+    pub upper: Option<f64>,
+    pub lower: Option<f64>,
+}
+
+impl Barriers {
+    /// Ngưỡng theo % thay đổi so với giá ban đầu, vd `from_percent(100.0, 20.0, 15.0)` → 120 / 85;
+    /// giá trị ≤ 0 nghĩa là không dùng ngưỡng đó
+    pub fn from_percent(initial_price: f64, up_percent: f64, down_percent: f64) -> Self {
+        Barriers {
+            upper: (up_percent > 0.0).then(|| initial_price * (1.0 + up_percent / 100.0)),
+            lower: (down_percent > 0.0 && down_percent < 100.0)
+                .then(|| initial_price * (1.0 - down_percent / 100.0)),
+        }
+    }
+}
+
+/// Các chỉ số dọc theo 1 path; thời gian tính bằng số bước (nhân `dt` để ra phiên)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathMetrics {
+    /// Mức sụt lớn nhất từ đỉnh trước đó, theo tỷ lệ (0.25 = -25%)
+    pub max_drawdown: f64,
+    /// Mức tăng lớn nhất từ đáy trước đó, theo tỷ lệ
+    pub max_run_up: f64,
+    /// Đoạn dài nhất liên tục nằm dưới đỉnh trước đó
+    pub time_under_water: usize,
+    /// Số bước từ đáy của max drawdown đến khi quay lại đỉnh cũ, None nếu chưa hồi phục trong kỳ
+    pub recovery_steps: Option<usize>,
+    /// Bước đầu tiên chạm ngưỡng trên / dưới
+    pub upper_hit: Option<usize>,
+    pub lower_hit: Option<usize>,
+}
+
+/// Tính chỉ số của 1 path (path gồm cả giá ban đầu ở vị trí 0)
+///
+/// Ngưỡng chỉ được kiểm tra tại các bước mô phỏng nên xác suất chạm là cận dưới
+/// của xác suất khi giá theo dõi liên tục.
+pub fn path_metrics(path: &[f64], barriers: &Barriers) -> PathMetrics {
+    let mut peak = f64::NEG_INFINITY;
+    let mut trough = f64::INFINITY;
+    let mut max_drawdown = 0.0;
+    let mut max_run_up = 0.0;
+    let mut drawdown_peak = 0.0;
+    let mut drawdown_trough_index = 0;
+    let mut under_water = 0;
+    let mut time_under_water = 0;
+
+    for (i, &price) in path.iter().enumerate() {
+        if price >= peak {
+            peak = price;
+            under_water = 0;
+        } else {
+            under_water += 1;
+            time_under_water = time_under_water.max(under_water);
+            let drawdown = (peak - price) / peak;
+            if drawdown > max_drawdown {
+                max_drawdown = drawdown;
+                drawdown_peak = peak;
+                drawdown_trough_index = i;
+            }
+        }
+        trough = trough.min(price);
+        let run_up = (price - trough) / trough;
+        if run_up > max_run_up {
+            max_run_up = run_up;
+        }
+    }
+
+    let recovery_steps = if max_drawdown > 0.0 {
+        path[drawdown_trough_index..]
+            .iter()
+            .position(|&price| price >= drawdown_peak)
+    } else {
+        Some(0)
+    };
+
+    PathMetrics {
+        max_drawdown,
+        max_run_up,
+        time_under_water,
+        recovery_steps,
+        upper_hit: barriers
+            .upper
+            .and_then(|upper| path.iter().position(|&price| price >= upper)),
+        lower_hit: barriers
+            .lower
+            .and_then(|lower| path.iter().position(|&price| price <= lower)),
+    }
+}
+
+/// Phân phối các chỉ số path trên toàn bộ mô phỏng
+#[derive(Debug, Clone)]
+pub struct PathMetricsSummary {
+    pub path_count: usize,
+    pub max_drawdown: SummaryStats,
+    pub max_run_up: SummaryStats,
+    pub time_under_water: SummaryStats,
+    /// Tỷ lệ path quay lại đỉnh trước max drawdown trong kỳ
+    pub recovered_fraction: f64,
+    /// Thời gian hồi phục trên các path đã hồi phục (None nếu ít hơn 2 path)
+    pub recovery_steps: Option<SummaryStats>,
+    pub barriers: Barriers,
+    pub upper_hit_probability: Option<f64>,
+    pub lower_hit_probability: Option<f64>,
+    /// Xác suất chạm ít nhất 1 trong 2 ngưỡng
+    pub any_hit_probability: Option<f64>,
+}
+
+/// Tổng hợp chỉ số của từng path, cần ít nhất 2 path
+pub fn summarize_path_metrics(
+    paths: &[Vec<f64>],
+    barriers: &Barriers,
+) -> StatsResult<PathMetricsSummary> {
+    if paths.iter().any(|path| path.is_empty()) {
+        return Err(StatsError::Empty);
+    }
+    let metrics: Vec<PathMetrics> = paths
+        .iter()
+        .map(|path| path_metrics(path, barriers))
+        .collect();
+    let stats = |value: fn(&PathMetrics) -> f64| {
+        let values: Vec<f64> = metrics.iter().map(value).collect();
+        summary_stats(&values, QuantileMethod::Type7, NanPolicy::Reject)
+    };
+    let probability = |hit: fn(&PathMetrics) -> bool| {
+        metrics.iter().filter(|m| hit(m)).count() as f64 / metrics.len() as f64
+    };
+
+    let recovery: Vec<f64> = metrics
+        .iter()
+        .filter_map(|m| m.recovery_steps.map(|steps| steps as f64))
+        .collect();
+
+    Ok(PathMetricsSummary {
+        path_count: metrics.len(),
+        max_drawdown: stats(|m| m.max_drawdown)?,
+        max_run_up: stats(|m| m.max_run_up)?,
+        time_under_water: stats(|m| m.time_under_water as f64)?,
+        recovered_fraction: recovery.len() as f64 / metrics.len() as f64,
+        recovery_steps: summary_stats(&recovery, QuantileMethod::Type7, NanPolicy::Reject).ok(),
+        barriers: *barriers,
+        upper_hit_probability: barriers
+            .upper
+            .map(|_| probability(|m| m.upper_hit.is_some())),
+        lower_hit_probability: barriers
+            .lower
+            .map(|_| probability(|m| m.lower_hit.is_some())),
+        any_hit_probability: (barriers.upper.is_some() || barriers.lower.is_some())
+            .then(|| probability(|m| m.upper_hit.is_some() || m.lower_hit.is_some())),
+    })
+}
+
+/// Quantile của giá tại từng bước, tính chéo qua mọi path (dùng cho fan chart)
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantileBands {
+    /// Xác suất theo thứ tự tăng dần
+    pub probabilities: Vec<f64>,
+    /// `bands[k][t]`: quantile `probabilities[k]` của giá tại bước t
+    pub bands: Vec<Vec<f64>>,
+}
+
+impl QuantileBands {
+    /// Số bước (kể cả bước 0 là giá ban đầu)
+    pub fn steps(&self) -> usize {
+        self.bands.first().map_or(0, |band| band.len())
+    }
+
+    /// Dải quantile của xác suất `p`, None nếu không được tính
+    pub fn band(&self, p: f64) -> Option<&[f64]> {
+        self.probabilities
+            .iter()
+            .position(|&q| (q - p).abs() < 1e-12)
+            .map(|k| self.bands[k].as_slice())
+    }
+}
+
+/// Tính quantile theo từng bước qua mọi path (Hyndman-Fan type 7, bỏ qua NaN)
+///
+/// Các path phải cùng độ dài như kết quả của `core_sim::run_simulation`.
+pub fn path_quantile_bands(
+    paths: &[Vec<f64>],
+    probabilities: &[f64],
+) -> StatsResult<QuantileBands> {
+    let steps = paths.first().map_or(0, |path| path.len());
+    if steps == 0 {
+        return Err(StatsError::Empty);
+    }
+    if let Some((index, ragged)) = paths
+        .iter()
+        .enumerate()
+        .find(|(_, path)| path.len() != steps)
+    {
+        return Err(StatsError::LengthMismatch {
+            index,
+            expected: steps,
+            found: ragged.len(),
+        });
+    }
+    let mut probabilities = probabilities.to_vec();
+    probabilities.sort_by(|a, b| a.total_cmp(b));
+
+    let mut bands = vec![Vec::with_capacity(steps); probabilities.len()];
+    let mut column = Vec::with_capacity(paths.len());
+    for t in 0..steps {
+        column.clear();
+        column.extend(paths.iter().map(|path| path[t]));
+        let values = quantiles(
+            &column,
+            &probabilities,
+            QuantileMethod::Type7,
+            NanPolicy::Omit,
+        )?;
+        for (band, value) in bands.iter_mut().zip(values) {
+            band.push(value);
+        }
+    }
+
+    Ok(QuantileBands {
+        probabilities,
+        bands,
+    })
+}
@@ -0,0 +1,265 @@
+use chrono::NaiveDate;
+
+use crate::error::{DataIoError, DataIoResult};
+use crate::statistics::{calculate_log_returns, estimate_volatility, VolatilityEstimator};
+use crate::stock_price::StockPrice;
+
+/// Format ngày trong file CafeF (cột `<DTYYYYMMDD>`)
+pub const CAFEF_DATE_FORMAT: &str = "%Y%m%d";
+
+/// Một phiên giao dịch (OHLCV) với ngày đã được parse
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceBar {
+    pub date: NaiveDate,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+}
+
+impl PriceBar {
+    /// Chuyển 1 dòng CSV thô sang PriceBar (parse ngày YYYYMMDD)
+    ///
+    /// `line` là số dòng trong file, dùng cho thông báo lỗi
+    pub fn from_stock_price(record: &StockPrice, line: u64) -> DataIoResult<Self> {
+        let date = NaiveDate::parse_from_str(record.date.trim(), CAFEF_DATE_FORMAT)
+            .map_err(|e| DataIoError::MalformedRow {
+                line,
+                message: format!("invalid date '{}' for ticker {}: {}", record.date, record.ticker, e),
+            })?;
+
+        Ok(PriceBar {
+            date,
+            open: record.open,
+            high: record.high,
+            low: record.low,
+            close: record.close,
+            volume: record.volume,
+        })
+    }
+
+    /// Kiểm tra giá dương, hữu hạn và volume không âm
+    pub fn validate(&self, ticker: &str) -> DataIoResult<()> {
+        for (field, price) in [
+            ("open", self.open),
+            ("high", self.high),
+            ("low", self.low),
+            ("close", self.close),
+        ] {
+            if !price.is_finite() || price <= 0.0 {
+                return Err(DataIoError::NonPositivePrice {
+                    ticker: ticker.to_string(),
+                    date: self.date,
+                    field,
+                    price,
+                });
+            }
+        }
+        if self.volume < 0 {
+            return Err(DataIoError::NegativeVolume {
+                ticker: ticker.to_string(),
+                date: self.date,
+                volume: self.volume,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Chuỗi giá lịch sử của 1 ticker
+///
+/// Dữ liệu được lưu theo cột (dates, open, high, low, close, volume) và luôn đảm bảo:
+/// - Ngày tăng dần (file CafeF xếp ngày mới nhất lên đầu)
+/// - Không trùng ngày (giữ lại dòng xuất hiện đầu tiên, ngày bị trùng lưu trong `duplicate_dates`)
+/// - Giá dương, hữu hạn và volume không âm
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriceSeries {
+    ticker: String,
+    dates: Vec<NaiveDate>,
+    open: Vec<f64>,
+    high: Vec<f64>,
+    low: Vec<f64>,
+    close: Vec<f64>,
+    volume: Vec<i64>,
+    duplicate_dates: Vec<NaiveDate>,
+}
+
+impl PriceSeries {
+    /// Tạo series từ danh sách bars theo thứ tự bất kỳ: validate, sort theo ngày, bỏ ngày trùng
+    pub fn from_bars(ticker: &str, mut bars: Vec<PriceBar>) -> DataIoResult<Self> {
+        for bar in &bars {
+            bar.validate(ticker)?;
+        }
+
+        // Sort ổn định → dòng xuất hiện trước trong file được giữ lại khi trùng ngày
+        bars.sort_by_key(|bar| bar.date);
+        let mut duplicate_dates = Vec::new();
+        bars.dedup_by(|bar, kept| {
+            let duplicate = bar.date == kept.date;
+            if duplicate {
+                duplicate_dates.push(bar.date);
+            }
+            duplicate
+        });
+
+        let mut series = PriceSeries {
+            ticker: ticker.to_string(),
+            duplicate_dates,
+            ..Default::default()
+        };
+        for bar in bars {
+            series.push_unchecked(bar);
+        }
+
+        Ok(series)
+    }
+
+    /// Tạo series từ các dòng CSV thô của cùng 1 ticker
+    ///
+    /// Giả định records theo đúng thứ tự trong file, ngay sau dòng header (để báo số dòng khi lỗi)
+    pub fn from_records(ticker: &str, records: &[StockPrice]) -> DataIoResult<Self> {
+        let bars = records
+            .iter()
+            .enumerate()
+            .map(|(i, record)| PriceBar::from_stock_price(record, i as u64 + 2))
+            .collect::<DataIoResult<Vec<_>>>()?;
+        Self::from_bars(ticker, bars)
+    }
+
+    fn push_unchecked(&mut self, bar: PriceBar) {
+        self.dates.push(bar.date);
+        self.open.push(bar.open);
+        self.high.push(bar.high);
+        self.low.push(bar.low);
+        self.close.push(bar.close);
+        self.volume.push(bar.volume);
+    }
+
+    pub fn ticker(&self) -> &str {
+        &self.ticker
+    }
+
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    pub fn dates(&self) -> &[NaiveDate] {
+        &self.dates
+    }
+
+    pub fn opens(&self) -> &[f64] {
+        &self.open
+    }
+
+    pub fn highs(&self) -> &[f64] {
+        &self.high
+    }
+
+    pub fn lows(&self) -> &[f64] {
+        &self.low
+    }
+
+    pub fn closes(&self) -> &[f64] {
+        &self.close
+    }
+
+    pub fn volumes(&self) -> &[i64] {
+        &self.volume
+    }
+
+    /// Các ngày bị trùng trong dữ liệu gốc (mỗi dòng bị bỏ đi xuất hiện 1 lần)
+    pub fn duplicate_dates(&self) -> &[NaiveDate] {
+        &self.duplicate_dates
+    }
+
+    pub(crate) fn set_duplicate_dates(&mut self, dates: Vec<NaiveDate>) {
+        self.duplicate_dates = dates;
+    }
+
+    /// Nối các bar từ vị trí `start` của `other` vào cuối series
+    ///
+    /// Caller phải đảm bảo các bar này có ngày sau `last_date()` (xem `MarketDataStore::append`)
+    pub(crate) fn extend_from(&mut self, other: &PriceSeries, start: usize) {
+        for bar in (start..other.len()).filter_map(|i| other.bar(i)) {
+            self.push_unchecked(bar);
+        }
+        self.duplicate_dates.extend_from_slice(&other.duplicate_dates);
+    }
+
+    /// Lấy bar thứ i (theo thứ tự thời gian)
+    pub fn bar(&self, index: usize) -> Option<PriceBar> {
+        if index >= self.len() {
+            return None;
+        }
+        Some(PriceBar {
+            date: self.dates[index],
+            open: self.open[index],
+            high: self.high[index],
+            low: self.low[index],
+            close: self.close[index],
+            volume: self.volume[index],
+        })
+    }
+
+    pub fn first_date(&self) -> Option<NaiveDate> {
+        self.dates.first().copied()
+    }
+
+    pub fn last_date(&self) -> Option<NaiveDate> {
+        self.dates.last().copied()
+    }
+
+    pub fn last_close(&self) -> Option<f64> {
+        self.close.last().copied()
+    }
+
+    /// Kiểm tra series có ít nhất `required` phiên
+    pub fn ensure_history(&self, required: usize) -> DataIoResult<()> {
+        if self.len() < required {
+            return Err(DataIoError::InsufficientHistory {
+                ticker: self.ticker.clone(),
+                required,
+                available: self.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Lấy đoạn series trong khoảng ngày [from, to] (bao gồm 2 đầu)
+    pub fn slice_by_date(&self, from: NaiveDate, to: NaiveDate) -> PriceSeries {
+        // dates đã sort tăng dần nên dùng binary search
+        let start = self.dates.partition_point(|&d| d < from);
+        let end = self.dates.partition_point(|&d| d <= to).max(start);
+
+        PriceSeries {
+            ticker: self.ticker.clone(),
+            dates: self.dates[start..end].to_vec(),
+            open: self.open[start..end].to_vec(),
+            high: self.high[start..end].to_vec(),
+            low: self.low[start..end].to_vec(),
+            close: self.close[start..end].to_vec(),
+            volume: self.volume[start..end].to_vec(),
+            duplicate_dates: self
+                .duplicate_dates
+                .iter()
+                .copied()
+                .filter(|&d| d >= from && d <= to)
+                .collect(),
+        }
+    }
+
+    /// Log-returns theo đúng chiều thời gian: r_t = ln(Close_t / Close_{t-1})
+    pub fn log_returns(&self) -> Vec<f64> {
+        calculate_log_returns(&self.close)
+    }
+
+    /// σ theo ngày bằng estimator được chọn (close-to-close hoặc dựa trên OHLC)
+    pub fn volatility(&self, estimator: VolatilityEstimator) -> f64 {
+        estimate_volatility(estimator, &self.open, &self.high, &self.low, &self.close)
+    }
+}
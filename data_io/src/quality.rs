@@ -0,0 +1,232 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::fmt;
+
+use crate::market_data::MarketDataStore;
+use crate::price_series::PriceSeries;
+use crate::statistics::{calculate_mean, calculate_stdev};
+
+/// Ngưỡng cho các bước kiểm tra chất lượng dữ liệu
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityConfig {
+    /// Số ngày làm việc (T2-T6) liên tiếp bị thiếu tối đa trước khi báo gap.
+    /// Mặc định 5 để không báo các kỳ nghỉ lễ thông thường
+    pub max_missing_weekdays: usize,
+    /// Ngưỡng |z-score| của log-return để coi là outlier
+    pub outlier_z_score: f64,
+    /// Sai số tương đối cho phép khi so sánh OHLC. Giá CafeF đã điều chỉnh được làm tròn
+    /// nên high có thể thấp hơn close vài phần vạn
+    pub ohlc_tolerance: f64,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        QualityConfig {
+            max_missing_weekdays: 5,
+            outlier_z_score: 5.0,
+            ohlc_tolerance: 0.001,
+        }
+    }
+}
+
+/// Loại vấn đề phát hiện được trong lịch sử giá
+#[derive(Debug, Clone, PartialEq)]
+pub enum QualityIssueKind {
+    /// Khoảng trống dữ liệu kể từ phiên `previous`
+    Gap { previous: NaiveDate, missing_weekdays: usize },
+    /// Ngày xuất hiện nhiều lần trong file gốc
+    DuplicateDate,
+    /// Phiên có volume = 0
+    ZeroVolume,
+    /// OHLC không nhất quán (high < close, low > open...)
+    OhlcInconsistent { detail: String },
+    /// Log-return bất thường (thường do chia tách / cổ tức chưa điều chỉnh)
+    OutlierReturn { log_return: f64, z_score: f64 },
+}
+
+/// 1 vấn đề tại 1 ngày cụ thể
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityIssue {
+    pub date: NaiveDate,
+    pub kind: QualityIssueKind,
+}
+
+impl fmt::Display for QualityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            QualityIssueKind::Gap { previous, missing_weekdays } => write!(
+                f,
+                "{}: gap of {} trading days since {}",
+                self.date, missing_weekdays, previous
+            ),
+            QualityIssueKind::DuplicateDate => write!(f, "{}: duplicate date", self.date),
+            QualityIssueKind::ZeroVolume => write!(f, "{}: zero volume", self.date),
+            QualityIssueKind::OhlcInconsistent { detail } => {
+                write!(f, "{}: OHLC inconsistent ({})", self.date, detail)
+            }
+            QualityIssueKind::OutlierReturn { log_return, z_score } => write!(
+                f,
+                "{}: outlier return {:+.2}% (z = {:.1})",
+                self.date,
+                log_return * 100.0,
+                z_score
+            ),
+        }
+    }
+}
+
+/// Báo cáo chất lượng dữ liệu của 1 ticker
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataQualityReport {
+    pub ticker: String,
+    pub records: usize,
+    pub first_date: Option<NaiveDate>,
+    pub last_date: Option<NaiveDate>,
+    /// Các vấn đề, sort theo ngày
+    pub issues: Vec<QualityIssue>,
+}
+
+impl DataQualityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn gaps(&self) -> usize {
+        self.count(|kind| matches!(kind, QualityIssueKind::Gap { .. }))
+    }
+
+    pub fn duplicates(&self) -> usize {
+        self.count(|kind| matches!(kind, QualityIssueKind::DuplicateDate))
+    }
+
+    pub fn zero_volume_days(&self) -> usize {
+        self.count(|kind| matches!(kind, QualityIssueKind::ZeroVolume))
+    }
+
+    pub fn ohlc_inconsistencies(&self) -> usize {
+        self.count(|kind| matches!(kind, QualityIssueKind::OhlcInconsistent { .. }))
+    }
+
+    pub fn outlier_returns(&self) -> usize {
+        self.count(|kind| matches!(kind, QualityIssueKind::OutlierReturn { .. }))
+    }
+
+    fn count(&self, predicate: impl Fn(&QualityIssueKind) -> bool) -> usize {
+        self.issues.iter().filter(|issue| predicate(&issue.kind)).count()
+    }
+
+    /// Tóm tắt 1 dòng, ví dụ "AAA: 3 gaps, 0 duplicates, ..."
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {} gaps, {} duplicates, {} zero-volume, {} OHLC errors, {} outliers",
+            self.ticker,
+            self.gaps(),
+            self.duplicates(),
+            self.zero_volume_days(),
+            self.ohlc_inconsistencies(),
+            self.outlier_returns()
+        )
+    }
+}
+
+/// Đếm số ngày T2-T6 nằm giữa 2 ngày (không tính 2 đầu)
+fn weekdays_between(from: NaiveDate, to: NaiveDate) -> usize {
+    from.iter_days()
+        .skip(1)
+        .take_while(|&d| d < to)
+        .filter(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun))
+        .count()
+}
+
+/// Kiểm tra chất lượng lịch sử giá của 1 ticker
+pub fn check_data_quality(series: &PriceSeries, config: &QualityConfig) -> DataQualityReport {
+    let mut issues = Vec::new();
+    let dates = series.dates();
+
+    for &date in series.duplicate_dates() {
+        issues.push(QualityIssue {
+            date,
+            kind: QualityIssueKind::DuplicateDate,
+        });
+    }
+
+    for window in dates.windows(2) {
+        let missing_weekdays = weekdays_between(window[0], window[1]);
+        if missing_weekdays > config.max_missing_weekdays {
+            issues.push(QualityIssue {
+                date: window[1],
+                kind: QualityIssueKind::Gap {
+                    previous: window[0],
+                    missing_weekdays,
+                },
+            });
+        }
+    }
+
+    for i in 0..series.len() {
+        let Some(bar) = series.bar(i) else { continue };
+
+        if bar.volume == 0 {
+            issues.push(QualityIssue {
+                date: bar.date,
+                kind: QualityIssueKind::ZeroVolume,
+            });
+        }
+
+        let upper = 1.0 + config.ohlc_tolerance;
+        let mut problems = Vec::new();
+        if bar.high * upper < bar.low {
+            problems.push(format!("high {} < low {}", bar.high, bar.low));
+        }
+        if bar.high * upper < bar.open.max(bar.close) {
+            problems.push(format!("high {} < open/close {}", bar.high, bar.open.max(bar.close)));
+        }
+        if bar.low > bar.open.min(bar.close) * upper {
+            problems.push(format!("low {} > open/close {}", bar.low, bar.open.min(bar.close)));
+        }
+        if !problems.is_empty() {
+            issues.push(QualityIssue {
+                date: bar.date,
+                kind: QualityIssueKind::OhlcInconsistent {
+                    detail: problems.join(", "),
+                },
+            });
+        }
+    }
+
+    let returns = series.log_returns();
+    if returns.len() >= 2 {
+        let mean = calculate_mean(&returns);
+        let stdev = calculate_stdev(&returns, mean);
+        if stdev > 0.0 {
+            for (i, &log_return) in returns.iter().enumerate() {
+                let z_score = (log_return - mean) / stdev;
+                if z_score.abs() > config.outlier_z_score {
+                    issues.push(QualityIssue {
+                        // returns[i] ứng với phiên i + 1
+                        date: dates[i + 1],
+                        kind: QualityIssueKind::OutlierReturn { log_return, z_score },
+                    });
+                }
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.date);
+
+    DataQualityReport {
+        ticker: series.ticker().to_string(),
+        records: series.len(),
+        first_date: series.first_date(),
+        last_date: series.last_date(),
+        issues,
+    }
+}
+
+impl MarketDataStore {
+    /// Báo cáo chất lượng cho mọi ticker trong store
+    pub fn quality_reports(&self, config: &QualityConfig) -> Vec<DataQualityReport> {
+        self.iter()
+            .map(|series| check_data_quality(series, config))
+            .collect()
+    }
+}
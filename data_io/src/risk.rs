@@ -0,0 +1,250 @@
+use crate::error::{StatsError, StatsResult};
+use crate::statistics::{
+    calculate_excess_kurtosis, calculate_mean, calculate_skewness, calculate_stdev, normal_pdf,
+    normal_quantile, quantile_sorted, QuantileMethod, RunningMoments, StreamingSummary,
+};
+
+/// Các mức tin cậy hiển thị mặc định
+pub const DEFAULT_RISK_LEVELS: [f64; 4] = [0.90, 0.95, 0.99, 0.995];
+
+/// Số điểm chia đuôi khi tính ES bằng trung bình VaR trên các mức α' < α
+const ES_INTEGRATION_STEPS: usize = 1000;
+
+/// Công thức tham số cho VaR / ES
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParametricMethod {
+    /// Giả định giá cuối phân phối chuẩn với mean / std đã cho
+    #[default]
+    Normal,
+    /// Hiệu chỉnh quantile chuẩn theo skewness / kurtosis (Cornish-Fisher)
+    CornishFisher,
+}
+
+impl ParametricMethod {
+    pub const ALL: [ParametricMethod; 2] =
+        [ParametricMethod::Normal, ParametricMethod::CornishFisher];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParametricMethod::Normal => "Normal",
+            ParametricMethod::CornishFisher => "Cornish-Fisher",
+        }
+    }
+}
+
+/// VaR và Expected Shortfall ở 1 mức tin cậy, tính theo lỗ trên 1 cổ phiếu (đơn vị giá)
+///
+/// VaR là mức lỗ chỉ bị vượt với xác suất 1 - confidence; ES (CVaR) là lỗ trung bình
+/// trong các trường hợp vượt VaR, nên luôn ≥ VaR.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiskMeasure {
+    pub confidence: f64,
+    pub var: f64,
+    pub es: f64,
+}
+
+impl RiskMeasure {
+    /// VaR theo % giá ban đầu
+    pub fn var_percent(&self, initial_price: f64) -> f64 {
+        self.var / initial_price * 100.0
+    }
+
+    /// ES theo % giá ban đầu
+    pub fn es_percent(&self, initial_price: f64) -> f64 {
+        self.es / initial_price * 100.0
+    }
+
+    /// VaR của vị thế `shares` cổ phiếu (cùng đơn vị tiền với giá)
+    pub fn var_position(&self, shares: f64) -> f64 {
+        self.var * shares
+    }
+
+    /// ES của vị thế `shares` cổ phiếu
+    pub fn es_position(&self, shares: f64) -> f64 {
+        self.es * shares
+    }
+}
+
+/// Mean, std, skewness và excess kurtosis của giá cuối
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistributionMoments {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub skewness: f64,
+    pub excess_kurtosis: f64,
+}
+
+impl DistributionMoments {
+    /// Tính từ mẫu, cần ít nhất 2 giá trị
+    pub fn from_values(values: &[f64]) -> StatsResult<Self> {
+        if values.len() < 2 {
+            return Err(StatsError::InsufficientData {
+                required: 2,
+                available: values.len(),
+            });
+        }
+        let mean = calculate_mean(values);
+        Ok(DistributionMoments {
+            mean,
+            std_dev: calculate_stdev(values, mean),
+            skewness: calculate_skewness(values),
+            excess_kurtosis: calculate_excess_kurtosis(values),
+        })
+    }
+
+    /// Lấy từ moment tính 1 lượt, None nếu ít hơn 2 giá trị
+    pub fn from_running(moments: &RunningMoments) -> Option<Self> {
+        Some(DistributionMoments {
+            mean: moments.mean()?,
+            std_dev: moments.std_dev()?,
+            skewness: moments.skewness()?,
+            excess_kurtosis: moments.excess_kurtosis()?,
+        })
+    }
+}
+
+fn check_level(confidence: f64) -> StatsResult<()> {
+    if confidence > 0.0 && confidence < 1.0 {
+        Ok(())
+    } else {
+        Err(StatsError::InvalidProbability { p: confidence })
+    }
+}
+
+/// Expected Shortfall từ giá cuối mô phỏng: lỗ trung bình của ⌈n·(1 - confidence)⌉ kịch bản xấu nhất
+pub fn expected_shortfall(final_prices: &[f64], initial_price: f64, confidence: f64) -> f64 {
+    let mut sorted: Vec<f64> = final_prices
+        .iter()
+        .copied()
+        .filter(|p| !p.is_nan())
+        .collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    tail_expected_shortfall(&sorted, initial_price, confidence)
+}
+
+fn tail_expected_shortfall(sorted: &[f64], initial_price: f64, confidence: f64) -> f64 {
+    // Trừ 1e-9 để 100 · (1 - 0.99) = 1.0000000000000009 vẫn là 1 kịch bản
+    let tail = ((sorted.len() as f64 * (1.0 - confidence) - 1e-9).ceil() as usize)
+        .clamp(1, sorted.len());
+    initial_price - calculate_mean(&sorted[..tail])
+}
+
+/// VaR / ES ở từng mức từ giá cuối mô phỏng (sort 1 lần; NaN bị bỏ qua)
+pub fn simulated_risk(
+    final_prices: &[f64],
+    initial_price: f64,
+    levels: &[f64],
+) -> StatsResult<Vec<RiskMeasure>> {
+    let mut sorted: Vec<f64> = final_prices
+        .iter()
+        .copied()
+        .filter(|p| !p.is_nan())
+        .collect();
+    if sorted.is_empty() {
+        return Err(StatsError::Empty);
+    }
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    levels
+        .iter()
+        .map(|&confidence| {
+            check_level(confidence)?;
+            Ok(RiskMeasure {
+                confidence,
+                var: initial_price
+                    - quantile_sorted(&sorted, 1.0 - confidence, QuantileMethod::Type7),
+                es: tail_expected_shortfall(&sorted, initial_price, confidence),
+            })
+        })
+        .collect()
+}
+
+/// VaR / ES từ sketch t-digest khi không giữ toàn bộ giá cuối
+///
+/// ES = trung bình VaR trên các mức α' ∈ (0, α), lấy quantile từ digest.
+pub fn streaming_risk(
+    summary: &StreamingSummary,
+    initial_price: f64,
+    levels: &[f64],
+) -> StatsResult<Vec<RiskMeasure>> {
+    let mut digest = summary.digest().clone();
+    digest.compress();
+
+    levels
+        .iter()
+        .map(|&confidence| {
+            check_level(confidence)?;
+            let alpha = 1.0 - confidence;
+            let var = initial_price - digest.quantile(alpha).ok_or(StatsError::Empty)?;
+            let tail_mean = average_over_tail(alpha, |u| digest.quantile(u).unwrap_or(f64::NAN));
+            Ok(RiskMeasure {
+                confidence,
+                var,
+                es: initial_price - tail_mean,
+            })
+        })
+        .collect()
+}
+
+/// Trung bình của `quantile(u)` trên u ∈ (0, alpha), theo quy tắc điểm giữa
+fn average_over_tail(alpha: f64, quantile: impl Fn(f64) -> f64) -> f64 {
+    let step = alpha / ES_INTEGRATION_STEPS as f64;
+    (0..ES_INTEGRATION_STEPS)
+        .map(|i| quantile((i as f64 + 0.5) * step))
+        .sum::<f64>()
+        / ES_INTEGRATION_STEPS as f64
+}
+
+/// Quantile chuẩn hiệu chỉnh Cornish-Fisher (khai triển bậc 2 theo skewness S và excess kurtosis K)
+///
+/// z_cf = z + (z² - 1)·S/6 + (z³ - 3z)·K/24 - (2z³ - 5z)·S²/36
+pub fn cornish_fisher_quantile(z: f64, skewness: f64, excess_kurtosis: f64) -> f64 {
+    z + (z * z - 1.0) * skewness / 6.0 + (z.powi(3) - 3.0 * z) * excess_kurtosis / 24.0
+        - (2.0 * z.powi(3) - 5.0 * z) * skewness * skewness / 36.0
+}
+
+/// VaR / ES tham số từ 4 moment đầu của giá cuối
+///
+/// Normal: VaR = S₀ - (m + z_α·s), ES = S₀ - (m - s·φ(z_α)/α).
+/// Cornish-Fisher: thay z_α bằng z_cf; ES lấy trung bình VaR trên đuôi.
+pub fn parametric_risk(
+    moments: &DistributionMoments,
+    initial_price: f64,
+    levels: &[f64],
+    method: ParametricMethod,
+) -> StatsResult<Vec<RiskMeasure>> {
+    levels
+        .iter()
+        .map(|&confidence| {
+            check_level(confidence)?;
+            let alpha = 1.0 - confidence;
+            let z = normal_quantile(alpha);
+
+            let (var, es) = match method {
+                ParametricMethod::CornishFisher => {
+                    let price = |u: f64| {
+                        let z_cf = cornish_fisher_quantile(
+                            normal_quantile(u),
+                            moments.skewness,
+                            moments.excess_kurtosis,
+                        );
+                        moments.mean + z_cf * moments.std_dev
+                    };
+                    (
+                        initial_price - price(alpha),
+                        initial_price - average_over_tail(alpha, price),
+                    )
+                }
+                ParametricMethod::Normal => (
+                    initial_price - (moments.mean + z * moments.std_dev),
+                    initial_price - (moments.mean - moments.std_dev * normal_pdf(z) / alpha),
+                ),
+            };
+            Ok(RiskMeasure {
+                confidence,
+                var,
+                es,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,278 @@
+use chrono::NaiveDate;
+use csv::StringRecord;
+
+use crate::error::{DataIoError, DataIoResult};
+use crate::price_series::{PriceBar, CAFEF_DATE_FORMAT};
+
+/// Mô tả layout của 1 file CSV giá: tên cột cho từng trường OHLCV và format ngày
+///
+/// Có sẵn các preset (CafeF, Yahoo, Vietstock, SSI). Với layout khác, tạo bằng
+/// `CsvSchema::custom` rồi chỉnh các trường cần thiết.
+///
+/// Tên cột được so khớp không phân biệt hoa thường và bỏ khoảng trắng 2 đầu.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvSchema {
+    /// Tên profile (hiển thị cho người dùng)
+    pub name: String,
+    /// Cột ticker. `None` → file chỉ chứa 1 mã, lấy từ `default_ticker` hoặc tên file
+    pub ticker_column: Option<String>,
+    pub date_column: String,
+    /// Format ngày theo chrono, ví dụ "%Y%m%d", "%Y-%m-%d", "%d/%m/%Y"
+    pub date_format: String,
+    /// Các cột open/high/low là tùy chọn, nếu thiếu sẽ lấy bằng giá close
+    pub open_column: Option<String>,
+    pub high_column: Option<String>,
+    pub low_column: Option<String>,
+    pub close_column: String,
+    /// Cột volume tùy chọn, nếu thiếu volume = 0
+    pub volume_column: Option<String>,
+    pub delimiter: u8,
+    /// Ticker dùng khi file không có cột ticker
+    pub default_ticker: Option<String>,
+}
+
+/// Vị trí các cột trong 1 file cụ thể (sau khi đối chiếu schema với header)
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnIndex {
+    ticker: Option<usize>,
+    date: usize,
+    open: Option<usize>,
+    high: Option<usize>,
+    low: Option<usize>,
+    close: usize,
+    volume: Option<usize>,
+}
+
+impl CsvSchema {
+    /// Schema tối thiểu: chỉ cần cột ngày và cột giá đóng cửa
+    pub fn custom(name: &str, date_column: &str, date_format: &str, close_column: &str) -> Self {
+        CsvSchema {
+            name: name.to_string(),
+            ticker_column: None,
+            date_column: date_column.to_string(),
+            date_format: date_format.to_string(),
+            open_column: None,
+            high_column: None,
+            low_column: None,
+            close_column: close_column.to_string(),
+            volume_column: None,
+            delimiter: b',',
+            default_ticker: None,
+        }
+    }
+
+    /// Export của CafeF: `<Ticker>,<DTYYYYMMDD>,<Open>,<High>,<Low>,<Close>,<Volume>`
+    pub fn cafef() -> Self {
+        CsvSchema {
+            ticker_column: Some("<Ticker>".to_string()),
+            open_column: Some("<Open>".to_string()),
+            high_column: Some("<High>".to_string()),
+            low_column: Some("<Low>".to_string()),
+            volume_column: Some("<Volume>".to_string()),
+            ..Self::custom("CafeF", "<DTYYYYMMDD>", CAFEF_DATE_FORMAT, "<Close>")
+        }
+    }
+
+    /// Yahoo Finance: `Date,Open,High,Low,Close,Adj Close,Volume` (1 mã / file)
+    ///
+    /// Dùng cột `Close`; đổi `close_column` thành "Adj Close" nếu muốn giá đã điều chỉnh
+    pub fn yahoo() -> Self {
+        CsvSchema {
+            open_column: Some("Open".to_string()),
+            high_column: Some("High".to_string()),
+            low_column: Some("Low".to_string()),
+            volume_column: Some("Volume".to_string()),
+            ..Self::custom("Yahoo", "Date", "%Y-%m-%d", "Close")
+        }
+    }
+
+    /// Vietstock: `StockCode,TradingDate,OpenPrice,HighestPrice,LowestPrice,ClosePrice,TotalVol`
+    pub fn vietstock() -> Self {
+        CsvSchema {
+            ticker_column: Some("StockCode".to_string()),
+            open_column: Some("OpenPrice".to_string()),
+            high_column: Some("HighestPrice".to_string()),
+            low_column: Some("LowestPrice".to_string()),
+            volume_column: Some("TotalVol".to_string()),
+            ..Self::custom("Vietstock", "TradingDate", "%d/%m/%Y", "ClosePrice")
+        }
+    }
+
+    /// SSI FastConnect: `Symbol,TradingDate,Open,High,Low,Close,Volume`
+    pub fn ssi() -> Self {
+        CsvSchema {
+            ticker_column: Some("Symbol".to_string()),
+            open_column: Some("Open".to_string()),
+            high_column: Some("High".to_string()),
+            low_column: Some("Low".to_string()),
+            volume_column: Some("Volume".to_string()),
+            ..Self::custom("SSI", "TradingDate", "%d/%m/%Y", "Close")
+        }
+    }
+
+    /// Các preset có sẵn, theo thứ tự ưu tiên khi tự nhận diện
+    pub fn presets() -> Vec<CsvSchema> {
+        vec![Self::cafef(), Self::ssi(), Self::vietstock(), Self::yahoo()]
+    }
+
+    /// Tự nhận diện schema từ dòng header: preset đầu tiên có đủ mọi cột được khai báo
+    pub fn detect(headers: &StringRecord) -> Option<CsvSchema> {
+        Self::presets()
+            .into_iter()
+            .find(|schema| schema.missing_columns(headers).is_empty())
+    }
+
+    pub fn with_default_ticker(mut self, ticker: &str) -> Self {
+        self.default_ticker = Some(ticker.to_string());
+        self
+    }
+
+    pub fn with_date_format(mut self, date_format: &str) -> Self {
+        self.date_format = date_format.to_string();
+        self
+    }
+
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    fn declared_columns(&self) -> Vec<&str> {
+        let mut columns = vec![self.date_column.as_str(), self.close_column.as_str()];
+        for column in [
+            &self.ticker_column,
+            &self.open_column,
+            &self.high_column,
+            &self.low_column,
+            &self.volume_column,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            columns.push(column.as_str());
+        }
+        columns
+    }
+
+    fn missing_columns(&self, headers: &StringRecord) -> Vec<String> {
+        self.declared_columns()
+            .into_iter()
+            .filter(|column| find_column(headers, column).is_none())
+            .map(|column| column.to_string())
+            .collect()
+    }
+
+    /// Đối chiếu schema với header của file, trả về vị trí từng cột
+    pub fn resolve(&self, path: &str, headers: &StringRecord) -> DataIoResult<ColumnIndex> {
+        let missing = self.missing_columns(headers);
+        if !missing.is_empty() {
+            return Err(DataIoError::BadHeader {
+                path: path.to_string(),
+                message: format!("missing columns {} for {} layout", missing.join(", "), self.name),
+            });
+        }
+
+        let optional = |column: &Option<String>| column.as_deref().and_then(|c| find_column(headers, c));
+
+        Ok(ColumnIndex {
+            ticker: optional(&self.ticker_column),
+            date: find_column(headers, &self.date_column).unwrap_or(0),
+            open: optional(&self.open_column),
+            high: optional(&self.high_column),
+            low: optional(&self.low_column),
+            close: find_column(headers, &self.close_column).unwrap_or(0),
+            volume: optional(&self.volume_column),
+        })
+    }
+
+    /// Parse 1 dòng dữ liệu thành (ticker, bar)
+    ///
+    /// Trả về `Ok(None)` với dòng không có giá (ví dụ "null" trong file Yahoo vào ngày nghỉ)
+    pub fn parse_row(
+        &self,
+        columns: &ColumnIndex,
+        row: &StringRecord,
+        fallback_ticker: &str,
+        line: u64,
+    ) -> DataIoResult<Option<(String, PriceBar)>> {
+        let field = |index: usize| row.get(index).unwrap_or("").trim();
+        let malformed = |message: String| DataIoError::MalformedRow { line, message };
+
+        let close_text = field(columns.close);
+        if close_text.is_empty() || close_text.eq_ignore_ascii_case("null") {
+            return Ok(None);
+        }
+
+        let ticker = match columns.ticker {
+            Some(index) => field(index).to_string(),
+            None => self
+                .default_ticker
+                .clone()
+                .unwrap_or_else(|| fallback_ticker.to_string()),
+        };
+        if ticker.is_empty() {
+            return Err(malformed("empty ticker".to_string()));
+        }
+
+        let date_text = field(columns.date);
+        let date = NaiveDate::parse_from_str(date_text, &self.date_format).map_err(|e| {
+            malformed(format!(
+                "invalid date '{}' (expected {}): {}",
+                date_text, self.date_format, e
+            ))
+        })?;
+
+        let parse_price = |name: &str, index: usize| -> DataIoResult<f64> {
+            let text = field(index);
+            text.parse::<f64>()
+                .map_err(|_| malformed(format!("invalid {} value '{}'", name, text)))
+        };
+
+        let close = parse_price("close", columns.close)?;
+        let open = match columns.open {
+            Some(index) => parse_price("open", index)?,
+            None => close,
+        };
+        let high = match columns.high {
+            Some(index) => parse_price("high", index)?,
+            None => close,
+        };
+        let low = match columns.low {
+            Some(index) => parse_price("low", index)?,
+            None => close,
+        };
+        let volume = match columns.volume {
+            // Một số nguồn ghi volume dạng số thực ("1200.0")
+            Some(index) => parse_price("volume", index)?.round() as i64,
+            None => 0,
+        };
+
+        Ok(Some((
+            ticker,
+            PriceBar {
+                date,
+                open,
+                high,
+                low,
+                close,
+                volume,
+            },
+        )))
+    }
+}
+
+impl Default for CsvSchema {
+    fn default() -> Self {
+        Self::cafef()
+    }
+}
+
+/// Tìm vị trí cột theo tên (không phân biệt hoa thường, bỏ BOM UTF-8 ở cột đầu)
+fn find_column(headers: &StringRecord, name: &str) -> Option<usize> {
+    headers.iter().position(|header| {
+        header
+            .trim_start_matches('\u{feff}')
+            .trim()
+            .eq_ignore_ascii_case(name.trim())
+    })
+}
@@ -0,0 +1,77 @@
+use chrono::NaiveDate;
+
+use crate::csv_loader::stock_info;
+use crate::error::{DataIoError, DataIoResult};
+use crate::exchange::Exchange;
+use crate::market_data::MarketDataStore;
+use crate::price_series::PriceSeries;
+
+/// Nguồn dữ liệu giá chung cho GUI và các bước phân tích
+///
+/// Store trong bộ nhớ (CSV, Parquet...) và database SQLite (feature `sqlite`) cùng cài đặt
+/// trait này, nên dropdown ticker, thông tin mã và chuỗi giá không phụ thuộc backend.
+pub trait PriceSource {
+    /// Danh sách ticker đã sort theo tên
+    fn ticker_list(&self) -> DataIoResult<Vec<String>>;
+
+    /// Sàn niêm yết của ticker (None nếu không rõ)
+    fn ticker_exchange(&self, ticker: &str) -> DataIoResult<Option<Exchange>>;
+
+    /// Toàn bộ lịch sử của ticker, lỗi `UnknownTicker` nếu không có
+    fn price_series(&self, ticker: &str) -> DataIoResult<PriceSeries>;
+
+    /// Lịch sử của ticker trong khoảng ngày [from, to]
+    fn price_range(
+        &self,
+        ticker: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> DataIoResult<PriceSeries> {
+        Ok(self.price_series(ticker)?.slice_by_date(from, to))
+    }
+
+    /// Giá đóng cửa theo thứ tự thời gian (cũ → mới)
+    fn close_prices(&self, ticker: &str) -> DataIoResult<Vec<f64>> {
+        Ok(self.price_series(ticker)?.closes().to_vec())
+    }
+
+    /// Thông tin tóm tắt: (ticker, date range, số records, giá cuối), như `get_stock_info`
+    fn ticker_info(&self, ticker: &str) -> DataIoResult<(String, String, usize, f64)> {
+        stock_info(&self.price_series(ticker)?).ok_or_else(|| DataIoError::UnknownTicker {
+            ticker: ticker.to_string(),
+        })
+    }
+}
+
+impl PriceSource for MarketDataStore {
+    fn ticker_list(&self) -> DataIoResult<Vec<String>> {
+        Ok(self.tickers())
+    }
+
+    fn ticker_exchange(&self, ticker: &str) -> DataIoResult<Option<Exchange>> {
+        Ok(self.exchange(ticker))
+    }
+
+    fn price_series(&self, ticker: &str) -> DataIoResult<PriceSeries> {
+        self.require(ticker).cloned()
+    }
+
+    fn price_range(
+        &self,
+        ticker: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> DataIoResult<PriceSeries> {
+        Ok(self.require(ticker)?.slice_by_date(from, to))
+    }
+
+    fn close_prices(&self, ticker: &str) -> DataIoResult<Vec<f64>> {
+        Ok(self.require(ticker)?.closes().to_vec())
+    }
+
+    fn ticker_info(&self, ticker: &str) -> DataIoResult<(String, String, usize, f64)> {
+        stock_info(self.require(ticker)?).ok_or_else(|| DataIoError::UnknownTicker {
+            ticker: ticker.to_string(),
+        })
+    }
+}
@@ -0,0 +1,350 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeMap;
+
+use crate::error::{DataIoError, DataIoResult};
+use crate::exchange::Exchange;
+use crate::market_data::{for_each_csv_bar, MarketDataStore};
+use crate::price_series::{PriceBar, PriceSeries};
+use crate::schema::CsvSchema;
+use crate::source::PriceSource;
+
+/// Format ngày lưu trong database (TEXT, sort đúng theo thứ tự thời gian)
+const DB_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Số dòng mỗi transaction khi import, giới hạn bộ nhớ của journal
+const IMPORT_BATCH_ROWS: usize = 50_000;
+
+const CREATE_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS tickers (
+        ticker   TEXT PRIMARY KEY,
+        exchange TEXT
+    );
+    CREATE TABLE IF NOT EXISTS prices (
+        ticker TEXT NOT NULL,
+        date   TEXT NOT NULL,
+        open   REAL NOT NULL,
+        high   REAL NOT NULL,
+        low    REAL NOT NULL,
+        close  REAL NOT NULL,
+        volume INTEGER NOT NULL,
+        PRIMARY KEY (ticker, date)
+    ) WITHOUT ROWID;
+";
+
+/// Database giá cục bộ (SQLite, 1 file) cho universe lớn hơn mức tiện dùng với CSV
+///
+/// Bảng `prices` có khóa chính (ticker, date) nên tra lịch sử / khoảng ngày / thông tin mã
+/// đều là truy vấn theo index, không cần load toàn bộ dữ liệu vào bộ nhớ.
+/// Import lại cùng ngày sẽ ghi đè bar cũ (file mới hơn được ưu tiên).
+pub struct PriceDatabase {
+    path: String,
+    conn: Connection,
+}
+
+impl PriceDatabase {
+    /// Mở (hoặc tạo mới) file database và đảm bảo có đủ bảng
+    pub fn open(path: &str) -> DataIoResult<Self> {
+        let conn = Connection::open(path).map_err(|e| db_error(path, e))?;
+        conn.execute_batch(CREATE_SCHEMA)
+            .map_err(|e| db_error(path, e))?;
+        Ok(PriceDatabase {
+            path: path.to_string(),
+            conn,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Import file CSV theo kiểu streaming (đọc từng dòng, ghi theo lô), trả về số dòng đã ghi
+    ///
+    /// Layout tự nhận diện từ header; sàn lấy từ tên file nếu có (CafeF.HNX... → HNX)
+    pub fn import_csv(&mut self, csv_path: &str) -> DataIoResult<usize> {
+        self.import_csv_with(csv_path, None)
+    }
+
+    /// Giống `import_csv` nhưng dùng schema do người dùng chỉ định
+    pub fn import_csv_with_schema(
+        &mut self,
+        csv_path: &str,
+        schema: &CsvSchema,
+    ) -> DataIoResult<usize> {
+        self.import_csv_with(csv_path, Some(schema))
+    }
+
+    fn import_csv_with(
+        &mut self,
+        csv_path: &str,
+        schema: Option<&CsvSchema>,
+    ) -> DataIoResult<usize> {
+        let exchange = Exchange::from_path(csv_path);
+        let mut batch: Vec<(String, PriceBar)> = Vec::with_capacity(IMPORT_BATCH_ROWS);
+        let mut imported = 0;
+
+        for_each_csv_bar(csv_path, schema, |ticker, bar| {
+            bar.validate(&ticker)?;
+            batch.push((ticker, bar));
+            if batch.len() >= IMPORT_BATCH_ROWS {
+                imported += self.write_batch(&batch, exchange)?;
+                batch.clear();
+            }
+            Ok(())
+        })?;
+        imported += self.write_batch(&batch, exchange)?;
+
+        Ok(imported)
+    }
+
+    /// Ghi toàn bộ store trong bộ nhớ vào database (giữ nguyên sàn của từng ticker)
+    pub fn import_store(&mut self, store: &MarketDataStore) -> DataIoResult<usize> {
+        let mut imported = 0;
+        for series in store.iter() {
+            let rows: Vec<(String, PriceBar)> = (0..series.len())
+                .filter_map(|i| series.bar(i))
+                .map(|bar| (series.ticker().to_string(), bar))
+                .collect();
+            imported += self.write_batch(&rows, store.exchange(series.ticker()))?;
+        }
+        Ok(imported)
+    }
+
+    /// Ghi 1 lô dòng trong 1 transaction
+    fn write_batch(
+        &mut self,
+        rows: &[(String, PriceBar)],
+        exchange: Option<Exchange>,
+    ) -> DataIoResult<usize> {
+        let path = self.path.clone();
+        let err = |e: rusqlite::Error| db_error(&path, e);
+
+        let tx = self.conn.transaction().map_err(err)?;
+        {
+            let mut insert_price = tx
+                .prepare_cached(
+                    "INSERT INTO prices (ticker, date, open, high, low, close, volume)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                     ON CONFLICT (ticker, date) DO UPDATE SET
+                        open = excluded.open, high = excluded.high, low = excluded.low,
+                        close = excluded.close, volume = excluded.volume",
+                )
+                .map_err(err)?;
+            // Ticker chưa có sàn thì giữ NULL; có sàn mới thì ghi đè (mã chuyển sàn)
+            let mut insert_ticker = tx
+                .prepare_cached(
+                    "INSERT INTO tickers (ticker, exchange) VALUES (?1, ?2)
+                     ON CONFLICT (ticker) DO UPDATE SET
+                        exchange = COALESCE(excluded.exchange, tickers.exchange)",
+                )
+                .map_err(err)?;
+
+            let mut last_ticker: Option<&str> = None;
+            for (ticker, bar) in rows {
+                if last_ticker != Some(ticker.as_str()) {
+                    insert_ticker
+                        .execute(params![ticker, exchange.map(|e| e.as_str())])
+                        .map_err(err)?;
+                    last_ticker = Some(ticker.as_str());
+                }
+                insert_price
+                    .execute(params![
+                        ticker,
+                        bar.date.format(DB_DATE_FORMAT).to_string(),
+                        bar.open,
+                        bar.high,
+                        bar.low,
+                        bar.close,
+                        bar.volume
+                    ])
+                    .map_err(err)?;
+            }
+        }
+        tx.commit().map_err(err)?;
+
+        Ok(rows.len())
+    }
+
+    /// Đọc các bar của ticker trong khoảng ngày (None → không giới hạn đầu đó)
+    fn query_series(
+        &self,
+        ticker: &str,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> DataIoResult<PriceSeries> {
+        let err = |e: rusqlite::Error| db_error(&self.path, e);
+        let from = from.map(|d| d.format(DB_DATE_FORMAT).to_string());
+        let to = to.map(|d| d.format(DB_DATE_FORMAT).to_string());
+
+        let mut statement = self
+            .conn
+            .prepare_cached(
+                "SELECT date, open, high, low, close, volume FROM prices
+                 WHERE ticker = ?1
+                   AND (?2 IS NULL OR date >= ?2)
+                   AND (?3 IS NULL OR date <= ?3)
+                 ORDER BY date",
+            )
+            .map_err(err)?;
+        let rows = statement
+            .query_map(params![ticker, from, to], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, f64>(1)?,
+                    row.get::<_, f64>(2)?,
+                    row.get::<_, f64>(3)?,
+                    row.get::<_, f64>(4)?,
+                    row.get::<_, i64>(5)?,
+                ))
+            })
+            .map_err(err)?;
+
+        let mut bars = Vec::new();
+        for row in rows {
+            let (date, open, high, low, close, volume) = row.map_err(err)?;
+            let date = parse_db_date(&self.path, &date)?;
+            bars.push(PriceBar {
+                date,
+                open,
+                high,
+                low,
+                close,
+                volume,
+            });
+        }
+
+        PriceSeries::from_bars(ticker, bars)
+    }
+
+    fn require_ticker(&self, ticker: &str) -> DataIoResult<()> {
+        let known = self
+            .conn
+            .query_row("SELECT 1 FROM tickers WHERE ticker = ?1", [ticker], |_| {
+                Ok(())
+            })
+            .optional()
+            .map_err(|e| db_error(&self.path, e))?;
+        known.ok_or_else(|| DataIoError::UnknownTicker {
+            ticker: ticker.to_string(),
+        })
+    }
+
+    /// Load toàn bộ database vào `MarketDataStore` (dùng cho các bước cần mọi ticker trong bộ nhớ)
+    pub fn to_store(&self) -> DataIoResult<MarketDataStore> {
+        let mut bars_by_ticker = BTreeMap::new();
+        for ticker in self.ticker_list()? {
+            let series = self.query_series(&ticker, None, None)?;
+            let bars = (0..series.len())
+                .filter_map(|i| series.bar(i))
+                .collect::<Vec<_>>();
+            bars_by_ticker.insert(ticker, bars);
+        }
+
+        let mut store = MarketDataStore::from_bars(bars_by_ticker)?;
+        for ticker in store.tickers() {
+            if let Some(exchange) = self.ticker_exchange(&ticker)? {
+                store.set_exchange(&ticker, exchange);
+            }
+        }
+        store.set_source_path(&self.path);
+        Ok(store)
+    }
+}
+
+impl PriceSource for PriceDatabase {
+    fn ticker_list(&self) -> DataIoResult<Vec<String>> {
+        let err = |e: rusqlite::Error| db_error(&self.path, e);
+        let mut statement = self
+            .conn
+            .prepare_cached("SELECT ticker FROM tickers ORDER BY ticker")
+            .map_err(err)?;
+        let rows = statement.query_map([], |row| row.get(0)).map_err(err)?;
+        rows.collect::<Result<Vec<String>, _>>().map_err(err)
+    }
+
+    fn ticker_exchange(&self, ticker: &str) -> DataIoResult<Option<Exchange>> {
+        let exchange: Option<Option<String>> = self
+            .conn
+            .query_row(
+                "SELECT exchange FROM tickers WHERE ticker = ?1",
+                [ticker],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| db_error(&self.path, e))?;
+        Ok(exchange.flatten().as_deref().and_then(Exchange::parse))
+    }
+
+    fn price_series(&self, ticker: &str) -> DataIoResult<PriceSeries> {
+        self.require_ticker(ticker)?;
+        self.query_series(ticker, None, None)
+    }
+
+    fn price_range(
+        &self,
+        ticker: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> DataIoResult<PriceSeries> {
+        self.require_ticker(ticker)?;
+        self.query_series(ticker, Some(from), Some(to))
+    }
+
+    fn close_prices(&self, ticker: &str) -> DataIoResult<Vec<f64>> {
+        self.require_ticker(ticker)?;
+        let err = |e: rusqlite::Error| db_error(&self.path, e);
+        let mut statement = self
+            .conn
+            .prepare_cached("SELECT close FROM prices WHERE ticker = ?1 ORDER BY date")
+            .map_err(err)?;
+        let rows = statement
+            .query_map([ticker], |row| row.get(0))
+            .map_err(err)?;
+        rows.collect::<Result<Vec<f64>, _>>().map_err(err)
+    }
+
+    /// Chỉ dùng truy vấn tổng hợp trên index, không đọc cả lịch sử
+    fn ticker_info(&self, ticker: &str) -> DataIoResult<(String, String, usize, f64)> {
+        let err = |e: rusqlite::Error| db_error(&self.path, e);
+        let (first, last, count): (Option<String>, Option<String>, i64) = self
+            .conn
+            .query_row(
+                "SELECT MIN(date), MAX(date), COUNT(*) FROM prices WHERE ticker = ?1",
+                [ticker],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(err)?;
+        let (Some(first), Some(last)) = (first, last) else {
+            return Err(DataIoError::UnknownTicker {
+                ticker: ticker.to_string(),
+            });
+        };
+        let last_price: f64 = self
+            .conn
+            .query_row(
+                "SELECT close FROM prices WHERE ticker = ?1 ORDER BY date DESC LIMIT 1",
+                [ticker],
+                |row| row.get(0),
+            )
+            .map_err(err)?;
+
+        let date_range = format!(
+            "{} to {}",
+            parse_db_date(&self.path, &first)?,
+            parse_db_date(&self.path, &last)?
+        );
+        Ok((ticker.to_string(), date_range, count as usize, last_price))
+    }
+}
+
+fn db_error(path: &str, err: impl ToString) -> DataIoError {
+    DataIoError::Database {
+        path: path.to_string(),
+        message: err.to_string(),
+    }
+}
+
+fn parse_db_date(path: &str, text: &str) -> DataIoResult<NaiveDate> {
+    NaiveDate::parse_from_str(text, DB_DATE_FORMAT)
+        .map_err(|e| db_error(path, format!("invalid date '{}': {}", text, e)))
+}
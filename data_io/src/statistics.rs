@@ -1,3 +1,7 @@
+use std::borrow::Cow;
+
+use crate::error::{StatsError, StatsResult};
+
 pub fn calculate_log_returns(closes: &[f64]) -> Vec<f64> {
     let mut returns = Vec::new();
 
@@ -14,6 +18,7 @@ pub fn calculate_log_returns(closes: &[f64]) -> Vec<f64> {
     returns
 }
 
+/// Bản nhanh không kiểm tra đầu vào: slice rỗng → NaN. Dùng `mean` nếu cần báo lỗi.
 // tinh tong trung binh
 pub fn calculate_mean(returns: &[f64]) -> f64 {
     //let mut sum = 0.0;
@@ -24,55 +29,54 @@ pub fn calculate_mean(returns: &[f64]) -> f64 {
     sum / returns.len() as f64
 }
 
+/// Bản nhanh không kiểm tra đầu vào: ít hơn 2 giá trị → NaN. Dùng `std_dev` nếu cần báo lỗi.
 // tính độ lệch chuẩn σ = √(Σ(xi - μ)² / (n-1))
 pub fn calculate_stdev(returns: &[f64], mean: f64) -> f64 {
     let mut sum_squared_diff = 0.0;
     for r in returns {
-        sum_squared_diff = sum_squared_diff + (r - mean).powf(2.0);
+        sum_squared_diff += (r - mean).powf(2.0);
     }
     let n = returns.len();
     //let variance = sum_squared_diff/(n as f64 - 1.0);
-    let stdev = (sum_squared_diff/(n as f64 - 1.0)).sqrt();
-    
-    stdev
+    (sum_squared_diff/(n as f64 - 1.0)).sqrt()
 }
 
+/// Giá trị nhỏ nhất, bỏ qua NaN (0.0 nếu không có giá trị hợp lệ)
 pub fn find_min(values: &[f64]) -> f64 {
-    if values.is_empty() {
+    let min = values
+        .iter()
+        .copied()
+        .filter(|v| !v.is_nan())
+        .fold(f64::INFINITY, f64::min);
+    if min == f64::INFINITY && !values.contains(&f64::INFINITY) {
         return 0.0;
     }
-    let mut min = values[0];
-    for &value in values {
-        if value < min {
-            min = value;
-        }
-    }
     min
 }
 
+/// Giá trị lớn nhất, bỏ qua NaN (0.0 nếu không có giá trị hợp lệ)
 pub fn find_max(values: &[f64]) -> f64 {
-    if values.is_empty() {
+    let max = values
+        .iter()
+        .copied()
+        .filter(|v| !v.is_nan())
+        .fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY && !values.contains(&f64::NEG_INFINITY) {
         return 0.0;
     }
-    let mut max = values[0];
-    for &value in values {
-        if value > max {
-            max = value;
-        }
-    }
     max
 }
 
+/// Percentile p ∈ [0, 1] có nội suy tuyến tính (Hyndman-Fan type 7, mặc định của R/NumPy)
+///
+/// Bỏ qua NaN; trả về 0.0 nếu không có giá trị hợp lệ. Dùng `quantile` để chọn định nghĩa
+/// khác hoặc nhận lỗi thay vì giá trị mặc định.
 pub fn calculate_percentile(values: &[f64], p: f64) -> f64 {
-    if values.is_empty() {
+    let sorted = sorted_without_nan(values);
+    if sorted.is_empty() {
         return 0.0;
     }
-    let mut sorted = values.to_vec();
-    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
-
-    let index = (p * (sorted.len() - 1) as f64) as usize;
-
-    sorted[index]
+    quantile_sorted(&sorted, p.clamp(0.0, 1.0), QuantileMethod::Type7)
 }
 
 pub fn calculate_var(final_prices: &[f64], initial_price: f64, confidence: f64) -> f64 {
@@ -93,19 +97,1338 @@ pub struct SummaryStats {
     pub max: f64,
 }
 
+/// Thống kê tóm tắt, NaN bị bỏ qua; xem `summary_stats` cho bản báo lỗi với đầu vào suy biến
 pub fn calculate_summary_stats(values: &[f64]) -> SummaryStats {
-    let mean = calculate_mean(values);
-    let std_dev = calculate_stdev(values, mean);
+    let sorted = sorted_without_nan(values);
+    let mean = calculate_mean(&sorted);
+    let std_dev = calculate_stdev(&sorted, mean);
+    let percentile = |p: f64| {
+        if sorted.is_empty() {
+            0.0
+        } else {
+            quantile_sorted(&sorted, p, QuantileMethod::Type7)
+        }
+    };
     
     SummaryStats {
         mean,
         std_dev,
-        median: calculate_percentile(values, 0.5),
-        p5: calculate_percentile(values, 0.05),
-        p25: calculate_percentile(values, 0.25),
-        p75: calculate_percentile(values, 0.75),
-        p95: calculate_percentile(values, 0.95),
-        min: find_min(values),
-        max: find_max(values),
-    }
-}
\ No newline at end of file
+        median: percentile(0.5),
+        p5: percentile(0.05),
+        p25: percentile(0.25),
+        p75: percentile(0.75),
+        p95: percentile(0.95),
+        min: find_min(&sorted),
+        max: find_max(&sorted),
+    }
+}
+
+/// Định nghĩa quantile mẫu theo Hyndman & Fan (1996), đánh số như `type` trong R
+///
+/// Type 1-3 không liên tục (luôn trả về 1 quan sát hoặc trung bình 2 quan sát),
+/// type 4-9 nội suy tuyến tính giữa 2 thống kê thứ tự với vị trí khác nhau.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuantileMethod {
+    /// Nghịch đảo của hàm phân phối thực nghiệm
+    Type1,
+    /// Như type 1 nhưng lấy trung bình tại điểm gián đoạn
+    Type2,
+    /// Thống kê thứ tự chẵn gần nhất (SAS)
+    Type3,
+    /// Nội suy tuyến tính hàm phân phối thực nghiệm, p_k = k / n
+    Type4,
+    /// p_k = (k - 0.5) / n
+    Type5,
+    /// p_k = k / (n + 1) (Minitab, SPSS)
+    Type6,
+    /// p_k = (k - 1) / (n - 1), mặc định của R, NumPy, Excel
+    #[default]
+    Type7,
+    /// p_k = (k - 1/3) / (n + 1/3), xấp xỉ median-unbiased, Hyndman & Fan khuyên dùng
+    Type8,
+    /// p_k = (k - 3/8) / (n + 1/4), xấp xỉ unbiased với phân phối chuẩn
+    Type9,
+}
+
+impl QuantileMethod {
+    pub const ALL: [QuantileMethod; 9] = [
+        QuantileMethod::Type1,
+        QuantileMethod::Type2,
+        QuantileMethod::Type3,
+        QuantileMethod::Type4,
+        QuantileMethod::Type5,
+        QuantileMethod::Type6,
+        QuantileMethod::Type7,
+        QuantileMethod::Type8,
+        QuantileMethod::Type9,
+    ];
+
+    /// Số thứ tự 1-9 theo Hyndman & Fan
+    pub fn number(&self) -> u8 {
+        *self as u8 + 1
+    }
+
+    pub fn from_number(number: u8) -> Option<Self> {
+        QuantileMethod::ALL.get((number as usize).checked_sub(1)?).copied()
+    }
+}
+
+/// Cách xử lý NaN trong dữ liệu đầu vào
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NanPolicy {
+    /// Báo lỗi `StatsError::NanValue` tại NaN đầu tiên
+    #[default]
+    Reject,
+    /// Bỏ qua NaN, tính trên các giá trị còn lại
+    Omit,
+    /// Có NaN thì kết quả là NaN (như phép tính số học thông thường)
+    Propagate,
+}
+
+/// Áp dụng `NanPolicy`: Ok(None) nghĩa là kết quả phải là NaN (Propagate gặp NaN)
+fn apply_nan_policy(values: &[f64], policy: NanPolicy) -> StatsResult<Option<Cow<'_, [f64]>>> {
+    let Some(index) = values.iter().position(|v| v.is_nan()) else {
+        return Ok(Some(Cow::Borrowed(values)));
+    };
+    match policy {
+        NanPolicy::Reject => Err(StatsError::NanValue { index }),
+        NanPolicy::Propagate => Ok(None),
+        NanPolicy::Omit => Ok(Some(Cow::Owned(
+            values.iter().copied().filter(|v| !v.is_nan()).collect(),
+        ))),
+    }
+}
+
+fn sorted_without_nan(values: &[f64]) -> Vec<f64> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted
+}
+
+/// Trung bình có kiểm tra đầu vào: lỗi `Empty` nếu không có giá trị
+pub fn mean(values: &[f64], nan_policy: NanPolicy) -> StatsResult<f64> {
+    let Some(values) = apply_nan_policy(values, nan_policy)? else {
+        return Ok(f64::NAN);
+    };
+    if values.is_empty() {
+        return Err(StatsError::Empty);
+    }
+    Ok(calculate_mean(&values))
+}
+
+/// Phương sai mẫu (chia n - 1), cần ít nhất 2 giá trị
+pub fn variance(values: &[f64], nan_policy: NanPolicy) -> StatsResult<f64> {
+    let Some(values) = apply_nan_policy(values, nan_policy)? else {
+        return Ok(f64::NAN);
+    };
+    if values.len() < 2 {
+        return Err(StatsError::InsufficientData {
+            required: 2,
+            available: values.len(),
+        });
+    }
+    Ok(calculate_stdev(&values, calculate_mean(&values)).powi(2))
+}
+
+/// Độ lệch chuẩn mẫu (chia n - 1), cần ít nhất 2 giá trị
+pub fn std_dev(values: &[f64], nan_policy: NanPolicy) -> StatsResult<f64> {
+    variance(values, nan_policy).map(f64::sqrt)
+}
+
+/// Quantile p của dữ liệu đã sort tăng dần, không có NaN và không rỗng
+pub fn quantile_sorted(sorted: &[f64], p: f64, method: QuantileMethod) -> f64 {
+    let n = sorted.len();
+    // Thống kê thứ tự x_(k) đánh số từ 1, ngoài [1, n] thì lấy giá trị biên
+    let order_stat = |k: f64| sorted[(k.max(1.0) as usize).min(n) - 1];
+
+    // Vị trí h = n·p + m, j = ⌊h⌋, g = h - j (như R, có sai số làm tròn nhỏ)
+    let m = match method {
+        QuantileMethod::Type1 | QuantileMethod::Type2 | QuantileMethod::Type4 => 0.0,
+        QuantileMethod::Type3 => -0.5,
+        QuantileMethod::Type5 => 0.5,
+        QuantileMethod::Type6 => p,
+        QuantileMethod::Type7 => 1.0 - p,
+        QuantileMethod::Type8 => (p + 1.0) / 3.0,
+        QuantileMethod::Type9 => p / 4.0 + 3.0 / 8.0,
+    };
+    let fuzz = 4.0 * f64::EPSILON * (n as f64).max(1.0);
+    let h = n as f64 * p + m;
+    let j = (h + fuzz).floor();
+    let g = if (h - j).abs() < fuzz { 0.0 } else { h - j };
+
+    let gamma = match method {
+        QuantileMethod::Type1 => if g > 0.0 { 1.0 } else { 0.0 },
+        QuantileMethod::Type2 => if g > 0.0 { 1.0 } else { 0.5 },
+        QuantileMethod::Type3 => {
+            if g == 0.0 && (j as i64) % 2 == 0 { 0.0 } else { 1.0 }
+        }
+        _ => g,
+    };
+
+    let low = order_stat(j);
+    let high = order_stat(j + 1.0);
+    if gamma == 0.0 {
+        low
+    } else {
+        (1.0 - gamma) * low + gamma * high
+    }
+}
+
+/// Quantile p ∈ [0, 1] theo định nghĩa `method`
+pub fn quantile(
+    values: &[f64],
+    p: f64,
+    method: QuantileMethod,
+    nan_policy: NanPolicy,
+) -> StatsResult<f64> {
+    quantiles(values, &[p], method, nan_policy).map(|q| q[0])
+}
+
+/// Nhiều quantile cùng lúc (chỉ sort 1 lần), theo thứ tự của `probabilities`
+pub fn quantiles(
+    values: &[f64],
+    probabilities: &[f64],
+    method: QuantileMethod,
+    nan_policy: NanPolicy,
+) -> StatsResult<Vec<f64>> {
+    if let Some(&p) = probabilities.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+        return Err(StatsError::InvalidProbability { p });
+    }
+    let Some(values) = apply_nan_policy(values, nan_policy)? else {
+        return Ok(vec![f64::NAN; probabilities.len()]);
+    };
+    if values.is_empty() {
+        return Err(StatsError::Empty);
+    }
+
+    let mut sorted = values.into_owned();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    Ok(probabilities
+        .iter()
+        .map(|&p| quantile_sorted(&sorted, p, method))
+        .collect())
+}
+
+/// Thống kê tóm tắt có kiểm tra đầu vào (cần ít nhất 2 giá trị cho std)
+pub fn summary_stats(
+    values: &[f64],
+    method: QuantileMethod,
+    nan_policy: NanPolicy,
+) -> StatsResult<SummaryStats> {
+    let Some(values) = apply_nan_policy(values, nan_policy)? else {
+        let nan = f64::NAN;
+        return Ok(SummaryStats {
+            mean: nan,
+            std_dev: nan,
+            median: nan,
+            p5: nan,
+            p25: nan,
+            p75: nan,
+            p95: nan,
+            min: nan,
+            max: nan,
+        });
+    };
+    let std_dev = std_dev(&values, NanPolicy::Reject)?;
+    let q = quantiles(
+        &values,
+        &[0.0, 0.05, 0.25, 0.5, 0.75, 0.95, 1.0],
+        method,
+        NanPolicy::Reject,
+    )?;
+
+    Ok(SummaryStats {
+        mean: calculate_mean(&values),
+        std_dev,
+        median: q[3],
+        p5: q[1],
+        p25: q[2],
+        p75: q[4],
+        p95: q[5],
+        min: q[0],
+        max: q[6],
+    })
+}
+
+/// Cách ước lượng σ theo ngày từ dữ liệu OHLC
+///
+/// Các estimator dựa trên biên độ (range) dùng thêm high/low/open nên hiệu quả hơn
+/// close-to-close trên cửa sổ ngắn. Parkinson, Garman-Klass, Rogers-Satchell bỏ qua
+/// gap qua đêm; Yang-Zhang cộng thêm phần biến động qua đêm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VolatilityEstimator {
+    /// Độ lệch chuẩn của log-returns giá đóng cửa
+    #[default]
+    CloseToClose,
+    Parkinson,
+    GarmanKlass,
+    RogersSatchell,
+    YangZhang,
+}
+
+impl VolatilityEstimator {
+    pub const ALL: [VolatilityEstimator; 5] = [
+        VolatilityEstimator::CloseToClose,
+        VolatilityEstimator::Parkinson,
+        VolatilityEstimator::GarmanKlass,
+        VolatilityEstimator::RogersSatchell,
+        VolatilityEstimator::YangZhang,
+    ];
+
+    /// Tên hiển thị (dùng trong ComboBox của GUI)
+    pub fn name(&self) -> &'static str {
+        match self {
+            VolatilityEstimator::CloseToClose => "Close-to-close",
+            VolatilityEstimator::Parkinson => "Parkinson",
+            VolatilityEstimator::GarmanKlass => "Garman-Klass",
+            VolatilityEstimator::RogersSatchell => "Rogers-Satchell",
+            VolatilityEstimator::YangZhang => "Yang-Zhang",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<VolatilityEstimator> {
+        Self::ALL.into_iter().find(|e| e.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+// Parkinson: σ² = Σ ln(H/L)² / (4 ln2 · n)
+pub fn parkinson_volatility(highs: &[f64], lows: &[f64]) -> f64 {
+    let n = highs.len().min(lows.len());
+    let sum: f64 = highs.iter().zip(lows).map(|(h, l)| (h / l).ln().powi(2)).sum();
+    (sum / (4.0 * std::f64::consts::LN_2 * n as f64)).sqrt()
+}
+
+/// Số phiên dùng được của các slice OHLC (slice dài hơn bị cắt về slice ngắn nhất như Parkinson)
+fn ohlc_len(opens: &[f64], highs: &[f64], lows: &[f64], closes: &[f64]) -> usize {
+    opens.len().min(highs.len()).min(lows.len()).min(closes.len())
+}
+
+/// Duyệt (open, high, low, close) của từng phiên, dừng ở slice ngắn nhất
+fn ohlc_bars<'a>(
+    opens: &'a [f64],
+    highs: &'a [f64],
+    lows: &'a [f64],
+    closes: &'a [f64],
+) -> impl Iterator<Item = (f64, f64, f64, f64)> + 'a {
+    opens
+        .iter()
+        .zip(highs)
+        .zip(lows)
+        .zip(closes)
+        .map(|(((&o, &h), &l), &c)| (o, h, l, c))
+}
+
+// Garman-Klass: σ² = (1/n) Σ [0.5 ln(H/L)² - (2ln2 - 1) ln(C/O)²]
+pub fn garman_klass_volatility(
+    opens: &[f64],
+    highs: &[f64],
+    lows: &[f64],
+    closes: &[f64],
+) -> f64 {
+    let n = ohlc_len(opens, highs, lows, closes);
+    let sum: f64 = ohlc_bars(opens, highs, lows, closes)
+        .map(|(o, h, l, c)| {
+            let hl = (h / l).ln();
+            let co = (c / o).ln();
+            0.5 * hl.powi(2) - (2.0 * std::f64::consts::LN_2 - 1.0) * co.powi(2)
+        })
+        .sum();
+    (sum / n as f64).max(0.0).sqrt()
+}
+
+/// Phương sai Rogers-Satchell của từng phiên: ln(H/C)·ln(H/O) + ln(L/C)·ln(L/O)
+fn rogers_satchell_terms<'a>(
+    opens: &'a [f64],
+    highs: &'a [f64],
+    lows: &'a [f64],
+    closes: &'a [f64],
+) -> impl Iterator<Item = f64> + 'a {
+    ohlc_bars(opens, highs, lows, closes)
+        .map(|(o, h, l, c)| (h / c).ln() * (h / o).ln() + (l / c).ln() * (l / o).ln())
+}
+
+// Rogers-Satchell: không bị lệch khi giá có drift
+pub fn rogers_satchell_volatility(
+    opens: &[f64],
+    highs: &[f64],
+    lows: &[f64],
+    closes: &[f64],
+) -> f64 {
+    let sum: f64 = rogers_satchell_terms(opens, highs, lows, closes).sum();
+    (sum / ohlc_len(opens, highs, lows, closes) as f64).max(0.0).sqrt()
+}
+
+// Yang-Zhang: σ² = σ²_overnight + k·σ²_open-close + (1 - k)·σ²_RS
+// với k = 0.34 / (1.34 + (n+1)/(n-1))
+// Phiên đầu tiên chỉ dùng làm giá đóng cửa tham chiếu nên cần ít nhất 3 phiên
+pub fn yang_zhang_volatility(
+    opens: &[f64],
+    highs: &[f64],
+    lows: &[f64],
+    closes: &[f64],
+) -> f64 {
+    let n = ohlc_len(opens, highs, lows, closes).saturating_sub(1);
+    if n < 2 {
+        return f64::NAN;
+    }
+
+    let overnight: Vec<f64> = (1..=n).map(|i| (opens[i] / closes[i - 1]).ln()).collect();
+    let open_close: Vec<f64> = (1..=n).map(|i| (closes[i] / opens[i]).ln()).collect();
+    let overnight_var = calculate_stdev(&overnight, calculate_mean(&overnight)).powi(2);
+    let open_close_var = calculate_stdev(&open_close, calculate_mean(&open_close)).powi(2);
+    let rs_var = rogers_satchell_terms(&opens[1..], &highs[1..], &lows[1..], &closes[1..])
+        .sum::<f64>()
+        / n as f64;
+
+    let n = n as f64;
+    let k = 0.34 / (1.34 + (n + 1.0) / (n - 1.0));
+    (overnight_var + k * open_close_var + (1.0 - k) * rs_var).max(0.0).sqrt()
+}
+
+/// Ước lượng σ theo ngày bằng estimator được chọn (các slice theo thứ tự thời gian, slice dài
+/// hơn bị cắt về slice ngắn nhất)
+pub fn estimate_volatility(
+    estimator: VolatilityEstimator,
+    opens: &[f64],
+    highs: &[f64],
+    lows: &[f64],
+    closes: &[f64],
+) -> f64 {
+    match estimator {
+        VolatilityEstimator::CloseToClose => {
+            let returns = calculate_log_returns(closes);
+            calculate_stdev(&returns, calculate_mean(&returns))
+        }
+        VolatilityEstimator::Parkinson => parkinson_volatility(highs, lows),
+        VolatilityEstimator::GarmanKlass => garman_klass_volatility(opens, highs, lows, closes),
+        VolatilityEstimator::RogersSatchell => {
+            rogers_satchell_volatility(opens, highs, lows, closes)
+        }
+        VolatilityEstimator::YangZhang => yang_zhang_volatility(opens, highs, lows, closes),
+    }
+}
+
+/// Quantile của phân phối chuẩn tắc N(0,1) (thuật toán Acklam, sai số tương đối ~1e-9)
+pub fn normal_quantile(p: f64) -> f64 {
+    if !(p > 0.0 && p < 1.0) {
+        return if p == 0.0 {
+            f64::NEG_INFINITY
+        } else if p == 1.0 {
+            f64::INFINITY
+        } else {
+            f64::NAN
+        };
+    }
+
+    const A: [f64; 6] = [
+        -3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2,
+        1.38357751867269e2, -3.066479806614716e1, 2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2,
+        6.680131188771972e1, -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838,
+        -2.549732539343734, 4.374664141464968, 2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    // Đuôi trái / phải dùng xấp xỉ theo √(-2 ln p), phần giữa dùng xấp xỉ hữu tỉ theo p - 0.5
+    let tail = |q: f64| {
+        let q = (-2.0 * q.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < P_LOW {
+        tail(p)
+    } else if p > 1.0 - P_LOW {
+        -tail(1.0 - p)
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Hàm phân phối của N(0,1): Φ(x) = erfc(-x/√2) / 2 (sai số tương đối < 1.2e-7)
+pub fn normal_cdf(x: f64) -> f64 {
+    // erfc theo xấp xỉ Chebyshev của Numerical Recipes
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let erfc = t * poly.exp();
+    if x >= 0.0 {
+        1.0 - 0.5 * erfc
+    } else {
+        0.5 * erfc
+    }
+}
+
+/// Mật độ của N(0,1)
+pub fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Sai số chuẩn của μ ước lượng: SE(μ̂) = s / √n
+pub fn mean_standard_error(returns: &[f64]) -> f64 {
+    let n = returns.len() as f64;
+    calculate_stdev(returns, calculate_mean(returns)) / n.sqrt()
+}
+
+/// Sai số chuẩn của σ ước lượng từ n quan sát, xấp xỉ chuẩn: SE(σ̂) ≈ σ̂ / √(2(n-1))
+pub fn stdev_standard_error(sigma: f64, n: usize) -> f64 {
+    sigma / (2.0 * (n as f64 - 1.0)).sqrt()
+}
+
+/// t-statistic của drift (H0: μ = 0): t = μ̂ / SE(μ̂)
+///
+/// |t| < 2 nghĩa là dữ liệu không đủ để khẳng định drift khác 0 ở mức ~95%.
+pub fn drift_t_statistic(returns: &[f64]) -> f64 {
+    calculate_mean(returns) / mean_standard_error(returns)
+}
+
+/// Khoảng tin cậy [low, high] ở mức `confidence`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceInterval {
+    pub low: f64,
+    pub high: f64,
+    pub confidence: f64,
+}
+
+impl ConfidenceInterval {
+    /// Khoảng đối xứng theo xấp xỉ chuẩn: value ± z · SE
+    pub fn normal(value: f64, std_error: f64, confidence: f64) -> Self {
+        let z = normal_quantile(0.5 + confidence / 2.0);
+        ConfidenceInterval {
+            low: value - z * std_error,
+            high: value + z * std_error,
+            confidence,
+        }
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.low <= value && value <= self.high
+    }
+}
+
+/// Ước lượng điểm kèm sai số chuẩn và khoảng tin cậy bootstrap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParameterEstimate {
+    pub value: f64,
+    pub std_error: f64,
+    pub interval: ConfidenceInterval,
+}
+
+/// Kết quả suy diễn cho μ/σ của log-returns
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftVolatilityInference {
+    /// Số returns dùng để ước lượng
+    pub sample_size: usize,
+    pub mu: ParameterEstimate,
+    pub sigma: ParameterEstimate,
+    /// t-statistic của drift (H0: μ = 0)
+    pub t_stat: f64,
+}
+
+/// Khoảng tin cậy bootstrap (percentile) cho μ và σ
+///
+/// Lấy mẫu lại có hoàn lại `resamples` lần từ returns, tính μ/σ trên mỗi mẫu rồi lấy
+/// percentile (1-c)/2 và (1+c)/2. Cùng `seed` → cùng kết quả. Cần ít nhất 2 returns,
+/// ít nhất 1 lần lấy mẫu lại và `confidence` ∈ (0, 1).
+pub fn bootstrap_confidence_intervals(
+    returns: &[f64],
+    confidence: f64,
+    resamples: usize,
+    seed: u64,
+) -> StatsResult<(ConfidenceInterval, ConfidenceInterval)> {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    if returns.len() < 2 {
+        return Err(StatsError::InsufficientData {
+            required: 2,
+            available: returns.len(),
+        });
+    }
+    if resamples == 0 {
+        return Err(StatsError::InsufficientData {
+            required: 1,
+            available: 0,
+        });
+    }
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(StatsError::InvalidProbability { p: confidence });
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut means = Vec::with_capacity(resamples);
+    let mut stdevs = Vec::with_capacity(resamples);
+    let mut sample = vec![0.0; returns.len()];
+    for _ in 0..resamples {
+        for value in sample.iter_mut() {
+            *value = returns[rng.gen_range(0..returns.len())];
+        }
+        let mean = calculate_mean(&sample);
+        means.push(mean);
+        stdevs.push(calculate_stdev(&sample, mean));
+    }
+
+    let interval = |values: &[f64]| ConfidenceInterval {
+        low: calculate_percentile(values, (1.0 - confidence) / 2.0),
+        high: calculate_percentile(values, (1.0 + confidence) / 2.0),
+        confidence,
+    };
+    Ok((interval(&means), interval(&stdevs)))
+}
+
+/// μ/σ của log-returns kèm sai số chuẩn, khoảng tin cậy bootstrap và t-statistic của drift
+///
+/// Trả về None nếu có ít hơn 2 returns hoặc `resamples` / `confidence` không hợp lệ
+/// (xem `bootstrap_confidence_intervals`).
+pub fn infer_drift_volatility(
+    returns: &[f64],
+    confidence: f64,
+    resamples: usize,
+    seed: u64,
+) -> Option<DriftVolatilityInference> {
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mu = calculate_mean(returns);
+    let sigma = calculate_stdev(returns, mu);
+    let (mu_interval, sigma_interval) =
+        bootstrap_confidence_intervals(returns, confidence, resamples, seed).ok()?;
+
+    Some(DriftVolatilityInference {
+        sample_size: returns.len(),
+        mu: ParameterEstimate {
+            value: mu,
+            std_error: mean_standard_error(returns),
+            interval: mu_interval,
+        },
+        sigma: ParameterEstimate {
+            value: sigma,
+            std_error: stdev_standard_error(sigma, returns.len()),
+            interval: sigma_interval,
+        },
+        t_stat: drift_t_statistic(returns),
+    })
+}
+
+/// Moment trung tâm bậc `order` (chia n)
+fn central_moment(values: &[f64], mean: f64, order: i32) -> f64 {
+    values.iter().map(|x| (x - mean).powi(order)).sum::<f64>() / values.len() as f64
+}
+
+/// Độ lệch (skewness) g1 = m3 / m2^1.5; 0 với phân phối đối xứng
+pub fn calculate_skewness(values: &[f64]) -> f64 {
+    let mean = calculate_mean(values);
+    central_moment(values, mean, 3) / central_moment(values, mean, 2).powf(1.5)
+}
+
+/// Độ nhọn vượt (excess kurtosis) m4 / m2² - 3; 0 với phân phối chuẩn, > 0 là đuôi dày
+pub fn calculate_excess_kurtosis(values: &[f64]) -> f64 {
+    let mean = calculate_mean(values);
+    central_moment(values, mean, 4) / central_moment(values, mean, 2).powi(2) - 3.0
+}
+
+/// Kết quả 1 kiểm định thống kê
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+impl TestResult {
+    /// Bác bỏ H0 ở mức ý nghĩa `alpha` (vd 0.05)
+    pub fn rejects(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// ln Γ(x) cho x > 0 (xấp xỉ Lanczos, g = 7)
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9, 676.520_368_121_885_1, -1_259.139_216_722_402_8,
+        771.323_428_777_653_1, -176.615_029_162_140_6, 12.507_343_278_686_905,
+        -0.138_571_095_265_720_12, 9.984_369_578_019_572e-6, 1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Công thức phản xạ Γ(x)Γ(1-x) = π / sin(πx)
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (i, &c) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Hàm gamma không đầy đủ trên đã chuẩn hóa Q(a, x) = Γ(a, x) / Γ(a)
+fn upper_incomplete_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefix = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        // Chuỗi của P(a, x), hội tụ nhanh khi x nhỏ
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut n = a;
+        for _ in 0..500 {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        (1.0 - sum * prefix).max(0.0)
+    } else {
+        // Liên phân số của Q(a, x) (thuật toán Lentz)
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        prefix * h
+    }
+}
+
+/// P(X > x) với X ~ χ²(dof)
+pub fn chi_squared_sf(x: f64, dof: f64) -> f64 {
+    upper_incomplete_gamma(dof / 2.0, x / 2.0)
+}
+
+/// Kiểm định Jarque-Bera (H0: phân phối chuẩn): JB = n/6 · (S² + K²/4) ~ χ²(2)
+pub fn jarque_bera(values: &[f64]) -> TestResult {
+    let n = values.len() as f64;
+    let skewness = calculate_skewness(values);
+    let kurtosis = calculate_excess_kurtosis(values);
+    let statistic = n / 6.0 * (skewness.powi(2) + kurtosis.powi(2) / 4.0);
+    TestResult {
+        statistic,
+        p_value: chi_squared_sf(statistic, 2.0),
+    }
+}
+
+/// Tự tương quan mẫu ở độ trễ `lag`, NaN nếu `lag` ≥ số giá trị
+pub fn autocorrelation(values: &[f64], lag: usize) -> f64 {
+    if lag >= values.len() {
+        return f64::NAN;
+    }
+    let mean = calculate_mean(values);
+    let variance: f64 = values.iter().map(|x| (x - mean).powi(2)).sum();
+    let covariance: f64 = values
+        .iter()
+        .zip(&values[lag..])
+        .map(|(a, b)| (a - mean) * (b - mean))
+        .sum();
+    covariance / variance
+}
+
+/// Kiểm định Ljung-Box (H0: không tự tương quan tới độ trễ `lags`)
+///
+/// Q = n(n+2) Σ ρ²_k / (n-k) ~ χ²(lags). Trên returns: kiểm tra tính độc lập mà
+/// Bootstrap i.i.d. giả định; trên returns bình phương: volatility clustering.
+/// Thống kê và p-value là NaN (không bác bỏ) nếu `lags` ≥ số giá trị.
+pub fn ljung_box(values: &[f64], lags: usize) -> TestResult {
+    if lags >= values.len() {
+        return TestResult {
+            statistic: f64::NAN,
+            p_value: f64::NAN,
+        };
+    }
+    let n = values.len() as f64;
+    let statistic = n
+        * (n + 2.0)
+        * (1..=lags)
+            .map(|k| autocorrelation(values, k).powi(2) / (n - k as f64))
+            .sum::<f64>();
+    TestResult {
+        statistic,
+        p_value: chi_squared_sf(statistic, lags as f64),
+    }
+}
+
+/// Giải hệ A·x = b bằng khử Gauss có chọn pivot, None nếu A suy biến
+fn solve_linear(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let (pivot_rows, rest) = a.split_at_mut(col + 1);
+        let pivot_row = &pivot_rows[col];
+        for (offset, target) in rest.iter_mut().enumerate() {
+            let factor = target[col] / pivot_row[col];
+            for (value, pivot_value) in target[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot_value;
+            }
+            b[col + 1 + offset] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let tail: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - tail) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Kết quả kiểm định Augmented Dickey-Fuller (có hằng số, không có trend)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdfResult {
+    /// t-statistic của hệ số y_{t-1}
+    pub statistic: f64,
+    /// Số độ trễ Δy đưa vào hồi quy
+    pub lags: usize,
+    /// Số quan sát dùng trong hồi quy
+    pub n_obs: usize,
+    /// Giá trị tới hạn MacKinnon (2010) ở mức 1%, 5%, 10%
+    pub critical_values: [(f64, f64); 3],
+}
+
+impl AdfResult {
+    /// Bác bỏ H0 (có unit root) ở mức `alpha` ∈ {0.01, 0.05, 0.10} → chuỗi dừng
+    pub fn rejects_unit_root(&self, alpha: f64) -> bool {
+        self.critical_values
+            .iter()
+            .find(|(level, _)| (level - alpha).abs() < 1e-9)
+            .is_some_and(|&(_, critical)| self.statistic < critical)
+    }
+}
+
+/// Kiểm định ADF (H0: chuỗi có unit root)
+///
+/// Hồi quy Δy_t = α + β·y_{t-1} + Σ γ_i·Δy_{t-i} + ε_t, thống kê là t của β.
+/// `lags = None` dùng quy tắc Schwert ⌊12·(n/100)^¼⌋. None nếu quá ít dữ liệu.
+/// Với giá: log giá thường không bác bỏ được H0, còn log-returns thì có.
+pub fn adf_test(values: &[f64], lags: Option<usize>) -> Option<AdfResult> {
+    let lags = lags.unwrap_or_else(|| (12.0 * (values.len() as f64 / 100.0).powf(0.25)) as usize);
+    let diffs: Vec<f64> = values.windows(2).map(|w| w[1] - w[0]).collect();
+    // Tham số: hằng số, y_{t-1} và `lags` độ trễ
+    let k = lags + 2;
+    if diffs.len() < lags + k + 2 {
+        return None;
+    }
+
+    let rows: Vec<(Vec<f64>, f64)> = (lags..diffs.len())
+        .map(|t| {
+            let mut x = Vec::with_capacity(k);
+            x.push(1.0);
+            x.push(values[t]);
+            x.extend((1..=lags).map(|i| diffs[t - i]));
+            (x, diffs[t])
+        })
+        .collect();
+
+    let mut xtx = vec![vec![0.0; k]; k];
+    let mut xty = vec![0.0; k];
+    for (x, y) in &rows {
+        for i in 0..k {
+            xty[i] += x[i] * y;
+            for j in 0..k {
+                xtx[i][j] += x[i] * x[j];
+            }
+        }
+    }
+
+    let beta = solve_linear(xtx.clone(), xty)?;
+    let rss: f64 = rows
+        .iter()
+        .map(|(x, y)| y - x.iter().zip(&beta).map(|(a, b)| a * b).sum::<f64>())
+        .map(|e| e * e)
+        .sum();
+    let n_obs = rows.len();
+    let s2 = rss / (n_obs - k) as f64;
+
+    // Var(β̂_1) = s² · [(X'X)^-1]_11
+    let mut unit = vec![0.0; k];
+    unit[1] = 1.0;
+    let inverse_column = solve_linear(xtx, unit)?;
+    let statistic = beta[1] / (s2 * inverse_column[1]).sqrt();
+
+    // MacKinnon (2010), hồi quy có hằng số: c = β∞ + β1/T + β2/T²
+    let t = n_obs as f64;
+    let critical = |b: [f64; 3]| b[0] + b[1] / t + b[2] / (t * t);
+    Some(AdfResult {
+        statistic,
+        lags,
+        n_obs,
+        critical_values: [
+            (0.01, critical([-3.43035, -6.5393, -16.786])),
+            (0.05, critical([-2.86154, -2.8903, -4.234])),
+            (0.10, critical([-2.56677, -1.5384, -2.809])),
+        ],
+    })
+}
+
+/// Chẩn đoán phân phối của log-returns lịch sử
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnDiagnostics {
+    pub n: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub skewness: f64,
+    pub excess_kurtosis: f64,
+    pub jarque_bera: TestResult,
+    /// Số độ trễ dùng cho Ljung-Box
+    pub ljung_box_lags: usize,
+    pub ljung_box: TestResult,
+    /// Ljung-Box trên returns bình phương (volatility clustering)
+    pub ljung_box_squared: TestResult,
+    /// ADF trên log giá
+    pub adf_log_prices: Option<AdfResult>,
+    /// ADF trên log-returns
+    pub adf_returns: Option<AdfResult>,
+}
+
+/// Chẩn đoán returns từ giá đóng cửa (theo thứ tự thời gian), None nếu ít hơn `lags` + 3 giá
+pub fn diagnose_returns(closes: &[f64], ljung_box_lags: usize) -> Option<ReturnDiagnostics> {
+    let returns = calculate_log_returns(closes);
+    if ljung_box_lags == 0 || returns.len() < ljung_box_lags + 2 {
+        return None;
+    }
+
+    let mean = calculate_mean(&returns);
+    let squared: Vec<f64> = returns.iter().map(|r| r * r).collect();
+    let log_prices: Vec<f64> = closes.iter().map(|p| p.ln()).collect();
+
+    Some(ReturnDiagnostics {
+        n: returns.len(),
+        mean,
+        std_dev: calculate_stdev(&returns, mean),
+        skewness: calculate_skewness(&returns),
+        excess_kurtosis: calculate_excess_kurtosis(&returns),
+        jarque_bera: jarque_bera(&returns),
+        ljung_box_lags,
+        ljung_box: ljung_box(&returns, ljung_box_lags),
+        ljung_box_squared: ljung_box(&squared, ljung_box_lags),
+        adf_log_prices: adf_test(&log_prices, None),
+        adf_returns: adf_test(&returns, None),
+    })
+}
+
+/// Điểm QQ-plot so với phân phối chuẩn: (quantile lý thuyết, returns đã chuẩn hóa và sort)
+///
+/// Plotting position (i - 0.5) / n; điểm nằm trên đường y = x nếu returns chuẩn.
+pub fn normal_qq_points(values: &[f64]) -> Vec<(f64, f64)> {
+    let mean = calculate_mean(values);
+    let std_dev = calculate_stdev(values, mean);
+    let mut standardized: Vec<f64> = values.iter().map(|x| (x - mean) / std_dev).collect();
+    standardized.sort_by(|a, b| a.total_cmp(b));
+
+    let n = standardized.len() as f64;
+    standardized
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| (normal_quantile((i as f64 + 0.5) / n), x))
+        .collect()
+}
+
+/// Mean / phương sai / skewness / kurtosis / min / max tính 1 lượt theo Welford, gộp được giữa các chunk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunningMoments {
+    count: u64,
+    mean: f64,
+    /// Σ (x - mean)², Σ (x - mean)³, Σ (x - mean)⁴
+    m2: f64,
+    m3: f64,
+    m4: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for RunningMoments {
+    fn default() -> Self {
+        RunningMoments {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            m3: 0.0,
+            m4: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl RunningMoments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cập nhật theo Welford, mở rộng cho moment bậc 3, 4 (Pébay)
+    pub fn push(&mut self, x: f64) {
+        let n1 = self.count as f64;
+        self.count += 1;
+        let n = self.count as f64;
+        let delta = x - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term = delta * delta_n * n1;
+
+        self.mean += delta_n;
+        self.m4 += term * delta_n2 * (n * n - 3.0 * n + 3.0) + 6.0 * delta_n2 * self.m2
+            - 4.0 * delta_n * self.m3;
+        self.m3 += term * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term;
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+    }
+
+    /// Gộp kết quả của chunk khác (Chan et al.), như thể đã push cùng các giá trị
+    pub fn merge(&mut self, other: &RunningMoments) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let (na, nb) = (self.count as f64, other.count as f64);
+        let n = na + nb;
+        let delta = other.mean - self.mean;
+        let delta2 = delta * delta;
+
+        let m2 = self.m2 + other.m2 + delta2 * na * nb / n;
+        let m3 = self.m3
+            + other.m3
+            + delta2 * delta * na * nb * (na - nb) / (n * n)
+            + 3.0 * delta * (na * other.m2 - nb * self.m2) / n;
+        let m4 = self.m4
+            + other.m4
+            + delta2 * delta2 * na * nb * (na * na - na * nb + nb * nb) / (n * n * n)
+            + 6.0 * delta2 * (na * na * other.m2 + nb * nb * self.m2) / (n * n)
+            + 4.0 * delta * (na * other.m3 - nb * self.m3) / n;
+
+        self.mean += delta * nb / n;
+        self.m2 = m2;
+        self.m3 = m3;
+        self.m4 = m4;
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// None nếu chưa có giá trị
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// Phương sai mẫu (chia n - 1), None nếu ít hơn 2 giá trị
+    pub fn variance(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.m2 / (self.count - 1) as f64)
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    /// Skewness m3 / m2^1.5 như `calculate_skewness`, None nếu ít hơn 2 giá trị
+    pub fn skewness(&self) -> Option<f64> {
+        (self.count > 1).then(|| (self.count as f64).sqrt() * self.m3 / self.m2.powf(1.5))
+    }
+
+    /// Excess kurtosis m4 / m2² - 3 như `calculate_excess_kurtosis`, None nếu ít hơn 2 giá trị
+    pub fn excess_kurtosis(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.count as f64 * self.m4 / (self.m2 * self.m2) - 3.0)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+}
+
+/// Centroid của t-digest: trung bình và số giá trị đã gộp
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// Compression mặc định: ~100 centroid, sai số quantile cỡ 0.1% rank ở giữa, nhỏ hơn ở 2 đuôi
+pub const DEFAULT_TDIGEST_COMPRESSION: f64 = 100.0;
+
+/// Sketch quantile t-digest dạng merging (Dunning & Ertl)
+///
+/// Giữ tối đa ~compression centroid thay vì mọi giá trị; centroid gần 2 đuôi nhỏ hơn nên
+/// quantile ở đuôi (P1, P5, P95...) chính xác hơn ở giữa. Giá trị mới được gom vào buffer
+/// rồi nén theo lô; 2 digest gộp được với nhau nên mỗi chunk rayon có thể dùng 1 digest riêng.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<f64>,
+    /// Tổng weight của các centroid (không tính buffer)
+    weight: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        TDigest::new(DEFAULT_TDIGEST_COMPRESSION)
+    }
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        TDigest {
+            compression: compression.max(10.0),
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            weight: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Thêm 1 giá trị (NaN bị bỏ qua)
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            return;
+        }
+        self.buffer.push(x);
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        if self.buffer.len() >= 5 * self.compression as usize {
+            self.compress();
+        }
+    }
+
+    /// Gộp digest khác vào digest này
+    pub fn merge(&mut self, other: &TDigest) {
+        self.centroids.extend_from_slice(&other.centroids);
+        self.weight += other.weight;
+        self.buffer.extend_from_slice(&other.buffer);
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.compress();
+    }
+
+    /// Số giá trị đã thêm
+    pub fn count(&self) -> u64 {
+        (self.weight + self.buffer.len() as f64).round() as u64
+    }
+
+    /// Số centroid sau lần nén gần nhất
+    pub fn centroid_count(&self) -> usize {
+        self.centroids.len()
+    }
+
+    /// Hàm scale k₁(q) = δ/(2π) · asin(2q - 1): mỗi centroid chiếm tối đa 1 đơn vị k
+    fn scale(&self, q: f64) -> f64 {
+        self.compression / (2.0 * std::f64::consts::PI) * (2.0 * q - 1.0).clamp(-1.0, 1.0).asin()
+    }
+
+    /// Gộp buffer vào các centroid
+    pub fn compress(&mut self) {
+        if self.buffer.is_empty() && self.centroids.len() <= self.compression as usize {
+            return;
+        }
+
+        let mut items = std::mem::take(&mut self.centroids);
+        items.extend(self.buffer.drain(..).map(|x| Centroid { mean: x, weight: 1.0 }));
+        items.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total: f64 = items.iter().map(|c| c.weight).sum();
+
+        let mut merged = Vec::with_capacity(self.compression as usize * 2);
+        let mut current = items[0];
+        let mut weight_before = 0.0;
+        for item in &items[1..] {
+            let q_left = weight_before / total;
+            let q_right = (weight_before + current.weight + item.weight) / total;
+            if self.scale(q_right) - self.scale(q_left) <= 1.0 {
+                let weight = current.weight + item.weight;
+                current.mean += (item.mean - current.mean) * item.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_before += current.weight;
+                merged.push(current);
+                current = *item;
+            }
+        }
+        merged.push(current);
+
+        self.centroids = merged;
+        self.weight = total;
+    }
+
+    /// Quantile p ∈ [0, 1] ước lượng từ sketch, None nếu chưa có giá trị
+    ///
+    /// Nội suy tuyến tính giữa tâm các centroid; 2 đầu nội suy tới min / max thật.
+    pub fn quantile(&self, p: f64) -> Option<f64> {
+        if !self.buffer.is_empty() {
+            let mut compressed = self.clone();
+            compressed.compress();
+            return compressed.quantile(p);
+        }
+        if self.centroids.is_empty() || !(0.0..=1.0).contains(&p) {
+            return None;
+        }
+        if p == 0.0 {
+            return Some(self.min);
+        }
+        if p == 1.0 {
+            return Some(self.max);
+        }
+
+        let target = p * self.weight;
+        let first = self.centroids[0];
+        if target < first.weight / 2.0 {
+            return Some(self.min + (first.mean - self.min) * target / (first.weight / 2.0));
+        }
+
+        // Tâm centroid i nằm ở weight tích lũy Σ_{j<i} w_j + w_i / 2
+        let mut center = first.weight / 2.0;
+        for pair in self.centroids.windows(2) {
+            let next_center = center + (pair[0].weight + pair[1].weight) / 2.0;
+            if target < next_center {
+                let t = (target - center) / (next_center - center);
+                return Some(pair[0].mean + (pair[1].mean - pair[0].mean) * t);
+            }
+            center = next_center;
+        }
+
+        let last = self.centroids[self.centroids.len() - 1];
+        let t = (target - center) / (self.weight - center);
+        Some(last.mean + (self.max - last.mean) * t.clamp(0.0, 1.0))
+    }
+}
+
+/// Thống kê tóm tắt tính 1 lượt: Welford cho moment, t-digest cho quantile
+///
+/// Mỗi chunk (vd 1 task rayon) dùng 1 instance riêng rồi `merge`; không cần giữ hay sort
+/// toàn bộ giá trị. NaN bị bỏ qua và đếm riêng.
+#[derive(Debug, Clone, Default)]
+pub struct StreamingSummary {
+    moments: RunningMoments,
+    digest: TDigest,
+    nan_count: u64,
+}
+
+impl StreamingSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_compression(compression: f64) -> Self {
+        StreamingSummary {
+            digest: TDigest::new(compression),
+            ..Self::default()
+        }
+    }
+
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            self.nan_count += 1;
+            return;
+        }
+        self.moments.push(x);
+        self.digest.push(x);
+    }
+
+    /// Gộp 2 summary, dạng dùng được trực tiếp cho `reduce`
+    pub fn merge(mut self, other: StreamingSummary) -> StreamingSummary {
+        self.moments.merge(&other.moments);
+        self.digest.merge(&other.digest);
+        self.nan_count += other.nan_count;
+        self
+    }
+
+    pub fn moments(&self) -> &RunningMoments {
+        &self.moments
+    }
+
+    pub fn digest(&self) -> &TDigest {
+        &self.digest
+    }
+
+    pub fn count(&self) -> u64 {
+        self.moments.count()
+    }
+
+    pub fn nan_count(&self) -> u64 {
+        self.nan_count
+    }
+
+    /// Quantile xấp xỉ theo t-digest
+    pub fn quantile(&self, p: f64) -> StatsResult<f64> {
+        if !(0.0..=1.0).contains(&p) {
+            return Err(StatsError::InvalidProbability { p });
+        }
+        self.digest.quantile(p).ok_or(StatsError::Empty)
+    }
+
+    /// Cùng các trường như `summary_stats`; percentile là xấp xỉ, min / max / mean / std chính xác
+    pub fn summary(&self) -> StatsResult<SummaryStats> {
+        let std_dev = self.moments.std_dev().ok_or(StatsError::InsufficientData {
+            required: 2,
+            available: self.count() as usize,
+        })?;
+        let mut digest = self.digest.clone();
+        digest.compress();
+        let q = |p: f64| digest.quantile(p).unwrap_or(f64::NAN);
+
+        Ok(SummaryStats {
+            mean: self.moments.mean,
+            std_dev,
+            median: q(0.5),
+            p5: q(0.05),
+            p25: q(0.25),
+            p75: q(0.75),
+            p95: q(0.95),
+            min: self.moments.min,
+            max: self.moments.max,
+        })
+    }
+}
@@ -1,5 +1,6 @@
 use serde::Deserialize;
 
+/// Dòng dữ liệu thô theo đúng layout CafeF (các layout khác xem `CsvSchema`)
 #[derive(Debug, Deserialize)]
 pub struct StockPrice {
     #[serde(rename = "<Ticker>")]